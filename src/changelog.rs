@@ -0,0 +1,231 @@
+use crate::consts::CHANGELOG_MARKER;
+use crate::error::CtGenError;
+use crate::manifest::CtGenManifest;
+use crate::CtGen;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Generated file of a changelog entry
+pub struct CtGenChangelogFile {
+    /// File path relative to the target dir
+    file: String,
+    /// SHA-256 hash of the file content after writing and formatting
+    hash: String,
+}
+
+impl CtGenChangelogFile {
+    /// File path relative to the target dir
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+    /// SHA-256 hash of the file content after writing and formatting
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Generation log entry of a successful run
+pub struct CtGenChangelogEntry {
+    /// Short hash of the generated files and their contents, the same for runs generating identical output
+    id: String,
+    /// Run time, RFC3339
+    date: String,
+    /// Profile name
+    profile: String,
+    /// Table name, if the run had one
+    table: Option<String>,
+    /// Generated files, sorted by path
+    files: Vec<CtGenChangelogFile>,
+}
+
+impl CtGenChangelogEntry {
+    /// Create a new entry from generated file paths and content hashes
+    pub fn new(date: &str, profile: &str, table: Option<&str>, files: Vec<(String, String)>) -> Self {
+        let mut files = files
+            .into_iter()
+            .map(|(file, hash)| CtGenChangelogFile { file, hash })
+            .collect::<Vec<CtGenChangelogFile>>();
+        files.sort_by(|a, b| a.file.cmp(&b.file));
+
+        let digest = files
+            .iter()
+            .map(|file| format!("{} {}\n", file.hash, file.file))
+            .collect::<String>();
+
+        Self {
+            id: CtGenManifest::hash(digest.as_bytes())[..12].to_string(),
+            date: date.to_string(),
+            profile: profile.to_string(),
+            table: table.map(str::to_string),
+            files,
+        }
+    }
+
+    /// Short hash of the generated files and their contents, the same for runs generating identical output
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    /// Run time, RFC3339
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+    /// Profile name
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+    /// Table name, if the run had one
+    pub fn table(&self) -> Option<&str> {
+        self.table.as_deref()
+    }
+    /// Generated files, sorted by path
+    pub fn files(&self) -> &Vec<CtGenChangelogFile> {
+        &self.files
+    }
+
+    /// Markdown section of the entry, with a marker line to recognize it by
+    fn to_markdown(&self) -> String {
+        let mut markdown = format!("## {} {}", self.date, self.profile);
+
+        if let Some(table) = self.table.as_deref() {
+            markdown.push_str(&format!(" `{}`", table));
+        }
+
+        markdown.push_str(&format!(
+            "\n\n<!-- {} profile={} table={} id={} -->\n\n",
+            CHANGELOG_MARKER,
+            escape_marker_value(&self.profile),
+            escape_marker_value(self.table.as_deref().unwrap_or_default()),
+            self.id
+        ));
+
+        for file in self.files.iter() {
+            markdown.push_str(&format!("- `{}`\n", file.file));
+        }
+
+        markdown
+    }
+}
+
+/// Percent-encode the characters that would end a marker field or the marker comment, like spaces in table names
+fn escape_marker_value(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '%' | '=' | '>' | '<' => format!("%{:02X}", c as u32),
+            c if c.is_whitespace() => c.to_string().bytes().map(|b| format!("%{:02X}", b)).collect(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Decode a percent-encoded marker field. Entries logged before values were encoded decode unchanged
+fn unescape_marker_value(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        if let Some(byte) = hex {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Id of the latest entry of a profile and table in a markdown log
+fn last_markdown_id(content: &str, profile: &str, table: &str) -> Option<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            line.trim()
+                .strip_prefix("<!-- ")?
+                .strip_suffix(" -->")?
+                .strip_prefix(CHANGELOG_MARKER)
+        })
+        .filter_map(|marker| {
+            let mut marker_profile = None;
+            let mut marker_table = String::new();
+            let mut marker_id = None;
+
+            for field in marker.split_whitespace() {
+                if let Some(value) = field.strip_prefix("profile=") {
+                    marker_profile = Some(unescape_marker_value(value));
+                } else if let Some(value) = field.strip_prefix("table=") {
+                    marker_table = unescape_marker_value(value);
+                } else if let Some(value) = field.strip_prefix("id=") {
+                    marker_id = Some(value);
+                }
+            }
+
+            if marker_profile.as_deref() == Some(profile) && marker_table == table {
+                marker_id.map(str::to_string)
+            } else {
+                None
+            }
+        })
+        .last()
+}
+
+/// Append an entry to a generation log, unless the latest entry of the same profile and table logs identical output.
+/// Logs ending with `.json` hold an array of entries, any other log is markdown. Returns false if the entry was a repeat
+pub async fn append(log_file: &str, entry: &CtGenChangelogEntry) -> Result<bool> {
+    let content = if CtGen::file_exists(log_file).await {
+        Some(
+            tokio::fs::read_to_string(log_file)
+                .await
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to read generation log {}: {}", log_file, e)))?,
+        )
+    } else {
+        None
+    };
+
+    let content = if log_file.ends_with(".json") {
+        let mut entries: Vec<CtGenChangelogEntry> = if let Some(content) = content.as_deref() {
+            serde_json::from_str(content)
+                .map_err(|e| CtGenError::ValidationError(format!("Failed to parse generation log {}: {}", log_file, e)))?
+        } else {
+            Vec::new()
+        };
+
+        let previous = entries
+            .iter()
+            .rev()
+            .find(|previous| previous.profile == entry.profile && previous.table == entry.table);
+
+        if previous.is_some_and(|previous| previous.id == entry.id) {
+            return Ok(false);
+        }
+
+        entries.push(entry.clone());
+
+        serde_json::to_string_pretty(&entries)?
+    } else {
+        let content = content.unwrap_or_else(|| "# Generation log\n".to_string());
+
+        let previous_id = last_markdown_id(&content, &entry.profile, entry.table.as_deref().unwrap_or_default());
+
+        if previous_id.as_deref() == Some(entry.id()) {
+            return Ok(false);
+        }
+
+        format!("{}\n\n{}", content.trim_end(), entry.to_markdown())
+    };
+
+    tokio::fs::write(log_file, content)
+        .await
+        .map_err(|e| CtGenError::RuntimeError(format!("Failed to write generation log {}: {}", log_file, e)))?;
+
+    Ok(true)
+}
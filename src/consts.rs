@@ -1,16 +1,148 @@
+pub const CTGEN_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub const CONFIG_DIR_NAME: &str = "ctgen";
 pub const CONFIG_FILE_NAME: &str = "Profiles.toml";
+pub const DEFAULTS_FILE_NAME: &str = "Defaults.toml";
+pub const MANIFEST_FILE_NAME: &str = ".ctgen-manifest.json";
 pub const CONFIG_NAME_DEFAULT: &str = "default";
-pub const CONFIG_NAME_PATTERN: &str = r"^[a-zA-Z-_]+$";
+pub const CONFIG_NAME_PATTERN: &str = r"^[\p{L}\p{M}_-]+$";
 
 pub const PROFILE_DEFAULT_FILENAME: &str = "Ctgen.toml";
+pub const PROFILES_DIR_NAME: &str = "profiles";
+
+pub const PACK_DEFAULT_FILENAME: &str = "ctgen-pack.toml";
+pub const PACKS_DIR_NAME: &str = "packs";
+pub const PACK_TEMPLATES_DIR_DEFAULT: &str = "templates";
+pub const PACK_SCRIPTS_DIR_DEFAULT: &str = "scripts";
+
+pub const PLUGIN_FUEL: u64 = 10_000_000_000;
 
 pub const FILE_EXT_RHAI: &str = ".rhai";
+pub const FILE_EXT_HBS: &str = ".hbs";
+pub const FILE_EXT_PROFILE_ARCHIVE: &str = ".ctgen";
+
+pub const SCRIPT_TEST_SUFFIX: &str = "_test";
+pub const SCRIPT_TEST_FN_PREFIX: &str = "test_";
+
+pub const PROMPT_ID_DATABASE: &str = "database";
+pub const PROMPT_ID_TABLE: &str = "table";
+pub const PROMPT_ID_LANGUAGE: &str = "language";
+pub const PROMPT_MAX_OPTIONS_DEFAULT: usize = 20;
+pub const CONFIRM_LABEL_YES: &str = "Yes";
+pub const CONFIRM_LABEL_NO: &str = "No";
+pub const PROMPT_SELECT_ALL: &str = "(all)";
+pub const PROMPT_PAGE_PREVIOUS: &str = "(previous page)";
+pub const PROMPT_PAGE_NEXT: &str = "(next page)";
+pub const PROMPT_REFERENCE_PATTERN: &str = r"\bprompts[/.]([\w-]+)";
+pub const TABLE_REFERENCE_PATTERN: &str = r"(?:^|[^\w./-])table[/.]";
+
+pub const PLATFORMS: &[&str] = &[
+    "unix",
+    "windows",
+    "linux",
+    "macos",
+    "freebsd",
+    "openbsd",
+    "netbsd",
+    "dragonfly",
+    "solaris",
+    "illumos",
+    "android",
+    "ios",
+];
+
+pub const ENV_SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+pub const ENV_FEATURES: &str = "CTGEN_FEATURES";
+pub const ENV_CONFIG_DIR: &str = "CTGEN_CONFIG_DIR";
+pub const ENV_PROMPT_PREFIX: &str = "CTGEN_PROMPT_";
+pub const ENV_ERROR: &str = "CTGEN_ERROR";
+
+pub const KEEP_MARKER_START: &str = "ctgen:keep:start";
+pub const KEEP_MARKER_END: &str = "ctgen:keep:end";
+
+pub const TARGET_STDOUT: &str = "-";
+
+pub const CHANGELOG_MARKER: &str = "ctgen:log";
+
+pub const CONFLICT_MARKER_START: &str = "<<<<<<< current";
+pub const CONFLICT_MARKER_SEPARATOR: &str = "=======";
+pub const CONFLICT_MARKER_END: &str = ">>>>>>> generated";
+
+pub const FRONT_MATTER_FENCE_YAML: &str = "---";
+pub const FRONT_MATTER_FENCE_TOML: &str = "+++";
+
+pub const SERVER_ADDRESS_DEFAULT: &str = "127.0.0.1:7415";
+pub const SERVER_IDLE_TIMEOUT_DEFAULT: u64 = 1800;
+pub const SERVER_TOKEN_LENGTH: usize = 32;
+pub const ENV_SERVER_TOKEN: &str = "CTGEN_SERVER_TOKEN";
+
+pub const NOTIFY_AFTER_DEFAULT: u64 = 30;
+pub const EXEC_TIMEOUT_DEFAULT: u64 = 30;
+pub const WATCH_DEBOUNCE_MS: u64 = 200;
+
+pub const SNAPSHOTS_DIR: &str = "tests";
+pub const SNAPSHOT_CONTEXT_FILE: &str = "context.json";
+pub const SNAPSHOT_EXPECTED_DIR: &str = "expected";
+
+pub const PROFILE_CONFIG_KEYS: &[&str] = &[
+    "name",
+    "env-file",
+    "env-var",
+    "dsn",
+    "target-dir",
+    "templates-dir",
+    "scripts-dir",
+    "reflect-include",
+    "reflect-exclude",
+    "parallelism",
+    "strict",
+    "allow-external-target-dir",
+    "generation-log",
+    "auto-targets",
+    "command-dir",
+    "languages",
+    "reflect-mode",
+    "version",
+    "requires-ctgen",
+    "features",
+    "uses",
+];
+
+pub const HELPERS_BUILTIN: &[&str] = &[
+    "if", "unless", "each", "with", "lookup", "raw", "log", "eq", "ne", "gt", "gte", "lt", "lte", "and", "or", "not", "len",
+];
+pub const HELPERS_BUNDLED: &[&str] = &[
+    "concat",
+    "inflect",
+    "datetime",
+    "json",
+    "include_file",
+    "exec",
+    "jsonpath",
+    "filter",
+    "map",
+    "sort_by",
+    "to_ascii",
+    "to_identifier",
+    "rust_ident",
+    "quote_ident",
+];
+
+/// Rust keywords, strict and reserved, that can't be used as plain identifiers
+pub const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+    "trait", "true", "try", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro", "override",
+    "priv", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Rust keywords that can't be raw identifiers either
+pub const RUST_KEYWORDS_NOT_RAW: &[&str] = &["crate", "self", "Self", "super"];
 
 pub const DUMMY_TEMPLATE: &str = r#"
 # Context Test
 
-Table {{table/name}} is selected for this task run. The dummy prompt has been answered with {{#if (eq prompts/dummy "1")}}YES{{else}}NO{{/if}}.
+Table {{table/name}} is selected for this task run. The dummy prompt has been answered with {{#if prompts/dummy}}YES{{else}}NO{{/if}}.
 
 Table primary key: {{#concat table/primary_key separator=", " render_all=true}}`{{this}}`{{/concat}}
 
@@ -41,3 +173,259 @@ Table columns: {{#concat table/columns separator=", " render_all=true}}`{{name}}
 
 {{{json this}}}
 "#;
+
+pub const KIT_BLANK: &str = "blank";
+
+/// Common MariaDB column types, typed by starter kits of profiles not generated from a table. Base types the reflected
+/// column types fall back to, like `int unsigned` for `int(10) unsigned`
+pub const KIT_DATATYPES: [&str; 33] = [
+    "bool",
+    "tinyint",
+    "tinyint unsigned",
+    "smallint",
+    "smallint unsigned",
+    "mediumint",
+    "mediumint unsigned",
+    "int",
+    "int unsigned",
+    "bigint",
+    "bigint unsigned",
+    "decimal",
+    "float",
+    "double",
+    "bit",
+    "char",
+    "varchar",
+    "tinytext",
+    "text",
+    "mediumtext",
+    "longtext",
+    "enum",
+    "set",
+    "date",
+    "time",
+    "datetime",
+    "timestamp",
+    "year",
+    "json",
+    "binary",
+    "varbinary",
+    "blob",
+    "longblob",
+];
+
+pub const KIT_RUST_SQLX_MODEL_TEMPLATE: &str = r#"use sqlx::FromRow;
+
+/// Row of table `{{table/name}}`
+#[derive(Clone, Debug, PartialEq, FromRow)]
+pub struct {{inflect (inflect table/name to_singular=true) to_pascal_case=true}} {
+{{#each table/columns}}
+{{#if (ne (rust_ident name) name)}}
+    #[sqlx(rename = "{{name}}")]
+{{/if}}
+    pub {{rust_ident name}}: {{#if nullable}}Option<{{{lookup ../language/types datatype}}}>{{else}}{{{lookup ../language/types datatype}}}{{/if}},
+{{/each}}
+}
+
+impl {{inflect (inflect table/name to_singular=true) to_pascal_case=true}} {
+    /// Column names of table `{{table/name}}`, in table order
+    pub const COLUMNS: &'static [&'static str] = &[{{#each table/columns}}"{{name}}"{{#unless @last}}, {{/unless}}{{/each}}];
+}
+"#;
+
+pub const KIT_RUST_SQLX_REPOSITORY_TEMPLATE: &str = r#"use sqlx::{MySql, MySqlPool};
+
+use crate::models::{{inflect (inflect table/name to_singular=true) to_snake_case=true}}::{{inflect (inflect table/name to_singular=true) to_pascal_case=true}};
+
+/// Queries of table `{{table/name}}`
+pub struct {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}Repository {
+    pool: MySqlPool,
+}
+
+impl {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}Repository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Every row of the table
+    pub async fn all(&self) -> sqlx::Result<Vec<{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}>> {
+        sqlx::query_as::<_, {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}>("SELECT {{#each table/columns}}`{{name}}`{{#unless @last}}, {{/unless}}{{/each}} FROM `{{table/name}}`")
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Insert a row, binding every column in table order
+    pub async fn insert(&self, row: &{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}) -> sqlx::Result<u64> {
+        sqlx::query::<MySql>("INSERT INTO `{{table/name}}` ({{#each table/columns}}`{{name}}`{{#unless @last}}, {{/unless}}{{/each}}) VALUES ({{#each table/columns}}?{{#unless @last}}, {{/unless}}{{/each}})")
+{{#each table/columns}}
+            .bind(&row.{{rust_ident name}})
+{{/each}}
+            .execute(&self.pool)
+            .await
+            .map(|result| result.rows_affected())
+    }
+{{#if table/primary_key}}
+
+    /// Delete a row by its primary key
+    pub async fn delete(&self, row: &{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}) -> sqlx::Result<u64> {
+        sqlx::query::<MySql>("DELETE FROM `{{table/name}}` WHERE {{#each table/primary_key}}`{{this}}` = ?{{#unless @last}} AND {{/unless}}{{/each}}")
+{{#each table/primary_key}}
+            .bind(&row.{{rust_ident this}})
+{{/each}}
+            .execute(&self.pool)
+            .await
+            .map(|result| result.rows_affected())
+    }
+{{/if}}
+}
+"#;
+
+pub const KIT_RUST_SQLX_TEST_TEMPLATE: &str = r#"#[allow(dead_code)]
+#[path = "../src/models/{{inflect (inflect table/name to_singular=true) to_snake_case=true}}.rs"]
+mod model;
+
+use model::{{inflect (inflect table/name to_singular=true) to_pascal_case=true}};
+
+#[test]
+fn model_has_every_column() {
+    assert_eq!({{inflect (inflect table/name to_singular=true) to_pascal_case=true}}::COLUMNS, [{{#each table/columns}}"{{name}}"{{#unless @last}}, {{/unless}}{{/each}}]);
+}
+"#;
+
+pub const KIT_PHP_LARAVEL_MODEL_TEMPLATE: &str = r#"<?php
+
+namespace App\Models;
+
+use Illuminate\Database\Eloquent\Model;
+
+/**
+ * Row of table `{{table/name}}`
+ */
+class {{inflect (inflect table/name to_singular=true) to_pascal_case=true}} extends Model
+{
+    protected $table = '{{table/name}}';
+{{#each table/primary_key}}
+{{#if @first}}
+
+    protected $primaryKey = '{{this}}';
+{{/if}}
+{{/each}}
+
+    public $timestamps = false;
+
+    protected $guarded = [{{#each table/primary_key}}'{{this}}'{{#unless @last}}, {{/unless}}{{/each}}];
+
+    protected $casts = [
+{{#each table/columns}}
+        '{{name}}' => '{{{lookup ../language/types datatype}}}',
+{{/each}}
+    ];
+}
+"#;
+
+pub const KIT_PHP_LARAVEL_REPOSITORY_TEMPLATE: &str = r#"<?php
+
+namespace App\Repositories;
+
+use App\Models\{{inflect (inflect table/name to_singular=true) to_pascal_case=true}};
+use Illuminate\Database\Eloquent\Collection;
+
+/**
+ * Queries of table `{{table/name}}`
+ */
+class {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}Repository
+{
+    public function all(): Collection
+    {
+        return {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}::all();
+    }
+
+    public function find(mixed $key): ?{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}
+    {
+        return {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}::find($key);
+    }
+
+    public function create(array $attributes): {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}
+    {
+        return {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}::create($attributes);
+    }
+
+    public function delete({{inflect (inflect table/name to_singular=true) to_pascal_case=true}} ${{inflect (inflect table/name to_singular=true) to_camel_case=true}}): bool
+    {
+        return (bool) ${{inflect (inflect table/name to_singular=true) to_camel_case=true}}->delete();
+    }
+}
+"#;
+
+pub const KIT_PHP_LARAVEL_TEST_TEMPLATE: &str = r#"<?php
+
+namespace Tests\Unit;
+
+use App\Models\{{inflect (inflect table/name to_singular=true) to_pascal_case=true}};
+use PHPUnit\Framework\TestCase;
+
+class {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}Test extends TestCase
+{
+    public function test_model_uses_table(): void
+    {
+        $this->assertSame('{{table/name}}', (new {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}())->getTable());
+    }
+
+    public function test_model_casts_every_column(): void
+    {
+        $casts = (new {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}())->getCasts();
+
+{{#each table/columns}}
+        $this->assertArrayHasKey('{{name}}', $casts);
+{{/each}}
+    }
+}
+"#;
+
+pub const KIT_TS_PRISMA_MODEL_TEMPLATE: &str = r#"/// Table `{{table/name}}`
+model {{inflect (inflect table/name to_singular=true) to_pascal_case=true}} {
+{{#each table/columns}}
+  {{name}} {{{lookup ../language/types datatype}}}{{#if nullable}}?{{/if}}
+{{/each}}
+{{#if table/primary_key}}
+
+  @@id([{{#each table/primary_key}}{{this}}{{#unless @last}}, {{/unless}}{{/each}}])
+{{/if}}
+  @@map("{{table/name}}")
+}
+"#;
+
+pub const KIT_TS_PRISMA_REPOSITORY_TEMPLATE: &str = r#"import { Prisma, PrismaClient, {{inflect (inflect table/name to_singular=true) to_pascal_case=true}} } from "@prisma/client";
+
+/** Queries of table `{{table/name}}` */
+export class {{inflect (inflect table/name to_singular=true) to_pascal_case=true}}Repository {
+  constructor(private readonly prisma: PrismaClient) {}
+
+  all(): Promise<{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}[]> {
+    return this.prisma.{{inflect (inflect table/name to_singular=true) to_camel_case=true}}.findMany();
+  }
+
+  create(data: Prisma.{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}CreateInput): Promise<{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}> {
+    return this.prisma.{{inflect (inflect table/name to_singular=true) to_camel_case=true}}.create({ data });
+  }
+{{#if table/primary_key}}
+
+  find(where: Prisma.{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}WhereUniqueInput): Promise<{{inflect (inflect table/name to_singular=true) to_pascal_case=true}} | null> {
+    return this.prisma.{{inflect (inflect table/name to_singular=true) to_camel_case=true}}.findUnique({ where });
+  }
+
+  delete(where: Prisma.{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}WhereUniqueInput): Promise<{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}> {
+    return this.prisma.{{inflect (inflect table/name to_singular=true) to_camel_case=true}}.delete({ where });
+  }
+{{/if}}
+}
+"#;
+
+pub const KIT_TS_PRISMA_TEST_TEMPLATE: &str = r#"import { test } from "node:test";
+import assert from "node:assert/strict";
+import { Prisma } from "@prisma/client";
+
+test("{{inflect (inflect table/name to_singular=true) to_pascal_case=true}} model has every column", () => {
+  assert.deepEqual(Object.keys(Prisma.{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}ScalarFieldEnum), [{{#each table/columns}}"{{name}}"{{#unless @last}}, {{/unless}}{{/each}}]);
+});
+"#;
@@ -0,0 +1,479 @@
+use crate::consts::{HELPERS_BUILTIN, HELPERS_BUNDLED};
+use crate::lint;
+use crate::profile::CtGenProfile;
+use crate::task::{frontmatter, renderer};
+use anyhow::Result;
+use handlebars::template::{HelperTemplate, Parameter, TemplateElement};
+use handlebars::{JsonTruthy, Path, PathSeg, Template};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Context field read state. Array items are collapsed into `*` segments
+pub struct CtGenFieldCoverage {
+    /// Field path, for example `table/columns/*/name`
+    path: String,
+    /// Flag that shows whether any template reads the field
+    read: bool,
+}
+
+impl CtGenFieldCoverage {
+    /// Field path
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    /// Flag that shows whether any template reads the field
+    pub fn read(&self) -> bool {
+        self.read
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Branch coverage of an `if` or `unless` block
+pub struct CtGenConditionCoverage {
+    /// Template name or profile field of the block
+    source: String,
+    /// Conditional helper name
+    helper: String,
+    /// Condition path
+    path: String,
+    /// Number of evaluations where the condition was truthy
+    truthy: usize,
+    /// Number of evaluations where the condition was falsy or missing
+    falsy: usize,
+}
+
+impl CtGenConditionCoverage {
+    /// Template name or profile field of the block
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    /// Conditional helper name
+    pub fn helper(&self) -> &str {
+        &self.helper
+    }
+    /// Condition path
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    /// Number of evaluations where the condition was truthy
+    pub fn truthy(&self) -> usize {
+        self.truthy
+    }
+    /// Number of evaluations where the condition was falsy or missing
+    pub fn falsy(&self) -> usize {
+        self.falsy
+    }
+    /// Check if both branches were taken
+    pub fn is_covered(&self) -> bool {
+        self.truthy > 0 && self.falsy > 0
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Context fields read by profile templates versus available in a set of contexts
+pub struct CtGenCoverageReport {
+    /// Number of contexts evaluated
+    contexts: usize,
+    /// Available context fields
+    fields: Vec<CtGenFieldCoverage>,
+    /// Conditional blocks with a context path condition
+    conditions: Vec<CtGenConditionCoverage>,
+}
+
+impl CtGenCoverageReport {
+    /// Number of contexts evaluated
+    pub fn contexts(&self) -> usize {
+        self.contexts
+    }
+    /// Available context fields
+    pub fn fields(&self) -> &Vec<CtGenFieldCoverage> {
+        &self.fields
+    }
+    /// Conditional blocks with a context path condition
+    pub fn conditions(&self) -> &Vec<CtGenConditionCoverage> {
+        &self.conditions
+    }
+    /// Number of available context fields read by templates
+    pub fn fields_read(&self) -> usize {
+        self.fields.iter().filter(|field| field.read).count()
+    }
+}
+
+impl Display for CtGenCoverageReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fields_read = self.fields_read();
+        let percent = if self.fields.is_empty() {
+            100.0
+        } else {
+            fields_read as f64 * 100.0 / self.fields.len() as f64
+        };
+
+        writeln!(
+            f,
+            "Fields read: {}/{} ({:.1}%) across {} context(s)",
+            fields_read,
+            self.fields.len(),
+            percent,
+            self.contexts
+        )?;
+
+        for field in self.fields.iter().filter(|field| !field.read) {
+            writeln!(f, "  unread {}", field.path)?;
+        }
+
+        let conditions_covered = self.conditions.iter().filter(|condition| condition.is_covered()).count();
+
+        write!(
+            f,
+            "Conditions with both branches taken: {}/{}",
+            conditions_covered,
+            self.conditions.len()
+        )?;
+
+        for condition in self.conditions.iter().filter(|condition| !condition.is_covered()) {
+            write!(
+                f,
+                "\n  {}: {{{{#{} {}}}}} is never {}",
+                condition.source,
+                condition.helper,
+                condition.path,
+                if condition.truthy == 0 { "truthy" } else { "falsy" }
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Walks template syntax trees and records the context paths they read, resolved against block scopes
+struct CtGenCoverageWalker {
+    helpers: HashSet<String>,
+    /// Paths whose value is output or passed to helpers
+    read: BTreeSet<String>,
+    /// Conditional blocks as source, helper and path
+    conditions: BTreeSet<(String, String, String)>,
+}
+
+impl CtGenCoverageWalker {
+    fn walk_source(&mut self, source: &str, content: &str) {
+        // templates that don't compile are reported by lint
+        if let Ok(template) = Template::compile(content) {
+            self.walk_elements(source, &template, Some(Vec::new()));
+        }
+    }
+
+    fn walk_elements(&mut self, source: &str, template: &Template, scope: Option<Vec<String>>) {
+        for element in template.elements.iter() {
+            match element {
+                TemplateElement::Expression(helper) | TemplateElement::HtmlExpression(helper) | TemplateElement::HelperBlock(helper) => {
+                    self.walk_helper(source, helper, scope.as_ref())
+                }
+                TemplateElement::PartialExpression(partial) | TemplateElement::PartialBlock(partial) => {
+                    for param in partial.params.iter().chain(partial.hash.values()) {
+                        self.walk_param(param, scope.as_ref());
+                    }
+
+                    if let Some(template) = partial.template.as_ref() {
+                        self.walk_elements(source, template, scope.clone());
+                    }
+                }
+                TemplateElement::DecoratorExpression(decorator) | TemplateElement::DecoratorBlock(decorator) => {
+                    if let Some(template) = decorator.template.as_ref() {
+                        self.walk_elements(source, template, scope.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn walk_helper(&mut self, source: &str, helper: &HelperTemplate, scope: Option<&Vec<String>>) {
+        let name = helper.name.as_name().unwrap_or_default();
+
+        if !self.helpers.contains(name) {
+            // name-only expression, it's a context path
+            if helper.params.is_empty() && helper.hash.is_empty() && helper.template.is_none() {
+                self.walk_param(&helper.name, scope);
+            }
+
+            return;
+        }
+
+        let first_path = helper.params.first().and_then(|param| CtGenCoverageWalker::resolve(param, scope));
+
+        // block scope params are traversed, not read
+        let traversed = helper.template.is_some() && (name == "each" || name == "with");
+
+        for (idx, param) in helper.params.iter().enumerate() {
+            if !(traversed && idx == 0) {
+                self.walk_param(param, scope);
+            }
+        }
+        for param in helper.hash.values() {
+            self.walk_param(param, scope);
+        }
+
+        if helper.template.is_none() {
+            return;
+        }
+
+        let inner_scope = if helper.block_param.is_some() {
+            None
+        } else {
+            match name {
+                "if" | "unless" => {
+                    if let Some(path) = first_path.as_ref() {
+                        self.conditions.insert((source.to_string(), name.to_string(), path.join("/")));
+                    }
+
+                    scope.cloned()
+                }
+                "each" => first_path.map(|mut path| {
+                    path.push("*".to_string());
+                    path
+                }),
+                "with" => first_path,
+                _ => None,
+            }
+        };
+
+        if let Some(template) = helper.template.as_ref() {
+            self.walk_elements(source, template, inner_scope.clone());
+        }
+
+        if let Some(template) = helper.inverse.as_ref() {
+            // `else` of `each` and `with` keeps the outer scope
+            let inverse_scope = if name == "each" || name == "with" {
+                scope.cloned()
+            } else {
+                inner_scope
+            };
+
+            self.walk_elements(source, template, inverse_scope);
+        }
+    }
+
+    fn walk_param(&mut self, param: &Parameter, scope: Option<&Vec<String>>) {
+        match param {
+            Parameter::Subexpression(subexpression) => {
+                if let TemplateElement::Expression(helper) = subexpression.as_element() {
+                    for param in helper.params.iter().chain(helper.hash.values()) {
+                        self.walk_param(param, scope);
+                    }
+                }
+            }
+            _ => {
+                if let Some(path) = CtGenCoverageWalker::resolve(param, scope) {
+                    self.read.insert(path.join("/"));
+                }
+            }
+        }
+    }
+
+    /// Resolve a path param against a scope. Unknown scopes, `@root`, `../` and local variables yield nothing
+    fn resolve(param: &Parameter, scope: Option<&Vec<String>>) -> Option<Vec<String>> {
+        let mut path = scope?.clone();
+
+        match param {
+            Parameter::Name(name) if !name.starts_with('@') => {
+                path.push(name.to_string());
+            }
+            Parameter::Path(Path::Relative((segments, _))) => {
+                for segment in segments {
+                    match segment {
+                        PathSeg::Named(name) => path.push(name.to_string()),
+                        _ => return None,
+                    }
+                }
+            }
+            _ => return None,
+        }
+
+        Some(path)
+    }
+}
+
+/// Collect context field paths of a value. Array items are collapsed into `*` segments
+fn collect_fields(value: &Value, path: &mut Vec<String>, fields: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                path.push(key.to_string());
+                collect_fields(value, path, fields);
+                path.pop();
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            path.push("*".to_string());
+            for item in items {
+                collect_fields(item, path, fields);
+            }
+            path.pop();
+        }
+        _ => {
+            fields.insert(path.join("/"));
+        }
+    }
+}
+
+/// Find every value of a path with `*` segments
+fn collect_values<'a>(value: &'a Value, path: &[&str], values: &mut Vec<&'a Value>) {
+    let Some((segment, rest)) = path.split_first() else {
+        values.push(value);
+        return;
+    };
+
+    match (value, *segment) {
+        (Value::Array(items), "*") => {
+            for item in items {
+                collect_values(item, rest, values);
+            }
+        }
+        (Value::Object(map), segment) => {
+            if let Some(value) = map.get(segment) {
+                collect_values(value, rest, values);
+            }
+        }
+        (Value::Array(items), segment) => {
+            if let Some(value) = segment.parse::<usize>().ok().and_then(|idx| items.get(idx)) {
+                collect_values(value, rest, values);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Check whether a field is read, directly or through one of its ancestors
+fn is_read(field: &str, read: &BTreeSet<String>) -> bool {
+    let mut prefix = field;
+
+    loop {
+        if read.contains(prefix) {
+            return true;
+        }
+
+        match prefix.rfind('/') {
+            Some(idx) => prefix = &prefix[..idx],
+            None => return false,
+        }
+    }
+}
+
+/// Report which context fields profile templates read versus what the given contexts provide, and which conditional branches they take
+pub async fn coverage(profile: &CtGenProfile, contexts: &[Value]) -> Result<CtGenCoverageReport> {
+    let mut helpers = HELPERS_BUILTIN
+        .iter()
+        .chain(HELPERS_BUNDLED.iter())
+        .map(|s| s.to_string())
+        .collect::<HashSet<String>>();
+    helpers.extend(renderer::script_helpers(&profile.scripts_dir()).into_iter().map(|(name, _)| name));
+    helpers.extend(profile.helpers().plugins().map(|(name, _)| name.to_string()));
+
+    let mut walker = CtGenCoverageWalker {
+        helpers,
+        read: BTreeSet::new(),
+        conditions: BTreeSet::new(),
+    };
+
+    let delimiters = profile
+        .targets()
+        .filter_map(|target_name| profile.target(target_name))
+        .filter_map(|target| target.delimiters().map(|delimiters| (target.template(), delimiters)))
+        .collect::<BTreeMap<&str, (&str, &str)>>();
+
+    for (template_name, content) in lint::load_templates(&profile.templates_dir()).await? {
+        let content = frontmatter::strip(&content);
+
+        if let Some((open, close)) = delimiters.get(template_name.as_str()) {
+            walker.walk_source(&template_name, &renderer::convert_delimiters(content, open, close));
+        } else {
+            walker.walk_source(&template_name, content);
+        }
+    }
+
+    for target_name in profile.targets() {
+        if let Some(target) = profile.target(target_name) {
+            if let Some(condition) = target.condition() {
+                walker.walk_source(&format!("target.{}.condition", target_name), condition);
+            }
+            if let Some(enumerate) = target.enumerate() {
+                walker.walk_source(&format!("target.{}.enumerate", target_name), enumerate);
+            }
+            walker.walk_source(&format!("target.{}.target", target_name), target.target());
+        }
+    }
+
+    for prompt_name in profile.prompts() {
+        if let Some(prompt) = profile.prompt(prompt_name) {
+            if let Some(condition) = prompt.condition() {
+                walker.walk_source(&format!("prompt.{}.condition", prompt_name), condition);
+            }
+            if let Some(enumerate) = prompt.enumerate() {
+                walker.walk_source(&format!("prompt.{}.enumerate", prompt_name), enumerate);
+            }
+            walker.walk_source(&format!("prompt.{}.prompt", prompt_name), prompt.prompt());
+            if let Some(default) = prompt.default() {
+                walker.walk_source(&format!("prompt.{}.default", prompt_name), default);
+            }
+            if let Some(transform) = prompt.transform() {
+                walker.walk_source(&format!("prompt.{}.transform", prompt_name), transform);
+            }
+        }
+    }
+
+    let mut fields = BTreeSet::new();
+    for context in contexts {
+        collect_fields(context, &mut Vec::new(), &mut fields);
+    }
+
+    let conditions = walker
+        .conditions
+        .iter()
+        .map(|(source, helper, path)| {
+            let segments = path.split('/').collect::<Vec<&str>>();
+
+            let mut truthy = 0;
+            let mut falsy = 0;
+
+            for context in contexts {
+                let mut values = Vec::new();
+                collect_values(context, &segments, &mut values);
+
+                if values.is_empty() {
+                    falsy += 1;
+                }
+
+                for value in values {
+                    if value.is_truthy(false) {
+                        truthy += 1;
+                    } else {
+                        falsy += 1;
+                    }
+                }
+            }
+
+            CtGenConditionCoverage {
+                source: source.to_string(),
+                helper: helper.to_string(),
+                path: path.to_string(),
+                truthy,
+                falsy,
+            }
+        })
+        .collect();
+
+    Ok(CtGenCoverageReport {
+        contexts: contexts.len(),
+        fields: fields
+            .into_iter()
+            .map(|path| CtGenFieldCoverage {
+                read: is_read(&path, &walker.read),
+                path,
+            })
+            .collect(),
+        conditions,
+    })
+}
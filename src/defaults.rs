@@ -0,0 +1,142 @@
+use crate::error::CtGenError;
+use crate::CtGen;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::env;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// User level defaults, loaded from `Defaults.toml` in the config directory. CLI flags take precedence
+pub struct CtGenDefaults {
+    #[serde(default)]
+    /// Preferred CLI theme name, `colorful` or `simple`
+    theme: Option<String>,
+    #[serde(default)]
+    /// CLI glyphs and colors
+    style: CtGenStyleConfig,
+    #[serde(default)]
+    /// Editor command used to open files. Falls back to `$VISUAL` and `$EDITOR`
+    editor: Option<String>,
+    #[serde(default)]
+    /// Default max number of concurrent reflections, target renders and formatters
+    jobs: Option<usize>,
+    #[serde(default)]
+    /// Run tasks in deterministic mode by default
+    deterministic: bool,
+    #[serde(default, rename = "skip-missing")]
+    /// Skip targets with missing templates by default
+    skip_missing: bool,
+    #[serde(default, rename = "simple-prompts")]
+    /// Ask prompts as numbered plain-text questions instead of interactive widgets
+    simple_prompts: bool,
+}
+
+impl CtGenDefaults {
+    /// Load defaults file. A missing file yields empty defaults
+    pub async fn load(file: &str) -> Result<Self> {
+        if !CtGen::file_exists(file).await {
+            return Ok(Self::default());
+        }
+
+        match tokio::fs::read_to_string(file).await {
+            Ok(c) => Ok(toml::from_str(&c).map_err(|e| CtGenError::InitError(format!("Failed to parse defaults file {}: {}", file, e)))?),
+            Err(e) => Err(CtGenError::InitError(format!("Failed to load defaults file {}: {}", file, e)).into()),
+        }
+    }
+
+    /// Preferred CLI theme name
+    pub fn theme(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+    /// CLI glyphs and colors
+    pub fn style(&self) -> &CtGenStyleConfig {
+        &self.style
+    }
+    /// Editor command used to open files, if configured here or in the environment
+    pub fn editor(&self) -> Option<String> {
+        self.editor
+            .clone()
+            .or_else(|| env::var("VISUAL").ok())
+            .or_else(|| env::var("EDITOR").ok())
+            .filter(|editor| !editor.is_empty())
+    }
+    /// Default max number of concurrent reflections, target renders and formatters
+    pub fn jobs(&self) -> Option<usize> {
+        self.jobs
+    }
+    /// Run tasks in deterministic mode by default
+    pub fn deterministic(&self) -> bool {
+        self.deterministic
+    }
+    /// Skip targets with missing templates by default
+    pub fn skip_missing(&self) -> bool {
+        self.skip_missing
+    }
+    /// Ask prompts as numbered plain-text questions by default
+    pub fn simple_prompts(&self) -> bool {
+        self.simple_prompts
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// CLI glyphs and colors. Colors are dotted style strings like `green` or `red.bold`
+pub struct CtGenStyleConfig {
+    #[serde(default)]
+    /// Use ASCII glyphs only, for terminals without Unicode
+    ascii: bool,
+    #[serde(default, rename = "info-glyph")]
+    /// Glyph in front of info messages
+    info_glyph: Option<String>,
+    #[serde(default, rename = "info-color")]
+    /// Color of the info glyph
+    info_color: Option<String>,
+    #[serde(default, rename = "fail-glyph")]
+    /// Glyph in front of failure messages
+    fail_glyph: Option<String>,
+    #[serde(default, rename = "fail-color")]
+    /// Color of the failure glyph
+    fail_color: Option<String>,
+    #[serde(default, rename = "prompt-glyph")]
+    /// Glyph in front of prompts
+    prompt_glyph: Option<String>,
+    #[serde(default, rename = "success-glyph")]
+    /// Glyph in front of answered prompts
+    success_glyph: Option<String>,
+    #[serde(default, rename = "error-glyph")]
+    /// Glyph in front of prompt errors
+    error_glyph: Option<String>,
+}
+
+impl CtGenStyleConfig {
+    /// Use ASCII glyphs only
+    pub fn ascii(&self) -> bool {
+        self.ascii
+    }
+    /// Glyph in front of info messages
+    pub fn info_glyph(&self) -> Option<&str> {
+        self.info_glyph.as_deref()
+    }
+    /// Color of the info glyph
+    pub fn info_color(&self) -> Option<&str> {
+        self.info_color.as_deref()
+    }
+    /// Glyph in front of failure messages
+    pub fn fail_glyph(&self) -> Option<&str> {
+        self.fail_glyph.as_deref()
+    }
+    /// Color of the failure glyph
+    pub fn fail_color(&self) -> Option<&str> {
+        self.fail_color.as_deref()
+    }
+    /// Glyph in front of prompts
+    pub fn prompt_glyph(&self) -> Option<&str> {
+        self.prompt_glyph.as_deref()
+    }
+    /// Glyph in front of answered prompts
+    pub fn success_glyph(&self) -> Option<&str> {
+        self.success_glyph.as_deref()
+    }
+    /// Glyph in front of prompt errors
+    pub fn error_glyph(&self) -> Option<&str> {
+        self.error_glyph.as_deref()
+    }
+}
@@ -0,0 +1,313 @@
+use crate::consts::FILE_EXT_HBS;
+use crate::error::CtGenError;
+use crate::lint::CtGenLinter;
+use crate::profile::{CtGenProfile, CtGenTargetKind, CtGenTargetMode};
+use crate::task::context::CtGenTaskContext;
+use crate::task::{frontmatter, renderer, CtGenTask};
+use crate::CtGen;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Summary of a profile target and everything it depends on
+pub struct CtGenTargetExplanation {
+    /// Target name
+    name: String,
+    /// Whether the target renders a template or copies an asset file
+    kind: CtGenTargetKind,
+    /// Template name, or asset file path of a copy target
+    template: String,
+    /// Canonical template or asset file path
+    template_file: String,
+    /// Flag that shows whether the template or asset file exists
+    template_exists: bool,
+    /// Template description from its front matter
+    description: Option<String>,
+    /// Output file path template
+    target: String,
+    /// Condition template
+    condition: Option<String>,
+    /// Enumerate template, rendering the items the target renders once for
+    enumerate: Option<String>,
+    /// Formatter command template
+    formatter: Option<String>,
+    /// Alternate template delimiters
+    delimiters: Option<(String, String)>,
+    /// How an existing output file is treated
+    mode: CtGenTargetMode,
+    /// Platforms the target renders on. Renders on every platform if empty
+    platforms: Vec<String>,
+    /// Expression over platforms and feature flags that must hold for the target to render
+    when: Option<String>,
+    /// Partials used by the template, directly or through other partials
+    partials: Vec<String>,
+    /// Rhai script helpers used by the target
+    scripts: Vec<String>,
+    /// WASM plugin helpers used by the target
+    plugins: Vec<String>,
+    /// Prompt ids referenced by the target or required by its template front matter
+    prompts: Vec<String>,
+    /// Context paths required by the template front matter
+    context: Vec<String>,
+    /// Output file path rendered against a sample context, or the rendering error
+    example_target: String,
+}
+
+impl CtGenTargetExplanation {
+    /// Target name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Whether the target renders a template or copies an asset file
+    pub fn kind(&self) -> CtGenTargetKind {
+        self.kind
+    }
+    /// Template name, or asset file path of a copy target
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+    /// Canonical template or asset file path
+    pub fn template_file(&self) -> &str {
+        &self.template_file
+    }
+    /// Flag that shows whether the template or asset file exists
+    pub fn template_exists(&self) -> bool {
+        self.template_exists
+    }
+    /// Template description from its front matter
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    /// Output file path template
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+    /// Condition template
+    pub fn condition(&self) -> Option<&str> {
+        self.condition.as_deref()
+    }
+    /// Enumerate template, rendering the items the target renders once for
+    pub fn enumerate(&self) -> Option<&str> {
+        self.enumerate.as_deref()
+    }
+    /// Formatter command template
+    pub fn formatter(&self) -> Option<&str> {
+        self.formatter.as_deref()
+    }
+    /// Alternate template delimiters
+    pub fn delimiters(&self) -> Option<(&str, &str)> {
+        self.delimiters.as_ref().map(|(open, close)| (open.as_str(), close.as_str()))
+    }
+    /// How an existing output file is treated
+    pub fn mode(&self) -> CtGenTargetMode {
+        self.mode
+    }
+    /// Platforms the target renders on. Renders on every platform if empty
+    pub fn platforms(&self) -> &Vec<String> {
+        &self.platforms
+    }
+    /// Expression over platforms and feature flags that must hold for the target to render
+    pub fn when(&self) -> Option<&str> {
+        self.when.as_deref()
+    }
+    /// Partials used by the template, directly or through other partials
+    pub fn partials(&self) -> &Vec<String> {
+        &self.partials
+    }
+    /// Rhai script helpers used by the target
+    pub fn scripts(&self) -> &Vec<String> {
+        &self.scripts
+    }
+    /// WASM plugin helpers used by the target
+    pub fn plugins(&self) -> &Vec<String> {
+        &self.plugins
+    }
+    /// Prompt ids referenced by the target or required by its template front matter
+    pub fn prompts(&self) -> &Vec<String> {
+        &self.prompts
+    }
+    /// Context paths required by the template front matter
+    pub fn context(&self) -> &Vec<String> {
+        &self.context
+    }
+    /// Output file path rendered against a sample context, or the rendering error
+    pub fn example_target(&self) -> &str {
+        &self.example_target
+    }
+}
+
+impl Display for CtGenTargetExplanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let list = |items: &Vec<String>| if items.is_empty() { "none".to_string() } else { items.join(", ") };
+
+        writeln!(f, "Target:         {}", self.name)?;
+        writeln!(
+            f,
+            "{}       {} ({}{})",
+            if self.kind == CtGenTargetKind::Copy {
+                "Source:  "
+            } else {
+                "Template:"
+            },
+            self.template,
+            self.template_file,
+            if self.template_exists { "" } else { ", missing" }
+        )?;
+        if let Some(description) = self.description.as_deref() {
+            writeln!(f, "Description:    {}", description)?;
+        }
+        writeln!(f, "Output path:    {}", self.target)?;
+        writeln!(f, "Mode:           {}", self.mode)?;
+        if !self.platforms.is_empty() {
+            writeln!(f, "Platforms:      {}", self.platforms.join(", "))?;
+        }
+        if let Some(when) = self.when.as_deref() {
+            writeln!(f, "When:           {}", when)?;
+        }
+        writeln!(f, "Condition:      {}", self.condition.as_deref().unwrap_or("none"))?;
+        if let Some(enumerate) = self.enumerate.as_deref() {
+            writeln!(f, "Enumerate:      {}", enumerate)?;
+        }
+        writeln!(f, "Formatter:      {}", self.formatter.as_deref().unwrap_or("none"))?;
+        if let Some((open, close)) = self.delimiters() {
+            writeln!(f, "Delimiters:     {} {}", open, close)?;
+        }
+        writeln!(f, "Partials:       {}", list(&self.partials))?;
+        writeln!(f, "Scripts:        {}", list(&self.scripts))?;
+        writeln!(f, "Plugins:        {}", list(&self.plugins))?;
+        writeln!(f, "Prompts:        {}", list(&self.prompts))?;
+        writeln!(f, "Context:        {}", list(&self.context))?;
+        write!(f, "Example output: {}", self.example_target)
+    }
+}
+
+/// Explain a profile target without connecting to a database
+pub async fn explain_target(profile: &CtGenProfile, target_name: &str) -> Result<CtGenTargetExplanation> {
+    let target = profile
+        .target(target_name)
+        .ok_or_else(|| CtGenError::ValidationError(format!("Target `{}` does not exist in targets table", target_name)))?;
+
+    let mut linter = CtGenLinter::new(profile)?;
+
+    let scripts = renderer::script_helpers(&profile.scripts_dir())
+        .into_iter()
+        .map(|(script_name, _)| script_name)
+        .collect::<BTreeSet<String>>();
+    let plugins = profile
+        .helpers()
+        .plugins()
+        .map(|(plugin_name, _)| plugin_name.to_string())
+        .collect::<BTreeSet<String>>();
+
+    linter.helpers.extend(scripts.iter().cloned());
+    linter.helpers.extend(plugins.iter().cloned());
+
+    let templates_dir = profile.templates_dir();
+    let (template, template_file) = if target.is_copy() {
+        (
+            target.source().unwrap_or_default().to_string(),
+            profile.target_source_file(target).unwrap_or_default(),
+        )
+    } else {
+        (
+            target.template().to_string(),
+            CtGen::get_filepath(&templates_dir, &format!("{}{}", target.template(), FILE_EXT_HBS)),
+        )
+    };
+    let template_exists = CtGen::file_exists(&template_file).await;
+
+    // follow partials through the templates dir. Copied assets aren't templates
+    let mut meta = None;
+    let mut partials = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut pending = if target.is_copy() { Vec::new() } else { vec![template.clone()] };
+
+    while let Some(template_name) = pending.pop() {
+        if !visited.insert(template_name.clone()) {
+            continue;
+        }
+
+        let file = CtGen::get_filepath(&templates_dir, &format!("{}{}", template_name, FILE_EXT_HBS));
+
+        if let Ok(content) = tokio::fs::read_to_string(&file).await {
+            let body = frontmatter::strip(&content);
+
+            if template_name == target.template() {
+                // invalid front matter is reported by lint
+                meta = frontmatter::parse(&content).unwrap_or_default();
+
+                if let Some((open, close)) = target.delimiters() {
+                    linter.lint_source(&template_name, &renderer::convert_delimiters(body, open, close), true);
+                } else {
+                    linter.lint_source(&template_name, body, true);
+                }
+            } else {
+                partials.insert(template_name.clone());
+                linter.lint_source(&template_name, body, true);
+            }
+
+            pending.extend(linter.used_partials.iter().filter(|partial| !visited.contains(*partial)).cloned());
+        }
+    }
+
+    if let Some(condition) = target.condition() {
+        linter.lint_source("condition", condition, true);
+    }
+    if let Some(enumerate) = target.enumerate() {
+        linter.lint_source("enumerate", enumerate, true);
+    }
+    linter.lint_source("target", target.target(), true);
+    if let Some(formatter) = target.formatter() {
+        linter.lint_source("formatter", formatter, false);
+    }
+
+    // render the output path against a sample context with empty prompt answers and the target params
+    let mut sample = serde_json::to_value(CtGenTaskContext::sample(
+        profile.prompts(),
+        profile.connections().map(|(name, _)| name),
+    ))?;
+
+    if let Some(sample) = sample.as_object_mut() {
+        sample.insert("params".to_string(), serde_json::to_value(target.params())?);
+        sample.insert("vars".to_string(), serde_json::to_value(profile.vars())?);
+        sample.insert(
+            "features".to_string(),
+            serde_json::to_value(CtGenTask::resolve_features(profile.configuration().features(), &[]))?,
+        );
+    }
+    let example_target = match renderer::init_renderer(profile) {
+        Ok(handlebars) => handlebars
+            .render_template(target.target(), &sample)
+            .unwrap_or_else(|e| format!("failed to render: {}", e)),
+        Err(e) => format!("failed to init renderer: {}", e),
+    };
+
+    let meta = meta.unwrap_or_default();
+
+    linter.used_prompts.extend(meta.prompts().iter().cloned());
+
+    Ok(CtGenTargetExplanation {
+        name: target_name.to_string(),
+        kind: target.kind(),
+        template,
+        template_file,
+        template_exists,
+        description: meta.description().map(str::to_string),
+        target: target.target().to_string(),
+        condition: target.condition().map(str::to_string),
+        enumerate: target.enumerate().map(str::to_string),
+        formatter: target.formatter().map(str::to_string),
+        delimiters: target.delimiters().map(|(open, close)| (open.to_string(), close.to_string())),
+        mode: target.mode(),
+        platforms: target.platforms().clone(),
+        when: target.when().map(str::to_string),
+        partials: partials.into_iter().collect(),
+        scripts: linter.used_helpers.intersection(&scripts).cloned().collect(),
+        plugins: linter.used_helpers.intersection(&plugins).cloned().collect(),
+        prompts: linter.used_prompts.into_iter().collect(),
+        context: meta.context().clone(),
+        example_target,
+    })
+}
@@ -0,0 +1,181 @@
+use crate::consts::SCRIPT_TEST_FN_PREFIX;
+use crate::profile::CtGenProfile;
+use crate::task::renderer;
+use anyhow::Result;
+use handlebars::Handlebars;
+use rhai::serde::from_dynamic;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Result of a single helper test case
+pub struct CtGenHelperTestCase {
+    /// Test script name, for example `utils/case_test`
+    script: String,
+    /// Test function name, or the script name for scripts without test functions
+    case: String,
+    /// Error of a failed case
+    error: Option<String>,
+}
+
+impl CtGenHelperTestCase {
+    /// Test script name, for example `utils/case_test`
+    pub fn script(&self) -> &str {
+        &self.script
+    }
+    /// Test function name, or the script name for scripts without test functions
+    pub fn case(&self) -> &str {
+        &self.case
+    }
+    /// Error of a failed case
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Check if the case passed
+    pub fn is_passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// Helper test results of a profile
+pub struct CtGenHelperTestReport {
+    /// Test cases of every test script, in script order
+    cases: Vec<CtGenHelperTestCase>,
+}
+
+impl CtGenHelperTestReport {
+    /// Test cases of every test script, in script order
+    pub fn cases(&self) -> &Vec<CtGenHelperTestCase> {
+        &self.cases
+    }
+
+    /// Check if every test case passed
+    pub fn is_passed(&self) -> bool {
+        self.cases.iter().all(CtGenHelperTestCase::is_passed)
+    }
+}
+
+impl Display for CtGenHelperTestReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for case in self.cases.iter() {
+            if let Some(error) = case.error.as_deref() {
+                writeln!(f, "{:>6} {}::{}: {}", "failed", case.script, case.case, error)?;
+            } else {
+                writeln!(f, "{:>6} {}::{}", "ok", case.script, case.case)?;
+            }
+        }
+
+        let failed = self.cases.iter().filter(|case| !case.is_passed()).count();
+
+        write!(f, "{} cases, {} failed", self.cases.len(), failed)
+    }
+}
+
+/// Run the helper test scripts of a profile, `*_test.rhai` files in the scripts dir. Every function named `test_*` without
+/// parameters is a test case; scripts without test functions run as a single case
+pub fn test_helpers(profile: &CtGenProfile) -> Result<CtGenHelperTestReport> {
+    let handlebars: Arc<Handlebars<'static>> = Arc::new(renderer::init_renderer(profile)?);
+    let engine = init_test_engine(handlebars);
+
+    let mut cases = Vec::new();
+
+    for (script_name, script_path) in renderer::script_tests(&profile.scripts_dir()) {
+        let ast = match engine.compile_file(script_path) {
+            Ok(ast) => ast,
+            Err(e) => {
+                cases.push(CtGenHelperTestCase {
+                    script: script_name.clone(),
+                    case: script_name,
+                    error: Some(e.to_string()),
+                });
+
+                continue;
+            }
+        };
+
+        let test_fns = ast
+            .iter_functions()
+            .filter(|test_fn| test_fn.name.starts_with(SCRIPT_TEST_FN_PREFIX) && test_fn.params.is_empty())
+            .map(|test_fn| test_fn.name.to_string())
+            .collect::<Vec<String>>();
+
+        if test_fns.is_empty() {
+            cases.push(CtGenHelperTestCase {
+                script: script_name.clone(),
+                case: script_name,
+                error: engine.run_ast(&ast).err().map(|e| e.to_string()),
+            });
+
+            continue;
+        }
+
+        for test_fn in test_fns {
+            cases.push(CtGenHelperTestCase {
+                script: script_name.clone(),
+                error: engine
+                    .call_fn::<Dynamic>(&mut Scope::new(), &ast, &test_fn, ())
+                    .err()
+                    .map(|e| e.to_string()),
+                case: test_fn,
+            });
+        }
+    }
+
+    Ok(CtGenHelperTestReport { cases })
+}
+
+/// Rhai engine of test scripts. Scripts call profile helpers with `helper(name, params)` or `helper(name, params, hash)` and
+/// check results with `assert(condition, message)` and `assert_eq(actual, expected)`
+fn init_test_engine(handlebars: Arc<Handlebars<'static>>) -> Engine {
+    let mut engine = renderer::init_engine();
+
+    let registry = handlebars.clone();
+    engine.register_fn("helper", move |name: &str, params: Array| {
+        call_helper(&registry, name, params, Map::new())
+    });
+    engine.register_fn("helper", move |name: &str, params: Array, hash: Map| {
+        call_helper(&handlebars, name, params, hash)
+    });
+
+    engine.register_fn("assert", |condition: bool, message: &str| -> Result<(), Box<EvalAltResult>> {
+        if condition {
+            Ok(())
+        } else {
+            Err(format!("Assertion failed: {}", message).into())
+        }
+    });
+    engine.register_fn(
+        "assert_eq",
+        |actual: Dynamic, expected: Dynamic| -> Result<(), Box<EvalAltResult>> {
+            if from_dynamic::<Value>(&actual)? == from_dynamic::<Value>(&expected)? {
+                Ok(())
+            } else {
+                Err(format!("Assertion failed: expected `{}`, got `{}`", expected, actual).into())
+            }
+        },
+    );
+
+    engine
+}
+
+/// Call a helper through a template expression, so it renders exactly like in profile templates
+fn call_helper(handlebars: &Handlebars, name: &str, params: Array, hash: Map) -> Result<String, Box<EvalAltResult>> {
+    let arguments = (0..params.len())
+        .map(|index| format!(" params.[{}]", index))
+        .chain(hash.keys().map(|key| format!(" {}=hash.[{}]", key, key)))
+        .collect::<String>();
+
+    let data = json!({
+        "params": from_dynamic::<Value>(&Dynamic::from_array(params))?,
+        "hash": from_dynamic::<Value>(&Dynamic::from_map(hash))?,
+    });
+
+    handlebars
+        .render_template(&format!("{{{{{{{}{}}}}}}}", name, arguments), &data)
+        .map_err(|e| format!("Helper `{}` failed: {}", name, e).into())
+}
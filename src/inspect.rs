@@ -0,0 +1,233 @@
+use crate::lint::CtGenLintIssue;
+use crate::profile::{CtGenProfile, CtGenProfileConfig, CtGenPrompt, CtGenTarget};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Overview of a registered profile, its prompts, targets, resolved directories and validation findings
+pub struct CtGenProfileInspection {
+    /// Registered profile name
+    name: String,
+    /// Profile config file path
+    config_file: String,
+    /// Canonical profile directory
+    context_dir: String,
+    /// Canonical templates directory
+    templates_dir: String,
+    /// Canonical scripts directory
+    scripts_dir: String,
+    /// Profile parameters
+    configuration: CtGenProfileConfig,
+    /// Prompts by id, in asking order
+    prompts: IndexMap<String, CtGenPrompt>,
+    /// Targets by name, in rendering order
+    targets: IndexMap<String, CtGenTarget>,
+    /// Validation findings of declared paths
+    findings: Vec<CtGenLintIssue>,
+}
+
+impl CtGenProfileInspection {
+    /// Registered profile name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Profile config file path
+    pub fn config_file(&self) -> &str {
+        &self.config_file
+    }
+    /// Canonical profile directory
+    pub fn context_dir(&self) -> &str {
+        &self.context_dir
+    }
+    /// Canonical templates directory
+    pub fn templates_dir(&self) -> &str {
+        &self.templates_dir
+    }
+    /// Canonical scripts directory
+    pub fn scripts_dir(&self) -> &str {
+        &self.scripts_dir
+    }
+    /// Profile parameters
+    pub fn configuration(&self) -> &CtGenProfileConfig {
+        &self.configuration
+    }
+    /// Prompts by id, in asking order
+    pub fn prompts(&self) -> &IndexMap<String, CtGenPrompt> {
+        &self.prompts
+    }
+    /// Targets by name, in rendering order
+    pub fn targets(&self) -> &IndexMap<String, CtGenTarget> {
+        &self.targets
+    }
+    /// Validation findings of declared paths
+    pub fn findings(&self) -> &Vec<CtGenLintIssue> {
+        &self.findings
+    }
+
+    /// Check if the profile passes validation
+    pub fn is_valid(&self) -> bool {
+        !self.findings.iter().any(CtGenLintIssue::is_error)
+    }
+}
+
+impl Display for CtGenProfileInspection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let configuration = &self.configuration;
+
+        writeln!(f, "Profile:        {}", self.name)?;
+        if let Some(version) = configuration.version() {
+            writeln!(f, "Version:        {}", version)?;
+        }
+        writeln!(f, "Config file:    {}", self.config_file)?;
+        writeln!(f, "Profile dir:    {}", self.context_dir)?;
+        writeln!(f, "Templates dir:  {}", self.templates_dir)?;
+        writeln!(f, "Scripts dir:    {}", self.scripts_dir)?;
+        writeln!(f, "Target dir:     {}", configuration.target_dir())?;
+        writeln!(f, "Env file:       {} ({})", configuration.env_file(), configuration.env_var())?;
+
+        writeln!(f, "Prompts:")?;
+        if self.prompts.is_empty() {
+            writeln!(f, "  none")?;
+        }
+        for (id, prompt) in self.prompts.iter() {
+            let mut flags = Vec::new();
+            if prompt.multiple() {
+                flags.push("multiple");
+            }
+            if prompt.required() {
+                flags.push("required");
+            }
+            if prompt.secret() {
+                flags.push("secret");
+            }
+            if prompt.must_exist() {
+                flags.push("must-exist");
+            }
+            if prompt.create_if_missing() {
+                flags.push("create-if-missing");
+            }
+
+            writeln!(
+                f,
+                "  {}: {}{}",
+                id,
+                prompt.prompt(),
+                if flags.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", flags.join(", "))
+                }
+            )?;
+            if !prompt.kind().is_default() {
+                writeln!(f, "    type:      {}", prompt.kind())?;
+            }
+            if let Some(source) = prompt.source() {
+                writeln!(f, "    source:    {}", source)?;
+            }
+            if !prompt.labels().is_empty() {
+                writeln!(f, "    labels:    {}", prompt.labels())?;
+            }
+            if let Some(group) = prompt.group() {
+                writeln!(f, "    group:     {}", group)?;
+            }
+            if let Some(order) = prompt.order() {
+                writeln!(f, "    order:     {}", order)?;
+            }
+            if prompt.options() != &CtGenPrompt::default_options() {
+                writeln!(f, "    options:   {}", prompt.options())?;
+            }
+            if let Some(condition) = prompt.condition() {
+                writeln!(f, "    condition: {}", condition)?;
+            }
+            if let Some(default) = prompt.default() {
+                writeln!(f, "    default:   {}", default)?;
+            }
+            if let Some(transform) = prompt.transform() {
+                writeln!(f, "    transform: {}", transform)?;
+            }
+            if let Some(enumerate) = prompt.enumerate() {
+                writeln!(f, "    enumerate: {}", enumerate)?;
+            }
+            if !prompt.platforms().is_empty() {
+                writeln!(f, "    platforms: {}", prompt.platforms().join(", "))?;
+            }
+            if let Some(when) = prompt.when() {
+                writeln!(f, "    when: {}", when)?;
+            }
+        }
+
+        writeln!(f, "Targets:")?;
+        if self.targets.is_empty() {
+            writeln!(f, "  none")?;
+        }
+        for (name, target) in self.targets.iter() {
+            writeln!(
+                f,
+                "  {}: {} -> {} ({}, {})",
+                name,
+                if target.is_copy() {
+                    target.source().unwrap_or_default()
+                } else {
+                    target.template()
+                },
+                target.target(),
+                target.kind(),
+                target.mode()
+            )?;
+            if let Some(condition) = target.condition() {
+                writeln!(f, "    condition: {}", condition)?;
+            }
+            if let Some(enumerate) = target.enumerate() {
+                writeln!(f, "    enumerate: {}", enumerate)?;
+            }
+            if let Some(formatter) = target.formatter() {
+                writeln!(f, "    formatter: {}", formatter)?;
+            }
+            if !target.platforms().is_empty() {
+                writeln!(f, "    platforms: {}", target.platforms().join(", "))?;
+            }
+            if let Some(when) = target.when() {
+                writeln!(f, "    when: {}", when)?;
+            }
+        }
+
+        if self.findings.is_empty() {
+            write!(f, "Validation:     ok")
+        } else {
+            write!(
+                f,
+                "Validation:     {}",
+                if self.is_valid() { "ok, with warnings" } else { "failed" }
+            )?;
+            for finding in self.findings.iter() {
+                write!(f, "\n  {}", finding)?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Inspect a loaded profile without connecting to a database
+pub async fn inspect(profile: &CtGenProfile, config_file: &str) -> CtGenProfileInspection {
+    CtGenProfileInspection {
+        name: profile.name().to_string(),
+        config_file: config_file.to_string(),
+        context_dir: profile.context_dir().to_string(),
+        templates_dir: profile.templates_dir(),
+        scripts_dir: profile.scripts_dir(),
+        configuration: profile.configuration().clone(),
+        prompts: profile
+            .ordered_prompts()
+            .unwrap_or_else(|_| profile.prompts().collect())
+            .into_iter()
+            .filter_map(|id| Some((id.clone(), profile.prompt(id)?.clone())))
+            .collect(),
+        targets: profile
+            .targets()
+            .filter_map(|name| Some((name.clone(), profile.target(name)?.clone())))
+            .collect(),
+        findings: profile.findings().await,
+    }
+}
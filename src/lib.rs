@@ -1,24 +1,61 @@
+pub mod changelog;
 pub mod consts;
+pub mod coverage;
+pub mod defaults;
 pub mod error;
+pub mod explain;
+pub mod helper_test;
+pub mod inspect;
+pub mod lint;
+pub mod manifest;
+pub mod pack;
 pub mod profile;
+pub mod registry;
+pub mod server;
+pub mod snapshot;
 pub mod task;
+pub mod theme;
+pub mod verify;
 
 use crate::consts::*;
+use crate::coverage::CtGenCoverageReport;
+use crate::defaults::CtGenDefaults;
 use crate::error::CtGenError;
-use crate::profile::{CtGenProfile, CtGenProfileConfigOverrides};
-use crate::task::CtGenTask;
+use crate::explain::CtGenTargetExplanation;
+use crate::helper_test::CtGenHelperTestReport;
+use crate::inspect::CtGenProfileInspection;
+use crate::lint::CtGenLintIssue;
+use crate::pack::CtGenPack;
+use crate::profile::editor::CtGenProfileEditor;
+use crate::profile::package;
+use crate::profile::starter::CtGenStarterKit;
+use crate::profile::{CtGenProfile, CtGenProfileConfigOverrides, CtGenPrompt, CtGenTarget};
+use crate::registry::{CtGenProfileRegistration, CtGenRegistryIssue, CtGenRegistryStatus};
+use crate::snapshot::CtGenSnapshotReport;
+use crate::task::archive::{self, CtGenArchiveEntry, CtGenArchiveFormat};
+use crate::task::frontmatter::CtGenTemplateMeta;
+use crate::task::renderer::CtGenHelperInfo;
+use crate::task::{frontmatter, renderer, CtGenTask};
 use anyhow::Result;
+use database_reflection::adapter::mariadb_innodb::MariadbInnodbReflectionAdapter;
+use database_reflection::adapter::reflection_adapter::{ReflectionAdapter, ReflectionAdapterUninitialized};
 use indexmap::IndexMap;
 use regex::Regex;
+use serde_json::Value;
 use std::env;
-use std::path::MAIN_SEPARATOR;
-use std::sync::LazyLock;
+use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::{LazyLock, OnceLock};
 use tokio::io::AsyncWriteExt;
 
+/// Config dir set for this process, overriding the env var and the user config dir
+static CONFIG_DIR: OnceLock<String> = OnceLock::new();
+
 #[derive(Clone, Default, Debug)]
 pub struct CtGen {
     config_file: String,
     profiles: IndexMap<String, String>,
+    registrations: IndexMap<String, CtGenProfileRegistration>,
+    defaults: CtGenDefaults,
     current_profile: Option<CtGenProfile>,
 }
 
@@ -39,17 +76,30 @@ impl CtGen {
             CtGen::init_config_file(&config_file).await?;
         }
 
-        let profiles = CtGen::load_profiles(&config_file).await?;
+        let (profiles, registrations) = CtGen::load_profiles(&config_file).await?;
+
+        let defaults = CtGenDefaults::load(&CtGen::get_filepath(&config_path, DEFAULTS_FILE_NAME)).await?;
 
         Ok(Self {
             config_file,
             profiles,
+            registrations,
+            defaults,
             ..Default::default()
         })
     }
 
-    /// Resolve and get path to store config files
+    /// Resolve and get path to store config files. A dir set with [`CtGen::set_config_dir`] comes first, then the
+    /// `CTGEN_CONFIG_DIR` env var, then the user config dir
     pub fn get_config_dir() -> Result<String> {
+        if let Some(config_dir) = CONFIG_DIR.get() {
+            return Ok(config_dir.clone());
+        }
+
+        if let Some(config_dir) = env::var(ENV_CONFIG_DIR).ok().filter(|dir| !dir.is_empty()) {
+            return CtGen::resolve_config_dir(&config_dir);
+        }
+
         let path = dirs::config_dir().ok_or_else(|| CtGenError::InitError("Failed to get config directory.".to_string()))?;
 
         Ok(format!(
@@ -61,6 +111,23 @@ impl CtGen {
         ))
     }
 
+    /// Use another config dir for the registry, defaults and installed profiles and packs, like a workspace-local one.
+    /// Must be set before [`CtGen::new`] and only once
+    pub fn set_config_dir(config_dir: &str) -> Result<()> {
+        CONFIG_DIR
+            .set(CtGen::resolve_config_dir(config_dir)?)
+            .map_err(|_| CtGenError::InitError("Config directory is already set.".to_string()).into())
+    }
+
+    /// Absolute path of a config dir, relative ones resolved against the current working directory
+    fn resolve_config_dir(config_dir: &str) -> Result<String> {
+        Ok(
+            CtGen::normalize_path(&Path::new(&CtGen::get_current_working_dir()?).join(config_dir).to_string_lossy())
+                .to_string_lossy()
+                .to_string(),
+        )
+    }
+
     /// Resolve and get current working directory
     pub fn get_current_working_dir() -> Result<String> {
         Ok(env::current_dir()
@@ -77,15 +144,7 @@ impl CtGen {
 
     /// Get canonical path
     pub async fn get_realpath(path: &str) -> Result<String> {
-        let mut path = path.to_string();
-
-        if path.starts_with('~') {
-            if let Ok(home) = env::var("HOME") {
-                path.remove(0);
-                path.insert(0, MAIN_SEPARATOR);
-                path.insert_str(0, &home);
-            }
-        }
+        let path = CtGen::expand_home(path);
 
         Ok(tokio::fs::canonicalize(&path)
             .await
@@ -95,6 +154,45 @@ impl CtGen {
             .ok_or_else(|| CtGenError::RuntimeError(format!("Failed to parse UTC-8 path: {:?}", path)))?)
     }
 
+    /// Expand a leading `~` of a path to the home directory
+    pub fn expand_home(path: &str) -> String {
+        match (path.strip_prefix('~'), env::var("HOME")) {
+            (Some(rest), Ok(home)) if rest.is_empty() || rest.starts_with(std::path::is_separator) => format!("{}{}", home, rest),
+            _ => path.to_string(),
+        }
+    }
+
+    /// Lexically normalize a path, so paths of files that don't exist yet can be compared
+    pub fn normalize_path(path: &str) -> PathBuf {
+        let mut normalized = PathBuf::new();
+
+        for component in Path::new(path).components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                component => normalized.push(component),
+            }
+        }
+
+        normalized
+    }
+
+    /// Check if ctgen runs on one of the given platforms, like `linux`, `macos`, `windows` or the `unix` family. Any
+    /// platform matches an empty list
+    pub fn is_current_platform(platforms: &[String]) -> bool {
+        platforms.is_empty()
+            || platforms
+                .iter()
+                .any(|platform| platform == env::consts::OS || platform == env::consts::FAMILY)
+    }
+
+    /// Check if a path stays inside a base directory once normalized
+    pub fn is_path_within(base: &str, path: &str) -> bool {
+        CtGen::normalize_path(path).starts_with(CtGen::normalize_path(base))
+    }
+
     /// Get full canonical filepath and filename
     pub async fn get_real_filepath(path: &str, file: &str) -> Result<String> {
         CtGen::get_realpath(&CtGen::get_filepath(path, file)).await
@@ -160,11 +258,20 @@ impl CtGen {
         &RE
     }
 
-    /// Load profiles config file
-    async fn load_profiles(config_file: &str) -> Result<IndexMap<String, String>> {
+    /// Compile a table name glob pattern, where `*` matches any sequence and `?` matches a single character
+    pub fn get_pattern_regex(pattern: &str) -> Result<Regex> {
+        let expression = format!("^{}$", regex::escape(pattern).replace("\\*", ".*").replace("\\?", "."));
+
+        Ok(Regex::new(&expression).map_err(|e| CtGenError::ValidationError(format!("Invalid table pattern `{}`: {}", pattern, e)))?)
+    }
+
+    /// Load profiles config file. Profiles are either a config file path, or a table with the `file` path and registration
+    /// metadata. Alias entries have `alias-of` instead of a `file` path
+    async fn load_profiles(config_file: &str) -> Result<(IndexMap<String, String>, IndexMap<String, CtGenProfileRegistration>)> {
         match tokio::fs::read_to_string(config_file).await {
             Ok(c) => {
                 let mut profiles: IndexMap<String, String> = IndexMap::new();
+                let mut registrations: IndexMap<String, CtGenProfileRegistration> = IndexMap::new();
 
                 let config = c
                     .parse::<toml::Table>()
@@ -172,15 +279,36 @@ impl CtGen {
 
                 if let Some(config_profiles) = config.get("profiles") {
                     if config_profiles.is_table() {
-                        for (profile_name, profile_file) in config_profiles
+                        for (profile_name, profile_entry) in config_profiles
                             .as_table()
                             .ok_or_else(|| CtGenError::ValidationError("Invalid profiles table.".to_string()))?
                             .iter()
                         {
+                            let profile_file = match profile_entry {
+                                toml::Value::Table(entry) => {
+                                    let registration: CtGenProfileRegistration =
+                                        toml::Value::Table(entry.clone()).try_into().map_err(|e| {
+                                            CtGenError::ValidationError(format!(
+                                                "Invalid registration of profile `{}`: {}",
+                                                profile_name, e
+                                            ))
+                                        })?;
+                                    let is_alias = registration.alias_of().is_some();
+
+                                    registrations.insert(profile_name.to_string(), registration);
+
+                                    if is_alias {
+                                        continue;
+                                    }
+
+                                    entry.get("file").and_then(toml::Value::as_str)
+                                }
+                                entry => entry.as_str(),
+                            };
+
                             profiles.insert(
                                 profile_name.to_string(),
                                 profile_file
-                                    .as_str()
                                     .ok_or_else(|| {
                                         CtGenError::ValidationError(format!("Invalid profile file for profile `{}`.", profile_name))
                                     })?
@@ -190,7 +318,7 @@ impl CtGen {
                     }
                 }
 
-                Ok(profiles)
+                Ok((profiles, registrations))
             }
             Err(e) => Err(CtGenError::InitError(format!("Failed to load profiles: {}", e)).into()),
         }
@@ -201,7 +329,29 @@ impl CtGen {
         let mut profiles_config = toml::map::Map::new();
         let mut profiles = toml::Table::new();
         for (profile_name, profile_file) in self.profiles.iter() {
-            profiles.insert(profile_name.to_string(), toml::Value::String(profile_file.to_string()));
+            let mut entry = toml::Table::new();
+            entry.insert("file".to_string(), toml::Value::String(profile_file.to_string()));
+
+            if let Some(registration) = self.registrations.get(profile_name) {
+                if let toml::Value::Table(registration) = toml::Value::try_from(registration)
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to generate toml file: {}", e)))?
+                {
+                    entry.extend(registration);
+                }
+            }
+
+            profiles.insert(profile_name.to_string(), toml::Value::Table(entry));
+        }
+
+        // alias entries go after the profiles
+        for (alias, registration) in self.registrations.iter() {
+            if registration.alias_of().is_some() && !self.profiles.contains_key(alias) {
+                profiles.insert(
+                    alias.to_string(),
+                    toml::Value::try_from(registration)
+                        .map_err(|e| CtGenError::RuntimeError(format!("Failed to generate toml file: {}", e)))?,
+                );
+            }
         }
 
         profiles_config.insert("profiles".to_string(), toml::Value::Table(profiles));
@@ -227,13 +377,88 @@ impl CtGen {
         Ok(())
     }
 
+    /// Get user level defaults
+    pub fn get_defaults(&self) -> &CtGenDefaults {
+        &self.defaults
+    }
+
     /// Get a list of loaded profiles
     pub fn get_profiles(&self) -> &IndexMap<String, String> {
         &self.profiles
     }
 
+    /// Check registered profiles for missing config files, config files registered under several names and config files
+    /// changed since they were registered
+    pub async fn check_profiles(&self) -> Vec<CtGenRegistryStatus> {
+        let mut statuses = Vec::new();
+
+        for (name, file) in self.profiles.iter() {
+            let registration = self.registrations.get(name).cloned().unwrap_or_default();
+
+            let mut issues = Vec::new();
+
+            match tokio::fs::read(file).await {
+                Ok(content) => {
+                    if registration.is_stale(&content) {
+                        issues.push(CtGenRegistryIssue::Stale);
+                    }
+                }
+                Err(_) => issues.push(CtGenRegistryIssue::Missing),
+            }
+
+            let duplicates = self
+                .profiles
+                .iter()
+                .filter(|(other, other_file)| *other != name && CtGen::normalize_path(other_file) == CtGen::normalize_path(file))
+                .map(|(other, _)| other.to_string())
+                .collect::<Vec<String>>();
+
+            if !duplicates.is_empty() {
+                issues.push(CtGenRegistryIssue::Duplicate { profiles: duplicates });
+            }
+
+            if let Some(message) = registration.deprecated() {
+                issues.push(CtGenRegistryIssue::Deprecated {
+                    message: message.to_string(),
+                });
+            }
+
+            statuses.push(CtGenRegistryStatus::new(name, file, registration, issues));
+        }
+
+        // aliases report the config file of the profile they stand for
+        for (alias, registration) in self.registrations.iter() {
+            if registration.alias_of().is_none() || self.profiles.contains_key(alias) {
+                continue;
+            }
+
+            let mut issues = Vec::new();
+
+            let file = self.alias_file(alias).cloned().unwrap_or_else(|| {
+                issues.push(CtGenRegistryIssue::Missing);
+                String::new()
+            });
+
+            if let Some(message) = registration.deprecated() {
+                issues.push(CtGenRegistryIssue::Deprecated {
+                    message: message.to_string(),
+                });
+            }
+
+            statuses.push(CtGenRegistryStatus::new(alias, &file, registration.clone(), issues));
+        }
+
+        statuses
+    }
+
     /// Add a new profile or replace existing
     pub async fn add_profile(&mut self, name: &str, path: &str) -> Result<CtGenProfile> {
+        self.register_profile(name, path, "add").await
+    }
+
+    /// Add a new profile or replace existing, recording where it was registered from. A config file can only be
+    /// registered under one name
+    async fn register_profile(&mut self, name: &str, path: &str, source: &str) -> Result<CtGenProfile> {
         // validate name
         let regex = CtGen::get_name_regex();
 
@@ -270,8 +495,32 @@ impl CtGen {
         // if no name is given, we use the profile internal name
         let name = if name.is_empty() { profile.configuration().name() } else { name };
 
+        // one config file, one profile
+        if let Some(other) = self.profiles.iter().find_map(|(other, file)| {
+            if other != name && CtGen::normalize_path(file) == CtGen::normalize_path(&fullpath) {
+                Some(other)
+            } else {
+                None
+            }
+        }) {
+            return Err(CtGenError::ValidationError(format!(
+                "Profile config file {} is already registered as `{}`. Rename it, or remove it first with `ctgen config rm {}`",
+                fullpath, other, other
+            ))
+            .into());
+        }
+
+        let content = tokio::fs::read(&fullpath)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to read profile config file {}: {}", fullpath, e)))?;
+
+        // deprecation outlives registering again
+        let mut registration = CtGenProfileRegistration::new(source, &content);
+        registration.set_deprecated(self.registrations.get(name).and_then(CtGenProfileRegistration::deprecated));
+
         // set profile
         self.profiles.insert(name.to_string(), fullpath.clone());
+        self.registrations.insert(name.to_string(), registration);
 
         // save profiles
         self.save_profiles().await?;
@@ -279,12 +528,121 @@ impl CtGen {
         Ok(profile)
     }
 
+    /// Install a profile from a `.ctgen` archive into the config dir and add it. If no name is given, the archived
+    /// profile name is used. Installing again replaces the previously installed files
+    pub async fn import_profile(&mut self, name: &str, archive_file: &str) -> Result<CtGenProfile> {
+        let archive_file = CtGen::get_realpath(archive_file).await?;
+        let source = archive_file.clone();
+
+        let entries = tokio::task::spawn_blocking(move || archive::read_archive(&archive_file, CtGenArchiveFormat::TarGz)).await??;
+
+        let name = if name.is_empty() {
+            package::profile_name(&entries)?
+        } else {
+            name.to_string()
+        };
+
+        // the name becomes a dir name, so it is validated before anything is written
+        if !CtGen::get_name_regex().is_match(&name) {
+            return Err(CtGenError::ValidationError(format!(
+                "Invalid profile name: {}. Make sure it matches {}",
+                name, CONFIG_NAME_PATTERN
+            ))
+            .into());
+        }
+
+        let profile_dir = CtGen::get_filepath(&CtGen::get_filepath(&CtGen::get_config_dir()?, PROFILES_DIR_NAME), &name);
+
+        // the archive is extracted and validated next to the installed profile, which is only replaced once it passes
+        let staging_dir = format!("{}.import-{}", profile_dir, std::process::id());
+
+        if let Err(e) = CtGen::stage_profile(&entries, &staging_dir, &name).await {
+            let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+            return Err(e);
+        }
+
+        let previous_dir = format!("{}.previous-{}", profile_dir, std::process::id());
+        let replaced = CtGen::file_exists(&profile_dir).await;
+
+        if replaced {
+            tokio::fs::rename(&profile_dir, &previous_dir)
+                .await
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to move previously installed profile {}: {}", profile_dir, e)))?;
+        }
+
+        let installed = match tokio::fs::rename(&staging_dir, &profile_dir).await {
+            Ok(()) => {
+                self.register_profile(&name, &CtGen::get_filepath(&profile_dir, PROFILE_DEFAULT_FILENAME), &source)
+                    .await
+            }
+            Err(e) => Err(CtGenError::RuntimeError(format!("Failed to install profile {}: {}", profile_dir, e)).into()),
+        };
+
+        match installed {
+            Ok(profile) => {
+                if replaced {
+                    let _ = tokio::fs::remove_dir_all(&previous_dir).await;
+                }
+
+                Ok(profile)
+            }
+            Err(e) => {
+                // put the previously installed profile back
+                let _ = tokio::fs::remove_dir_all(&staging_dir).await;
+
+                if replaced {
+                    let _ = tokio::fs::remove_dir_all(&profile_dir).await;
+                    let _ = tokio::fs::rename(&previous_dir, &profile_dir).await;
+                }
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Extract profile archive entries into a staging dir and check the profile they hold loads and validates
+    async fn stage_profile(entries: &[CtGenArchiveEntry], staging_dir: &str, name: &str) -> Result<()> {
+        if CtGen::file_exists(staging_dir).await {
+            tokio::fs::remove_dir_all(staging_dir).await?;
+        }
+
+        package::extract(entries, staging_dir).await?;
+
+        CtGenProfile::load(&CtGen::get_filepath(staging_dir, PROFILE_DEFAULT_FILENAME), name)
+            .await?
+            .validate()
+            .await
+    }
+
+    /// Bundle a profile config, templates, scripts and asset files into a single `.ctgen` archive. Returns the number of
+    /// bundled files
+    pub async fn export_profile(&self, name: &str, archive_file: &str) -> Result<usize> {
+        if let Some(profile_path) = self.profiles.get(name) {
+            let profile = CtGenProfile::load(profile_path, name).await?;
+
+            let entries = package::collect(&profile, profile_path).await?;
+            let count = entries.len();
+
+            let archive_file = archive_file.to_string();
+            let mtime = chrono::Utc::now().timestamp().max(0) as u64;
+
+            tokio::task::spawn_blocking(move || archive::write_archive_as(&archive_file, CtGenArchiveFormat::TarGz, &entries, mtime))
+                .await??;
+
+            Ok(count)
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
     /// Remove configuration profile
     pub async fn remove_profile(&mut self, name: &str) -> Result<()> {
         if self.profiles.contains_key(name) {
             self.profiles.swap_remove(name);
         }
 
+        self.registrations.shift_remove(name);
+
         if let Some(profile) = self.current_profile.clone() {
             if profile.name() == name {
                 self.current_profile = None;
@@ -294,8 +652,176 @@ impl CtGen {
         self.save_profiles().await
     }
 
-    /// Load configuration profile
+    /// Rename a registered profile, keeping its place in the registry. The default profile is the one named `default`, so
+    /// it can't be renamed, and no profile can be renamed to replace it
+    pub async fn rename_profile(&mut self, name: &str, new_name: &str) -> Result<()> {
+        self.validate_new_profile_name(new_name)?;
+
+        if name == CONFIG_NAME_DEFAULT {
+            return Err(CtGenError::ValidationError(
+                "The default profile can't be renamed. Add another profile with --default to replace it".to_string(),
+            )
+            .into());
+        }
+
+        let (index, _, profile_file) = self
+            .profiles
+            .shift_remove_full(name)
+            .ok_or_else(|| CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()))?;
+
+        self.profiles.shift_insert(index, new_name.to_string(), profile_file);
+
+        if let Some(registration) = self.registrations.shift_remove(name) {
+            self.registrations.insert(new_name.to_string(), registration);
+        }
+
+        if self.current_profile.as_ref().is_some_and(|profile| profile.name() == name) {
+            self.current_profile = None;
+        }
+
+        self.save_profiles().await
+    }
+
+    /// Copy the files of a registered profile into a new profile dir and register the copy right after the original. The
+    /// config file is copied as `Ctgen.toml`, along with everything `config export` would bundle
+    pub async fn copy_profile(&mut self, name: &str, path: &str, new_name: &str) -> Result<CtGenProfile> {
+        self.validate_new_profile_name(new_name)?;
+
+        let (index, _, profile_file) = self
+            .profiles
+            .get_full(name)
+            .ok_or_else(|| CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()))?;
+        let profile_file = profile_file.clone();
+
+        let profile_dir = CtGen::normalize_path(&Path::new(&CtGen::get_current_working_dir()?).join(path).to_string_lossy())
+            .to_string_lossy()
+            .to_string();
+
+        if CtGen::file_exists(&CtGen::get_filepath(&profile_dir, PROFILE_DEFAULT_FILENAME)).await {
+            return Err(CtGenError::ValidationError(format!("A profile already exists in {}", profile_dir)).into());
+        }
+
+        let profile = CtGenProfile::load(&profile_file, name).await?;
+
+        package::extract(&package::collect(&profile, &profile_file).await?, &profile_dir).await?;
+
+        let profile = self.register_profile(new_name, &profile_dir, &format!("copy of {}", name)).await?;
+
+        // added profiles go last, copies sit next to their original
+        self.profiles.move_index(self.profiles.len() - 1, index + 1);
+
+        self.save_profiles().await?;
+
+        Ok(profile)
+    }
+
+    /// Validate the name of a profile about to be registered by rename or copy
+    fn validate_new_profile_name(&self, name: &str) -> Result<()> {
+        if !CtGen::get_name_regex().is_match(name) {
+            return Err(CtGenError::ValidationError(format!(
+                "Invalid profile name: {}. Make sure it matches {}",
+                name, CONFIG_NAME_PATTERN
+            ))
+            .into());
+        }
+
+        if name == CONFIG_NAME_DEFAULT {
+            return Err(CtGenError::ValidationError(
+                "Profiles can't be renamed or copied to the default profile. Use `ctgen config add --default` instead".to_string(),
+            )
+            .into());
+        }
+
+        if self.profiles.contains_key(name) {
+            return Err(CtGenError::ValidationError(format!("Profile `{}` already exists", name)).into());
+        }
+
+        Ok(())
+    }
+
+    /// Install a pack of shared templates and scripts from a pack dir or its `ctgen-pack.toml`, replacing a previously
+    /// installed copy of the same version
+    pub async fn add_pack(&self, path: &str) -> Result<CtGenPack> {
+        let path = CtGen::get_realpath(path).await?;
+
+        pack::install(&CtGenPack::load(&path)?).await
+    }
+
+    /// List installed packs, sorted by name and version, with a warning for every malformed pack that was skipped
+    pub fn list_packs(&self) -> Result<(Vec<CtGenPack>, Vec<String>)> {
+        pack::installed()
+    }
+
+    /// Remove installed packs matching a reference like `acme-helpers` or `acme-helpers@1`. Returns the removed packs
+    pub async fn remove_pack(&self, reference: &str) -> Result<Vec<CtGenPack>> {
+        let packs = pack::installed()?
+            .0
+            .into_iter()
+            .filter(|pack| pack.satisfies(reference))
+            .collect::<Vec<CtGenPack>>();
+
+        if packs.is_empty() {
+            return Err(CtGenError::ValidationError(format!("Pack `{}` is not installed", reference)).into());
+        }
+
+        for pack in packs.iter() {
+            tokio::fs::remove_dir_all(pack.dir())
+                .await
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to remove pack {}: {}", pack, e)))?;
+        }
+
+        Ok(packs)
+    }
+
+    /// Resolve a profile name through alias entries. Returns the deprecation notices of every deprecated entry on the
+    /// way along with the resolved name
+    pub fn resolve_profile_name(&self, name: &str) -> Result<(String, Vec<String>)> {
+        let mut resolved = name.to_string();
+        let mut visited: Vec<String> = Vec::new();
+        let mut notices = Vec::new();
+
+        loop {
+            if visited.contains(&resolved) {
+                return Err(CtGenError::ValidationError(format!("Profile alias cycle: {} -> {}", visited.join(" -> "), resolved)).into());
+            }
+
+            let registration = self.registrations.get(&resolved);
+
+            if let Some(deprecated) = registration.and_then(CtGenProfileRegistration::deprecated) {
+                notices.push(format!("Profile `{}` is deprecated: {}", resolved, deprecated));
+            }
+
+            match registration.and_then(CtGenProfileRegistration::alias_of) {
+                Some(alias_of) if !self.profiles.contains_key(&resolved) => {
+                    let alias_of = alias_of.to_string();
+                    visited.push(resolved);
+                    resolved = alias_of;
+                }
+                _ => return Ok((resolved, notices)),
+            }
+        }
+    }
+
+    /// Config file of the profile an alias stands for, if the alias resolves
+    fn alias_file(&self, alias: &str) -> Option<&String> {
+        let mut resolved = alias;
+
+        // bounded, so alias cycles end unresolved
+        for _ in 0..=self.registrations.len() {
+            if let Some(file) = self.profiles.get(resolved) {
+                return Some(file);
+            }
+
+            resolved = self.registrations.get(resolved)?.alias_of()?;
+        }
+
+        None
+    }
+
+    /// Load configuration profile. Aliases resolve to the profile they stand for
     pub async fn set_current_profile(&mut self, name: &str) -> Result<&CtGenProfile> {
+        let (name, _) = &self.resolve_profile_name(name)?;
+
         if let Some(profile_path) = self.profiles.get(name) {
             let profile = CtGenProfile::load(profile_path, name).await?;
             profile.validate().await?;
@@ -310,28 +836,179 @@ impl CtGen {
         }
     }
 
+    /// Load configuration profile in degraded mode. Targets with missing templates are disabled and reported as warnings
+    pub async fn set_current_profile_skip_missing(&mut self, name: &str) -> Result<Vec<CtGenLintIssue>> {
+        let (name, _) = &self.resolve_profile_name(name)?;
+
+        if let Some(profile_path) = self.profiles.get(name) {
+            let mut profile = CtGenProfile::load(profile_path, name).await?;
+            let skipped = profile.skip_missing_targets().await;
+            profile.validate().await?;
+
+            self.current_profile = Some(profile);
+
+            Ok(skipped)
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
     /// Get currently loaded configuration profile
     pub fn get_current_profile(&self) -> Option<&CtGenProfile> {
         self.current_profile.as_ref()
     }
 
+    /// Inspect configuration profile prompts, targets, resolved directories and validation status
+    pub async fn inspect_profile(&self, name: &str) -> Result<CtGenProfileInspection> {
+        if let Some(profile_path) = self.profiles.get(name) {
+            let profile = CtGenProfile::load(profile_path, name).await?;
+
+            Ok(inspect::inspect(&profile, profile_path).await)
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
+    /// Lint configuration profile templates and scripts without connecting to a database
+    pub async fn lint_profile(&self, name: &str) -> Result<Vec<CtGenLintIssue>> {
+        if let Some(profile_path) = self.profiles.get(name) {
+            let profile = CtGenProfile::load(profile_path, name).await?;
+
+            lint::lint_profile(&profile).await
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
+    /// Explain a configuration profile target without connecting to a database
+    pub async fn explain_target(&self, name: &str, target: &str) -> Result<CtGenTargetExplanation> {
+        if let Some(profile_path) = self.profiles.get(name) {
+            let profile = CtGenProfile::load(profile_path, name).await?;
+
+            explain::explain_target(&profile, target).await
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
+    /// Report context fields and conditional branches covered by configuration profile templates for the given contexts
+    pub async fn coverage(&self, name: &str, contexts: &[Value]) -> Result<CtGenCoverageReport> {
+        if let Some(profile_path) = self.profiles.get(name) {
+            let profile = CtGenProfile::load(profile_path, name).await?;
+
+            coverage::coverage(&profile, contexts).await
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
+    /// Compare configuration profile outputs for its test case contexts with the expected outputs, optionally rewriting them
+    pub async fn test(&self, name: &str, update: bool) -> Result<CtGenSnapshotReport> {
+        if let Some(profile_path) = self.profiles.get(name) {
+            let profile = CtGenProfile::load(profile_path, name).await?;
+
+            snapshot::test(&profile, update).await
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
+    /// Run the helper test scripts of a configuration profile
+    pub async fn test_helpers(&self, name: &str) -> Result<CtGenHelperTestReport> {
+        if let Some(profile_path) = self.profiles.get(name) {
+            let profile = CtGenProfile::load(profile_path, name).await?;
+
+            helper_test::test_helpers(&profile)
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
+    /// List helpers available to configuration profile templates
+    pub async fn list_helpers(&self, name: &str) -> Result<Vec<CtGenHelperInfo>> {
+        if let Some(profile_path) = self.profiles.get(name) {
+            let profile = CtGenProfile::load(profile_path, name).await?;
+
+            Ok(renderer::list_helpers(&profile))
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
     /// Initialize new configuration profile
     pub async fn init_profile(&mut self, path: &str, name: &str) -> Result<CtGenProfile> {
+        self.init_profile_with_kit(path, name, KIT_BLANK).await
+    }
+
+    /// Initialize new configuration profile with the templates of a bundled starter kit
+    pub async fn init_profile_with_kit(&mut self, path: &str, name: &str, kit: &str) -> Result<CtGenProfile> {
+        let kit = CtGenStarterKit::get(kit)?;
+
+        let fullpath = CtGen::init_profile_path(path).await?;
+
+        let profile = CtGenProfile::new_from_kit(&fullpath, name, &kit, None);
+
+        self.create_profile(profile, kit.templates(None)).await
+    }
+
+    /// Initialize new configuration profile with the templates of a bundled starter kit, typed after the columns of a
+    /// table reflected from the given DSN. The DSN is only used for reflection and is not stored in the profile
+    pub async fn init_profile_from_table(
+        &mut self,
+        path: &str,
+        name: &str,
+        kit: &str,
+        dsn: &str,
+        table_name: &str,
+    ) -> Result<CtGenProfile> {
+        let kit = CtGenStarterKit::get(kit)?;
+
+        if kit.language().is_none() {
+            return Err(CtGenError::ValidationError(format!("Starter kit `{}` can't be generated from a table", kit.name())).into());
+        }
+
+        let reflection_adapter = MariadbInnodbReflectionAdapter::new(dsn).connect().await?;
+
+        if reflection_adapter.get_database_name().is_empty() {
+            return Err(CtGenError::ValidationError("DSN has no database name".to_string()).into());
+        }
+
+        if !reflection_adapter.list_table_names().await?.iter().any(|t| t == table_name) {
+            return Err(CtGenError::ValidationError("Table does not exist".to_string()).into());
+        }
+
+        let table = reflection_adapter.get_table_reflection(table_name).await?;
+        let table =
+            serde_json::to_value(&table).map_err(|e| CtGenError::RuntimeError(format!("Failed to serialize table reflection: {}", e)))?;
+
+        let fullpath = CtGen::init_profile_path(path).await?;
+
+        let profile = CtGenProfile::new_from_kit(&fullpath, name, &kit, Some(&table));
+
+        self.create_profile(profile, kit.templates(Some(&table))).await
+    }
+
+    /// Resolve the directory of a new configuration profile
+    async fn init_profile_path(path: &str) -> Result<String> {
         // validate name
         let regex = CtGen::get_name_regex();
 
-        let fullpath = if path == "." || path == "./" {
+        if path == "." || path == "./" {
             // default, cwd
-            CtGen::get_current_working_dir()?
+            CtGen::get_current_working_dir()
         } else if regex.is_match(path) {
             // just dir name, must create CWD/dirname if not exist
-            CtGen::get_filepath(&CtGen::get_current_working_dir()?, path)
+            Ok(CtGen::get_filepath(&CtGen::get_current_working_dir()?, path))
         } else {
             // resolve relative path
-            CtGen::get_realpath(path).await?
-        };
+            CtGen::get_realpath(path).await
+        }
+    }
 
-        let profile = CtGenProfile::new(&fullpath, name);
+    /// Write a new configuration profile with its template files and register it
+    async fn create_profile(&mut self, profile: CtGenProfile, templates: Vec<(String, String)>) -> Result<CtGenProfile> {
+        let fullpath = profile.context_dir().to_string();
+        let name = profile.name().to_string();
 
         CtGen::init_config_dir(&fullpath).await?;
         CtGen::init_config_dir(&profile.templates_dir()).await?;
@@ -362,32 +1039,104 @@ impl CtGen {
             .await
             .map_err(|e| CtGenError::RuntimeError(format!("Failed to flush toml file: {}", e)))?;
 
-        for target in profile.targets() {
-            let target = profile
-                .target(target)
-                .ok_or_else(|| CtGenError::ValidationError(format!("Target `{}` does not exist.", target)))?;
+        for (template, contents) in templates {
+            let template_file = CtGen::get_filepath(&profile.templates_dir(), &format!("{}{}", template, FILE_EXT_HBS));
 
-            let template_file = CtGen::get_filepath(&profile.templates_dir(), &format!("{}.hbs", target.template()));
+            CtGen::init_template_file(&template_file, &contents).await?;
+        }
 
-            let template = DUMMY_TEMPLATE;
+        self.register_profile(&name, &config_file, "init").await
+    }
 
-            let mut file = tokio::fs::OpenOptions::new()
-                .create_new(true)
-                .write(true)
-                .open(template_file)
-                .await
-                .map_err(|e| CtGenError::RuntimeError(format!("Failed to open template file: {}", e)))?;
+    /// Create a template file with the given contents
+    async fn init_template_file(template_file: &str, template: &str) -> Result<()> {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(template_file)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to open template file: {}", e)))?;
 
-            file.write_all(template.as_bytes())
-                .await
-                .map_err(|e| CtGenError::RuntimeError(format!("Failed to write template file: {}", e)))?;
+        file.write_all(template.as_bytes())
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to write template file: {}", e)))?;
 
-            file.flush()
+        file.flush()
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to flush template file: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Open configuration profile file for editing
+    pub async fn edit_profile(&self, name: &str) -> Result<CtGenProfileEditor> {
+        if let Some(profile_path) = self.profiles.get(name) {
+            CtGenProfileEditor::load(profile_path).await
+        } else {
+            Err(CtGenError::ValidationError("Invalid profile name. No such profile found".to_string()).into())
+        }
+    }
+
+    /// Add a new prompt to a configuration profile or replace existing. Call `refresh_registration` afterwards, so the
+    /// edit isn't reported as stale
+    pub async fn add_prompt(&self, profile_name: &str, id: &str, prompt: &CtGenPrompt) -> Result<()> {
+        let mut editor = self.edit_profile(profile_name).await?;
+
+        editor.add_prompt(id, prompt)?;
+        editor.save().await
+    }
+
+    /// Add a new target to a configuration profile or replace existing. Creates a dummy template file if missing, unless
+    /// the target copies an asset file. Call `refresh_registration` afterwards, so the edit isn't reported as stale
+    pub async fn add_target(&self, profile_name: &str, id: &str, target: &CtGenTarget) -> Result<()> {
+        let mut editor = self.edit_profile(profile_name).await?;
+
+        editor.add_target(id, target)?;
+
+        let profile = editor.profile(profile_name)?;
+
+        if !target.is_copy() {
+            let template_file = CtGen::get_filepath(&profile.templates_dir(), &format!("{}{}", target.template(), FILE_EXT_HBS));
+
+            if !CtGen::file_exists(&template_file).await {
+                CtGen::init_template_file(&template_file, DUMMY_TEMPLATE).await?;
+            }
+        }
+
+        editor.save().await
+    }
+
+    /// Record the current config file checksum of a profile edited by ctgen, so it isn't reported as stale
+    pub async fn refresh_registration(&mut self, profile_name: &str) -> Result<()> {
+        if let (Some(profile_file), Some(registration)) = (self.profiles.get(profile_name), self.registrations.get_mut(profile_name)) {
+            let content = tokio::fs::read(profile_file)
                 .await
-                .map_err(|e| CtGenError::RuntimeError(format!("Failed to flush template file: {}", e)))?;
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to read profile config file {}: {}", profile_file, e)))?;
+
+            registration.refresh(&content);
+
+            return self.save_profiles().await;
         }
 
-        self.add_profile(name, &config_file).await
+        Ok(())
+    }
+
+    /// Get the front matter of a configuration profile template, if the template exists and has any
+    pub async fn template_meta(&self, profile_name: &str, template: &str) -> Result<Option<CtGenTemplateMeta>> {
+        let editor = self.edit_profile(profile_name).await?;
+
+        let profile = editor.profile(profile_name)?;
+        let template_file = CtGen::get_filepath(&profile.templates_dir(), &format!("{}{}", template, FILE_EXT_HBS));
+
+        if !CtGen::file_exists(&template_file).await {
+            return Ok(None);
+        }
+
+        let content = tokio::fs::read_to_string(&template_file)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to read template file {}: {}", template_file, e)))?;
+
+        frontmatter::parse(&content).map_err(|e| CtGenError::ValidationError(format!("Template {}: {}", template, e)).into())
     }
 
     /// Create generation task
@@ -405,4 +1154,17 @@ impl CtGen {
 
         Err(CtGenError::RuntimeError("No current profile".to_string()).into())
     }
+
+    /// Render a template against a given context with the current profile helpers, scripts and partials
+    pub fn render(&self, template_content: &str, context: &Value) -> Result<String> {
+        if let Some(profile) = self.current_profile.as_ref() {
+            let handlebars = renderer::init_renderer(profile)?;
+
+            return handlebars
+                .render_template(template_content, context)
+                .map_err(renderer::render_error);
+        }
+
+        Err(CtGenError::RuntimeError("No current profile".to_string()).into())
+    }
 }
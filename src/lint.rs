@@ -0,0 +1,547 @@
+use crate::consts::{HELPERS_BUILTIN, HELPERS_BUNDLED, PROMPT_ID_LANGUAGE};
+use crate::profile::CtGenProfile;
+use crate::task::context::CtGenTaskContext;
+use crate::task::frontmatter;
+use crate::task::helpers::WasmPluginHelper;
+use crate::task::renderer;
+use crate::CtGen;
+use anyhow::Result;
+use handlebars::template::{DecoratorTemplate, HelperTemplate, Parameter, TemplateElement};
+use handlebars::{Path, PathSeg, Template};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CtGenLintSeverity {
+    /// The profile cannot run or produces broken output
+    Error,
+    /// The profile runs, but likely not as intended
+    Warning,
+}
+
+impl Display for CtGenLintSeverity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenLintSeverity::Error => write!(f, "error"),
+            CtGenLintSeverity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CtGenLintIssueKind {
+    MissingDirectory,
+    MissingPrompt,
+    MissingTarget,
+    MissingTemplate,
+    SkippedTarget,
+    InvalidConfig,
+    TemplateError,
+    ScriptError,
+    UnknownHelper,
+    MissingPartial,
+    MissingContextPath,
+}
+
+impl CtGenLintIssueKind {
+    /// Stable issue code
+    pub fn code(&self) -> &'static str {
+        match self {
+            CtGenLintIssueKind::MissingDirectory => "missing-directory",
+            CtGenLintIssueKind::MissingPrompt => "missing-prompt",
+            CtGenLintIssueKind::MissingTarget => "missing-target",
+            CtGenLintIssueKind::MissingTemplate => "missing-template",
+            CtGenLintIssueKind::SkippedTarget => "skipped-target",
+            CtGenLintIssueKind::InvalidConfig => "invalid-config",
+            CtGenLintIssueKind::TemplateError => "template-error",
+            CtGenLintIssueKind::ScriptError => "script-error",
+            CtGenLintIssueKind::UnknownHelper => "unknown-helper",
+            CtGenLintIssueKind::MissingPartial => "missing-partial",
+            CtGenLintIssueKind::MissingContextPath => "missing-context-path",
+        }
+    }
+
+    /// Default severity of the issue kind. Missing context paths render empty, so they don't break a run
+    pub fn severity(&self) -> CtGenLintSeverity {
+        match self {
+            CtGenLintIssueKind::MissingContextPath | CtGenLintIssueKind::SkippedTarget => CtGenLintSeverity::Warning,
+            _ => CtGenLintSeverity::Error,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Single problem found while validating or linting a profile
+pub struct CtGenLintIssue {
+    /// Issue severity
+    severity: CtGenLintSeverity,
+    #[serde(rename = "code")]
+    /// Issue category, serialized as its stable code
+    kind: CtGenLintIssueKind,
+    /// Template name, script name or profile field the issue was found in
+    path: String,
+    /// Human readable description
+    message: String,
+}
+
+impl CtGenLintIssue {
+    pub fn new(kind: CtGenLintIssueKind, path: &str, message: String) -> Self {
+        Self {
+            severity: kind.severity(),
+            kind,
+            path: path.to_string(),
+            message,
+        }
+    }
+
+    /// Issue severity
+    pub fn severity(&self) -> CtGenLintSeverity {
+        self.severity
+    }
+    /// Issue category
+    pub fn kind(&self) -> &CtGenLintIssueKind {
+        &self.kind
+    }
+    /// Stable issue code
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+    /// Template name, script name or profile field the issue was found in
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    /// Human readable description
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    /// Check if the issue is an error
+    pub fn is_error(&self) -> bool {
+        self.severity == CtGenLintSeverity::Error
+    }
+}
+
+impl Display for CtGenLintIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}] {}: {}", self.severity, self.code(), self.path, self.message)
+    }
+}
+
+/// Walks template syntax trees, collects issues and records what the templates reference
+pub(crate) struct CtGenLinter {
+    pub(crate) helpers: HashSet<String>,
+    pub(crate) partials: HashSet<String>,
+    inline_partials: HashSet<String>,
+    sample: Value,
+    pub(crate) issues: Vec<CtGenLintIssue>,
+    /// Known helpers used by the linted templates
+    pub(crate) used_helpers: BTreeSet<String>,
+    /// Partials used by the linted templates, inline partials excluded
+    pub(crate) used_partials: BTreeSet<String>,
+    /// Prompt ids referenced as `prompts/<id>` by the linted templates
+    pub(crate) used_prompts: BTreeSet<String>,
+}
+
+impl CtGenLinter {
+    /// Init linter with built-in and bundled helpers and a sample context of the profile
+    pub(crate) fn new(profile: &CtGenProfile) -> Result<Self> {
+        let mut sample = serde_json::to_value(CtGenTaskContext::sample(
+            profile.prompts(),
+            profile.connections().map(|(name, _)| name),
+        ))?;
+
+        // templates can be shared by targets, so params of every target are known
+        let mut params = Map::new();
+        for target in profile.targets().filter_map(|target_name| profile.target(target_name)) {
+            for (param_name, param_value) in target.params() {
+                params.insert(param_name.to_string(), serde_json::to_value(param_value)?);
+            }
+        }
+
+        if let Some(sample) = sample.as_object_mut() {
+            sample.insert("params".to_string(), Value::Object(params));
+            sample.insert("vars".to_string(), serde_json::to_value(profile.vars())?);
+            // features can be enabled at run time, so any feature flag is valid
+            sample.insert("features".to_string(), Value::Null);
+
+            // the chosen language and its type map, whose keys differ between languages
+            if !profile.configuration().languages().is_empty() {
+                sample.insert("language".to_string(), json!({"name": "", "types": Value::Null}));
+
+                if let Some(prompts) = sample.get_mut("prompts").and_then(Value::as_object_mut) {
+                    prompts.insert(PROMPT_ID_LANGUAGE.to_string(), Value::from(""));
+                }
+            }
+
+            // items of enumerating targets can be anything, so paths below `item` aren't checked
+            if profile
+                .targets()
+                .filter_map(|target_name| profile.target(target_name))
+                .any(|target| target.enumerate().is_some())
+            {
+                sample.insert("item".to_string(), Value::Null);
+            }
+        }
+
+        // scripts and templates of used packs are helpers and partials, unresolved packs are reported by the profile findings
+        let mut helpers = HELPERS_BUILTIN
+            .iter()
+            .chain(HELPERS_BUNDLED.iter())
+            .map(|s| s.to_string())
+            .collect::<HashSet<String>>();
+        let mut partials = HashSet::new();
+
+        for pack in profile.packs().unwrap_or_default() {
+            helpers.extend(renderer::script_helpers(&pack.scripts_dir()).into_iter().map(|(name, _)| name));
+            partials.extend(renderer::template_files(&pack.templates_dir()).into_iter().map(|(name, _)| name));
+        }
+
+        Ok(Self {
+            helpers,
+            partials,
+            inline_partials: HashSet::new(),
+            sample,
+            issues: Vec::new(),
+            used_helpers: BTreeSet::new(),
+            used_partials: BTreeSet::new(),
+            used_prompts: BTreeSet::new(),
+        })
+    }
+
+    /// Compile template source and lint it. Context paths are only checked when `check_paths` is set
+    pub(crate) fn lint_source(&mut self, source: &str, content: &str, check_paths: bool) {
+        match Template::compile(content) {
+            Ok(template) => self.lint_template(source, &template, check_paths),
+            Err(e) => self
+                .issues
+                .push(CtGenLintIssue::new(CtGenLintIssueKind::TemplateError, source, e.to_string())),
+        }
+    }
+
+    /// Lint a compiled template
+    fn lint_template(&mut self, source: &str, template: &Template, check_paths: bool) {
+        self.inline_partials.clear();
+
+        for element in template.elements.iter() {
+            if let TemplateElement::DecoratorBlock(decorator) = element {
+                if decorator.name.as_name() == Some("inline") {
+                    if let Some(Parameter::Literal(Value::String(name))) = decorator.params.first() {
+                        self.inline_partials.insert(name.to_string());
+                    }
+                }
+            }
+        }
+
+        self.lint_elements(source, template, check_paths);
+    }
+
+    fn lint_elements(&mut self, source: &str, template: &Template, root_scope: bool) {
+        for element in template.elements.iter() {
+            match element {
+                TemplateElement::Expression(helper) | TemplateElement::HtmlExpression(helper) | TemplateElement::HelperBlock(helper) => {
+                    self.lint_helper(source, helper, root_scope)
+                }
+                TemplateElement::PartialExpression(partial) | TemplateElement::PartialBlock(partial) => {
+                    self.lint_partial(source, partial, root_scope)
+                }
+                TemplateElement::DecoratorExpression(decorator) | TemplateElement::DecoratorBlock(decorator) => {
+                    if let Some(template) = decorator.template.as_ref() {
+                        self.lint_elements(source, template, root_scope);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn lint_helper(&mut self, source: &str, helper: &HelperTemplate, root_scope: bool) {
+        let name = helper.name.as_name().unwrap_or_default();
+        let is_helper = self.helpers.contains(name);
+
+        if is_helper {
+            self.used_helpers.insert(name.to_string());
+        } else {
+            if !helper.params.is_empty() || !helper.hash.is_empty() {
+                self.issues.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::UnknownHelper,
+                    source,
+                    format!("Unknown helper `{}`", name),
+                ));
+            } else {
+                // name-only expression, it's a context path
+                self.lint_param(source, &helper.name, root_scope);
+            }
+        }
+
+        for param in helper.params.iter().chain(helper.hash.values()) {
+            self.lint_param(source, param, root_scope);
+        }
+
+        // only conditionals keep the current context; everything else may change it
+        let inner_scope = root_scope && helper.block_param.is_none() && (name == "if" || name == "unless");
+
+        if let Some(template) = helper.template.as_ref() {
+            self.lint_elements(source, template, inner_scope);
+        }
+
+        if let Some(template) = helper.inverse.as_ref() {
+            self.lint_elements(source, template, inner_scope);
+        }
+    }
+
+    fn lint_partial(&mut self, source: &str, partial: &DecoratorTemplate, root_scope: bool) {
+        if let Some(name) = partial.name.as_name() {
+            if !name.starts_with('@') && !self.inline_partials.contains(name) {
+                self.used_partials.insert(name.to_string());
+            }
+
+            if !name.starts_with('@') && !self.partials.contains(name) && !self.inline_partials.contains(name) {
+                self.issues.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::MissingPartial,
+                    source,
+                    format!("Missing partial `{}`", name),
+                ));
+            }
+        }
+
+        for param in partial.params.iter().chain(partial.hash.values()) {
+            self.lint_param(source, param, root_scope);
+        }
+
+        if let Some(template) = partial.template.as_ref() {
+            self.lint_elements(source, template, root_scope);
+        }
+    }
+
+    fn lint_param(&mut self, source: &str, param: &Parameter, root_scope: bool) {
+        match param {
+            Parameter::Path(path) => {
+                if root_scope {
+                    self.lint_path(source, path);
+                }
+            }
+            Parameter::Subexpression(subexpression) => {
+                if let TemplateElement::Expression(helper) = subexpression.as_element() {
+                    self.lint_helper(source, helper, root_scope);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn lint_path(&mut self, source: &str, path: &Path) {
+        if let Path::Relative((segments, raw)) = path {
+            if let [PathSeg::Named(root), PathSeg::Named(prompt_id), ..] = segments.as_slice() {
+                if root == "prompts" {
+                    self.used_prompts.insert(prompt_id.to_string());
+                }
+            }
+
+            let mut names = Vec::new();
+
+            for segment in segments {
+                match segment {
+                    PathSeg::Named(name) => names.push(name.as_str()),
+                    _ => break, // @root, ../ and other special segments are skipped
+                }
+            }
+
+            if !self.context_path_exists(&names) {
+                self.issues.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::MissingContextPath,
+                    source,
+                    format!("Context path `{}` does not exist", raw),
+                ));
+            }
+        }
+    }
+
+    /// Lint a context path required by template front matter, written with `/` or `.` separators
+    pub(crate) fn lint_context_path(&mut self, source: &str, path: &str) {
+        let names = path.split(['/', '.']).filter(|name| !name.is_empty()).collect::<Vec<&str>>();
+
+        if !self.context_path_exists(&names) {
+            self.issues.push(CtGenLintIssue::new(
+                CtGenLintIssueKind::MissingContextPath,
+                source,
+                format!("Required context path `{}` does not exist", path),
+            ));
+        }
+    }
+
+    /// Check a context path against the sample context
+    fn context_path_exists(&self, names: &[&str]) -> bool {
+        let mut value = &self.sample;
+
+        for name in names {
+            match value {
+                Value::Object(map) => {
+                    if let Some(v) = map.get(*name) {
+                        value = v;
+                    } else {
+                        return false;
+                    }
+                }
+                _ => return true, // arrays and scalars can't be verified against the sample context
+            }
+        }
+
+        true
+    }
+}
+
+/// Load every template file of a templates dir as canonical template name and content, front matter included
+pub(crate) async fn load_templates(templates_dir: &str) -> Result<Vec<(String, String)>> {
+    let mut templates = Vec::new();
+
+    for (template_name, template_path) in renderer::template_files(templates_dir) {
+        let content = tokio::fs::read_to_string(&template_path).await?;
+
+        templates.push((template_name, content));
+    }
+
+    Ok(templates)
+}
+
+/// Lint profile templates, scripts, prompts and targets without connecting to a database
+pub async fn lint_profile(profile: &CtGenProfile) -> Result<Vec<CtGenLintIssue>> {
+    let mut linter = CtGenLinter::new(profile)?;
+
+    // directories, declarations and target templates existence
+    linter.issues.extend(profile.findings().await);
+
+    // scripts
+    let scripts_dir = profile.scripts_dir();
+
+    let engine = renderer::init_engine();
+    for (script_name, script_path) in renderer::script_helpers(&scripts_dir) {
+        if let Err(e) = engine.compile_file(script_path) {
+            linter
+                .issues
+                .push(CtGenLintIssue::new(CtGenLintIssueKind::ScriptError, &script_name, e.to_string()));
+        }
+
+        linter.helpers.insert(script_name);
+    }
+
+    // plugins
+    for (plugin_name, plugin_path) in profile.helpers().plugins() {
+        if let Err(e) = WasmPluginHelper::load(&CtGen::get_filepath(profile.context_dir(), plugin_path)) {
+            linter.issues.push(CtGenLintIssue::new(
+                CtGenLintIssueKind::ScriptError,
+                &format!("helpers.plugins.{}", plugin_name),
+                e.to_string(),
+            ));
+        }
+
+        linter.helpers.insert(plugin_name.to_string());
+    }
+
+    // templates
+    let templates = load_templates(&profile.templates_dir()).await?;
+
+    linter
+        .partials
+        .extend(templates.iter().map(|(template_name, _)| template_name.clone()));
+
+    let delimited_templates = profile
+        .targets()
+        .filter_map(|target_name| profile.target(target_name))
+        .filter(|target| target.delimiters().is_some())
+        .map(|target| target.template())
+        .collect::<HashSet<&str>>();
+
+    for (template_name, content) in templates.iter() {
+        match frontmatter::parse(content) {
+            Ok(Some(meta)) => {
+                for prompt_id in meta.prompts() {
+                    if profile.prompt(prompt_id).is_none() {
+                        linter.issues.push(CtGenLintIssue::new(
+                            CtGenLintIssueKind::MissingPrompt,
+                            template_name,
+                            format!("Template requires undeclared prompt `{}`", prompt_id),
+                        ));
+                    }
+                }
+
+                for context_path in meta.context() {
+                    linter.lint_context_path(template_name, context_path);
+                }
+
+                if let Some(target) = meta.target() {
+                    linter.lint_source(&format!("template.{}.target", template_name), target, true);
+                }
+
+                if let Some(condition) = meta.condition() {
+                    linter.lint_source(&format!("template.{}.condition", template_name), condition, true);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => linter
+                .issues
+                .push(CtGenLintIssue::new(CtGenLintIssueKind::TemplateError, template_name, e.to_string())),
+        }
+
+        if !delimited_templates.contains(template_name.as_str()) {
+            linter.lint_source(template_name, frontmatter::strip(content), true);
+        }
+    }
+
+    // templates used by targets with alternate delimiters are linted in converted form
+    for target in profile.targets().filter_map(|target_name| profile.target(target_name)) {
+        if let Some((open, close)) = target.delimiters() {
+            if let Some((template_name, content)) = templates.iter().find(|(name, _)| name == target.template()) {
+                linter.lint_source(
+                    &renderer::delimited_template_name(template_name, open, close),
+                    &renderer::convert_delimiters(frontmatter::strip(content), open, close),
+                    true,
+                );
+            }
+        }
+    }
+
+    // prompts
+    for prompt_name in profile.prompts() {
+        if let Some(prompt) = profile.prompt(prompt_name) {
+            if let Some(condition) = prompt.condition() {
+                linter.lint_source(&format!("prompt.{}.condition", prompt_name), condition, true);
+            }
+            if let Some(enumerate) = prompt.enumerate() {
+                linter.lint_source(&format!("prompt.{}.enumerate", prompt_name), enumerate, true);
+            }
+            linter.lint_source(&format!("prompt.{}.prompt", prompt_name), prompt.prompt(), true);
+            if let Some(default) = prompt.default() {
+                linter.lint_source(&format!("prompt.{}.default", prompt_name), default, true);
+            }
+            // the raw answer isn't part of the context, so paths aren't checked
+            if let Some(transform) = prompt.transform() {
+                linter.lint_source(&format!("prompt.{}.transform", prompt_name), transform, false);
+            }
+            if let Some(options) = prompt.options().as_str() {
+                linter.lint_source(&format!("prompt.{}.options", prompt_name), options, true);
+            }
+        }
+    }
+
+    // targets
+    for target_name in profile.targets() {
+        if let Some(target) = profile.target(target_name) {
+            if let Some(condition) = target.condition() {
+                linter.lint_source(&format!("target.{}.condition", target_name), condition, true);
+            }
+            if let Some(enumerate) = target.enumerate() {
+                linter.lint_source(&format!("target.{}.enumerate", target_name), enumerate, true);
+            }
+            linter.lint_source(&format!("target.{}.target", target_name), target.target(), true);
+            if let Some(formatter) = target.formatter() {
+                // formatters are rendered with their own context
+                linter.lint_source(&format!("target.{}.formatter", target_name), formatter, false);
+            }
+        }
+    }
+
+    Ok(linter.issues)
+}
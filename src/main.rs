@@ -1,25 +1,54 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use console::style;
-use ctgen::consts::CONFIG_NAME_DEFAULT;
+use ctgen::consts::{
+    CONFIG_NAME_DEFAULT, DEFAULTS_FILE_NAME, ENV_SERVER_TOKEN, FILE_EXT_HBS, FILE_EXT_PROFILE_ARCHIVE, FILE_EXT_RHAI, KIT_BLANK,
+    NOTIFY_AFTER_DEFAULT, PROMPT_ID_DATABASE, PROMPT_ID_LANGUAGE, PROMPT_PAGE_NEXT, PROMPT_PAGE_PREVIOUS, PROMPT_SELECT_ALL,
+    SERVER_ADDRESS_DEFAULT, SERVER_IDLE_TIMEOUT_DEFAULT, WATCH_DEBOUNCE_MS,
+};
+use ctgen::defaults::CtGenDefaults;
 use ctgen::error::CtGenError;
-use ctgen::profile::{CtGenProfile, CtGenProfileConfigOverrides};
-use ctgen::task::prompt::CtGenTaskPrompt;
+use ctgen::manifest::{CtGenFileStatus, CtGenManifest};
+use ctgen::pack::CtGenPack;
+use ctgen::profile::starter::STARTER_KITS;
+use ctgen::profile::{
+    CtGenConfirmStyle, CtGenProfile, CtGenProfileConfigOverrides, CtGenPrompt, CtGenPromptDefaults, CtGenPromptType, CtGenTarget,
+};
+use ctgen::server::CtGenServer;
+use ctgen::task::answers::CtGenAnswers;
+use ctgen::task::prompt::{CtGenRenderedPrompt, CtGenTaskPrompt};
+use ctgen::task::renderer;
+use ctgen::task::{
+    CtGenConflictPolicy, CtGenRunMode, CtGenTargetAction, CtGenTargetOutput, CtGenTargetPreview, CtGenTargetResult, CtGenTargetStatus,
+    CtGenTask,
+};
+use ctgen::theme::CtGenCliTheme;
+use ctgen::verify;
 use ctgen::CtGen;
 use database_reflection::adapter::reflection_adapter::ReflectionAdapter;
-use dialoguer::theme::ColorfulTheme;
-use dialoguer::{Confirm, Input, MultiSelect, Select, Sort};
+use dialoguer::{Completion, Confirm, Input, MultiSelect, Password, Select, Sort};
 #[allow(unused_imports)]
 use log::{debug, error, info, log_enabled, Level};
-use serde_json::Value;
+use notify::{RecursiveMode, Watcher};
+use notify_rust::Notification;
+use rand::distributions::{Alphanumeric, DistString};
+use serde_json::{Map, Value};
 use std::error::Error;
 use std::ffi::OsStr;
 use std::fmt::Display;
-use std::path::Path;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 #[derive(Parser, Debug)]
 #[command(author = "Cytec BG", version, about = "Code Template Generator", long_about = None)]
 pub struct Args {
+    #[arg(long, global = true)]
+    /// Config dir holding the profile registry, defaults, installed profiles and packs. Overrides CTGEN_CONFIG_DIR
+    pub config_dir: Option<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -31,6 +60,11 @@ pub enum Commands {
         #[command(subcommand)]
         op: CommandConfig,
     },
+    /// Manage packs of shared templates and rhai helpers that profiles use
+    Pack {
+        #[command(subcommand)]
+        op: CommandPack,
+    },
     /// Run code template generator
     Run {
         #[arg(long, default_value = "default")]
@@ -53,27 +87,397 @@ pub enum Commands {
         /// Override profile target-dir directive
         target_dir: Option<String>,
 
+        #[arg(long, number_of_values = 1)]
+        /// Override profile reflect-include directive, for example --reflect-include "user_*"
+        reflect_include: Option<Vec<String>>,
+
+        #[arg(long, number_of_values = 1)]
+        /// Override profile reflect-exclude directive, for example --reflect-exclude "log_*"
+        reflect_exclude: Option<Vec<String>>,
+
+        #[arg(long, short)]
+        /// Override profile parallelism directive. Max number of concurrent reflections, target renders and formatters
+        jobs: Option<usize>,
+
+        #[arg(long)]
+        /// Freeze the context timestamp, so identical inputs render byte-identical output
+        deterministic: bool,
+
+        #[arg(long)]
+        /// Fail on missing context variables instead of rendering empty strings
+        strict: bool,
+
+        #[arg(long)]
+        /// Keep files written before a failed write or formatter, instead of restoring their previous contents
+        no_rollback: bool,
+
+        #[arg(long)]
+        /// Allow the target dir to resolve outside of the working directory without asking
+        allow_external_target_dir: bool,
+
+        #[arg(long)]
+        /// Skip targets whose template file is missing instead of failing
+        skip_missing: bool,
+
+        #[arg(long)]
+        /// Render everything, but only print the planned output files instead of writing them and running formatters
+        dry_run: bool,
+
+        #[arg(long)]
+        /// Show a unified diff for every existing output file and ask whether to apply it
+        diff: bool,
+
+        #[arg(long, requires = "diff")]
+        /// Apply all diffs without asking
+        yes: bool,
+
+        #[arg(long, value_enum, default_value_t = ConflictPolicy::Ask)]
+        /// What to do with output files modified by hand since generation that the new render changes. Use a non-interactive policy in CI
+        on_conflict: ConflictPolicy,
+
+        #[arg(long, conflicts_with_all = ["dry_run", "diff", "check", "archive"])]
+        /// Print rendered targets to stdout instead of writing them, for shell pipelines and editors. Progress goes to stderr
+        stdout: bool,
+
+        #[arg(long)]
+        /// Ask prompts as numbered plain-text questions instead of interactive widgets, for screen readers and dumb terminals
+        simple_prompts: bool,
+
+        #[arg(long, conflicts_with = "simple_prompts")]
+        /// Read prompt answers line by line from stdin, in prompt order. Answers are given like --prompt values
+        stdin_answers: bool,
+
+        #[arg(long, conflicts_with_all = ["simple_prompts", "stdin_answers"])]
+        /// Never ask anything. Fail with the list of prompts left unanswered by --prompt and --answers instead, for CI
+        non_interactive: bool,
+
+        #[arg(long = "answers", value_name = "FILE")]
+        /// Pre-fill prompt answers from a .json or .toml file, like one written by --save-answers. --prompt values win
+        answers_file: Option<String>,
+
+        #[arg(long, value_name = "FILE")]
+        /// Save the prompt answers of this run to a .json or .toml file, to repeat it with --answers
+        save_answers: Option<String>,
+
+        #[arg(long, conflicts_with_all = ["dry_run", "diff", "archive"])]
+        /// Fail if any output file is missing or differs from its rendered and formatted output, without writing anything. For CI
+        check: bool,
+
+        #[arg(long, conflicts_with_all = ["dry_run", "diff"])]
+        /// Collect rendered targets into a .zip, .tar or .tar.gz archive instead of writing them. Formatters and hooks don't run
+        archive: Option<String>,
+
+        #[arg(long)]
+        /// Show a desktop notification when the run finishes or fails, if it took longer than --notify-after
+        notify: bool,
+
+        #[arg(long, default_value_t = NOTIFY_AFTER_DEFAULT, requires = "notify")]
+        /// Minimum run duration in seconds for --notify
+        notify_after: u64,
+
+        #[arg(long)]
+        /// Output language to generate, one of the profile languages. Same as --prompt "language=..."
+        language: Option<String>,
+
+        #[arg(long, number_of_values = 1)]
+        /// Enable a feature flag for templates, for example --feature experimental_api. Prefix with `!` to disable a profile feature
+        feature: Option<Vec<String>>,
+
+        #[arg(long, value_parser = parse_prompt_key_val::<String, String>, number_of_values = 1)]
+        /// Prompt answer override, for example --prompt "dummy=1". Use `database` and `table` to preseed built-in prompts
+        prompt: Option<Vec<(String, String)>>,
+
+        #[arg(long, conflicts_with_all = ["table", "save_answers", "archive"])]
+        /// Generate code for every table allowed by reflect-include and reflect-exclude, one table at a time
+        all_tables: bool,
+
+        /// Database table name to generate code templates for
+        table: Option<String>,
+    },
+    /// Run a task, then keep it alive and render targets again whenever profile templates or scripts change
+    Watch {
+        #[arg(long, default_value = "default")]
+        /// Config profile to use
+        profile: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = ConflictPolicy::Ask)]
+        /// What to do with output files modified by hand since generation that the new render changes
+        on_conflict: ConflictPolicy,
+
+        #[arg(long, value_name = "SECONDS")]
+        /// Also reflect the table again every few seconds, and render all targets again when its schema changed
+        poll_schema: Option<u64>,
+
+        #[arg(long = "answers", value_name = "FILE")]
+        /// Pre-fill prompt answers from a .json or .toml file, like one written by run --save-answers. --prompt values win
+        answers_file: Option<String>,
+
         #[arg(long, value_parser = parse_prompt_key_val::<String, String>, number_of_values = 1)]
-        /// Prompt answer override, for example --prompt "dummy=1"
+        /// Prompt answer override, for example --prompt "dummy=1". Use `database` and `table` to preseed built-in prompts
         prompt: Option<Vec<(String, String)>>,
 
         /// Database table name to generate code templates for
         table: Option<String>,
     },
+    /// Render a template expression or file and print the result, without rendering any targets
+    Render {
+        #[arg(long, default_value = "default")]
+        /// Config profile to use for rendering
+        profile: Option<String>,
+
+        #[arg(long, conflicts_with = "file", required_unless_present = "file")]
+        /// Template expression to render, for example --expr "{{table/name}}"
+        expr: Option<String>,
+
+        #[arg(long)]
+        /// Template file to render
+        file: Option<String>,
+
+        #[arg(long, conflicts_with_all = ["prompt", "table"])]
+        /// Saved context JSON file to render with, instead of reflecting a live database
+        context: Option<String>,
+
+        #[arg(long, value_parser = parse_prompt_key_val::<String, String>, number_of_values = 1)]
+        /// Prompt answer override, for example --prompt "dummy=1". Use `database` and `table` to preseed built-in prompts
+        prompt: Option<Vec<(String, String)>>,
+
+        /// Database table name to build the live context for
+        table: Option<String>,
+    },
+    /// Print the live task context as JSON, or save it as a fixture for `render --context`, `coverage` and `test`
+    Context {
+        #[arg(long, default_value = "default")]
+        /// Config profile to build the context with
+        profile: Option<String>,
+
+        #[arg(long)]
+        /// Save the context to a JSON file instead of printing it, for example --save fixtures/users.json
+        save: Option<String>,
+
+        #[arg(long, value_parser = parse_prompt_key_val::<String, String>, number_of_values = 1)]
+        /// Prompt answer override, for example --prompt "dummy=1". Use `database` and `table` to preseed built-in prompts
+        prompt: Option<Vec<(String, String)>>,
+
+        /// Database table name to build the live context for
+        table: Option<String>,
+    },
+    /// Lint profile templates and scripts without connecting to a database
+    Lint {
+        #[arg(long, default_value = "default")]
+        /// Config profile to lint
+        profile: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        /// Output format. `json` prints a list of findings with severity, code, path and message
+        format: OutputFormat,
+    },
+    /// Report context fields read by profile templates versus available, and conditional branches never taken
+    Coverage {
+        #[arg(long, default_value = "default")]
+        /// Config profile to report on
+        profile: Option<String>,
+
+        #[arg(long, number_of_values = 1, conflicts_with_all = ["prompt", "table"])]
+        /// Saved context JSON fixture, instead of reflecting a live database. Can be repeated
+        context: Option<Vec<String>>,
+
+        #[arg(long, value_parser = parse_prompt_key_val::<String, String>, number_of_values = 1)]
+        /// Prompt answer override, for example --prompt "dummy=1". Use `database` and `table` to preseed built-in prompts
+        prompt: Option<Vec<(String, String)>>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        /// Output format. `json` prints every field and condition with its counts
+        format: OutputFormat,
+
+        /// Database table name to build the live context for
+        table: Option<String>,
+    },
+    /// Render profile targets against saved test case contexts and compare them with the expected outputs
+    Test {
+        #[arg(long, default_value = "default")]
+        /// Config profile to test
+        profile: Option<String>,
+
+        #[arg(long, default_value_t = false)]
+        /// Rewrite expected outputs to match the rendered outputs, after intentional template changes
+        update: bool,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        /// Output format. `json` prints every output file with its status and diff
+        format: OutputFormat,
+    },
+    /// Run the rhai helper test scripts of a profile, `*_test.rhai` files in its scripts dir
+    TestHelpers {
+        #[arg(long, default_value = "default")]
+        /// Config profile to test helpers of
+        profile: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        /// Output format. `json` prints every test case with its error
+        format: OutputFormat,
+    },
+    /// Show files generated into the target dir and whether they were modified by hand since
+    Status {
+        #[arg(long, default_value = "default")]
+        /// Config profile whose target dir to inspect
+        profile: Option<String>,
+
+        #[arg(long)]
+        /// Target dir to inspect, instead of the profile's. Relative to the working directory
+        target_dir: Option<String>,
+    },
+    /// Remove files previously generated into the target dir
+    Clean {
+        #[arg(long, default_value = "default")]
+        /// Config profile whose target dir to clean
+        profile: Option<String>,
+
+        #[arg(long)]
+        /// Target dir to clean, instead of the profile's. Relative to the working directory
+        target_dir: Option<String>,
+
+        #[arg(long, default_value_t = false)]
+        /// Also remove files modified by hand since generation
+        force: bool,
+
+        #[arg(long, default_value_t = false)]
+        /// Remove without asking for confirmation
+        yes: bool,
+    },
+    /// Remove generated files the profile no longer produces, after targets were removed or stopped rendering them
+    Prune {
+        #[arg(long, default_value = "default")]
+        /// Config profile to prune generated files of
+        profile: Option<String>,
+
+        #[arg(long, default_value_t = false)]
+        /// Also remove files modified by hand since generation
+        force: bool,
+
+        #[arg(long, default_value_t = false)]
+        /// Remove without asking for confirmation
+        yes: bool,
+
+        #[arg(long, value_parser = parse_prompt_key_val::<String, String>, number_of_values = 1, requires = "table")]
+        /// Prompt answer override, for example --prompt "dummy=1". All prompts must be answered to plan the table
+        prompt: Option<Vec<(String, String)>>,
+
+        /// Database table to compare the render plan for. Without it, only files of removed targets are pruned
+        table: Option<String>,
+    },
+    /// Verify generated files against their generation records and the current render plan. Fails unless all are up to date
+    Verify {
+        #[arg(long, default_value = "default")]
+        /// Config profile to verify generated files of
+        profile: Option<String>,
+
+        #[arg(long, value_parser = parse_prompt_key_val::<String, String>, number_of_values = 1, requires = "table")]
+        /// Prompt answer override, for example --prompt "dummy=1". All prompts must be answered to plan the table
+        prompt: Option<Vec<(String, String)>>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        /// Output format
+        format: OutputFormat,
+
+        /// Database table to compare the render plan for. Without it, files are only compared with their records
+        table: Option<String>,
+    },
+    /// Explain a profile target: template, output path, dependencies and referenced prompts
+    Explain {
+        /// Config profile name
+        profile: String,
+
+        /// Target name
+        target: String,
+    },
+    /// Inspect helpers available to profile templates
+    Helpers {
+        #[command(subcommand)]
+        op: CommandHelpers,
+    },
+    /// Add a new prompt or target to a profile
+    New {
+        #[command(subcommand)]
+        op: CommandNew,
+    },
+    /// Serve a local JSON-RPC API for IDE plugins and other tools to drive ctgen tasks
+    Serve {
+        #[arg(long, default_value = SERVER_ADDRESS_DEFAULT)]
+        /// Address to listen on. Keep it on a loopback address
+        address: String,
+
+        #[arg(long)]
+        /// Token clients must send as `Authorization: Bearer <token>`. Taken from CTGEN_SERVER_TOKEN, or generated and printed if omitted
+        token: Option<String>,
+
+        #[arg(long)]
+        /// Allow clients to pass a `dsn` to connect to, instead of only the profile database
+        allow_dsn: bool,
+
+        #[arg(long, value_name = "SECONDS", default_value_t = SERVER_IDLE_TIMEOUT_DEFAULT)]
+        /// Close tasks without calls for this long
+        idle_timeout: u64,
+    },
     /// Init a new profile
     Init {
         #[arg(long)]
         /// Add config profile with specific name
         name: Option<String>,
 
+        #[arg(long)]
+        /// Bundled starter kit: blank, rust-sqlx, php-laravel or ts-prisma. Asked for when omitted
+        kit: Option<String>,
+
+        #[arg(long, requires = "dsn")]
+        /// Type the starter kit templates after the columns of a table
+        from_table: Option<String>,
+
+        #[arg(long, requires = "from_table")]
+        /// DSN of the database to reflect the table from. Not stored in the profile
+        dsn: Option<String>,
+
         #[arg(default_value = ".")]
         path: String,
     },
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+/// How output files modified by hand since generation are treated when the new render changes them
+pub enum ConflictPolicy {
+    /// Ask for every file
+    Ask,
+    /// Keep the modified file
+    Keep,
+    /// Overwrite it with the generated output
+    Overwrite,
+    /// Write the generated output with conflict markers around the changed lines
+    Markers,
+    /// Fail the run before anything is written
+    Fail,
+}
+
+impl ConflictPolicy {
+    /// Resolution applied to every conflicting file, unless asked for each
+    fn resolution(self) -> Option<CtGenConflictPolicy> {
+        match self {
+            ConflictPolicy::Ask => None,
+            ConflictPolicy::Keep => Some(CtGenConflictPolicy::Keep),
+            ConflictPolicy::Overwrite => Some(CtGenConflictPolicy::Overwrite),
+            ConflictPolicy::Markers => Some(CtGenConflictPolicy::Markers),
+            ConflictPolicy::Fail => Some(CtGenConflictPolicy::Fail),
+        }
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum CommandConfig {
-    /// Add a config profile. If no name is given, template name from toml file will be used
+    /// Add a config profile, or install one from a .ctgen archive. If no name is given, template name from toml file will be used
     Add {
         #[arg(long, conflicts_with = "name")]
         /// Add config as default
@@ -83,12 +487,60 @@ pub enum CommandConfig {
         name: Option<String>,
 
         #[arg(default_value = ".")]
-        /// Path to Ctgen.toml file
+        /// Path to Ctgen.toml file, or to a .ctgen profile archive
         path: String,
     },
-    /// List all saved config profiles
+    /// Bundle a config profile with its templates, scripts and asset files into a single .ctgen archive
+    Export {
+        #[arg(short, long)]
+        /// Archive file to write. Defaults to <name>.ctgen in the working directory
+        output: Option<String>,
+
+        /// Config profile name to export
+        name: String,
+    },
+    /// List all saved config profiles, flagging missing, duplicate and changed config files
     #[command(alias = "ls")]
-    List,
+    List {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        /// Output format. `json` prints every profile with its registration metadata and issues
+        format: OutputFormat,
+    },
+    /// Show a config profile with its prompts, targets, resolved directories and validation status
+    Show {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        /// Output format. `json` prints the full profile config, prompts, targets and validation findings
+        format: OutputFormat,
+
+        #[arg(long, conflicts_with = "format")]
+        /// Print JSON. Same as --format json
+        json: bool,
+
+        /// Config profile name to show
+        name: String,
+    },
+    /// Rename a config profile, keeping its place in the list
+    #[command(alias = "mv")]
+    Rename {
+        /// Config profile name to rename
+        name: String,
+
+        /// New config profile name
+        new_name: String,
+    },
+    /// Copy a config profile with its templates, scripts and asset files into a new directory and add the copy
+    #[command(alias = "cp")]
+    Copy {
+        #[arg(long)]
+        /// Name of the copy. Defaults to the new directory name
+        name: Option<String>,
+
+        /// Config profile name to copy
+        profile: String,
+
+        /// Directory to copy the profile into
+        path: String,
+    },
     /// Remove a config profile
     Rm {
         /// Config profile name to remove
@@ -96,6 +548,103 @@ pub enum CommandConfig {
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum CommandPack {
+    /// Install a pack into the config dir, replacing an installed copy of the same version
+    Add {
+        #[arg(default_value = ".")]
+        /// Path to the pack dir, or to its ctgen-pack.toml file
+        path: String,
+    },
+    /// List installed packs
+    #[command(alias = "ls")]
+    List,
+    /// Remove installed packs
+    Rm {
+        /// Pack reference, like `acme-helpers` for every version or `acme-helpers@1` for matching versions
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CommandHelpers {
+    /// List built-in, bundled, rhai script and plugin helpers of a profile
+    #[command(alias = "ls")]
+    List {
+        #[arg(long, default_value = "default")]
+        /// Config profile to list helpers for
+        profile: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CommandNew {
+    /// Add a new target, or replace existing. A dummy template file is created if missing
+    Target {
+        #[arg(long, default_value = "default")]
+        /// Config profile to add the target to
+        profile: Option<String>,
+
+        #[arg(long)]
+        /// Template name. Defaults to the target name
+        template: Option<String>,
+
+        #[arg(long, conflicts_with = "template")]
+        /// Copy an asset file, relative to the profile directory, instead of rendering a template
+        copy: Option<String>,
+
+        #[arg(long)]
+        /// Condition template. Must render to "1" for the target to render
+        condition: Option<String>,
+
+        #[arg(long)]
+        /// Formatter command template
+        formatter: Option<String>,
+
+        /// Target name
+        name: String,
+
+        /// Output file path template, relative to the target dir. Defaults to the `target` declared in the template front matter
+        target: Option<String>,
+    },
+    /// Add a new prompt, or replace existing
+    Prompt {
+        #[arg(long, default_value = "default")]
+        /// Config profile to add the prompt to
+        profile: Option<String>,
+
+        #[arg(long)]
+        /// Condition template. Must render to "1" for the prompt to be asked
+        condition: Option<String>,
+
+        #[arg(long)]
+        /// Options template that renders a comma-separated list
+        options: Option<String>,
+
+        #[arg(long)]
+        /// Default answer template, used when the prompt is skipped by its condition or answered empty
+        default: Option<String>,
+
+        #[arg(long)]
+        /// Allow multiple answers
+        multiple: bool,
+
+        #[arg(long)]
+        /// Disallow empty answers
+        required: bool,
+
+        #[arg(long)]
+        /// Mask the input and keep the answer out of context dumps and saved answer files
+        secret: bool,
+
+        /// Prompt name
+        name: String,
+
+        /// Prompt text template
+        prompt: String,
+    },
+}
+
 pub fn parse_prompt_key_val<T, U>(s: &str) -> Result<(T, U), Box<dyn Error + Send + Sync + 'static>>
 where
     T: std::str::FromStr,
@@ -115,8 +664,16 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
 
+    if let Some(config_dir) = args.config_dir.as_deref() {
+        CtGen::set_config_dir(config_dir)?;
+    }
+
     let mut ctgen = CtGen::new().await?;
 
+    CLI_THEME.get_or_init(|| CtGenCliTheme::new(ctgen.get_defaults()));
+    EDITOR.get_or_init(|| ctgen.get_defaults().editor());
+    SIMPLE_PROMPTS.store(ctgen.get_defaults().simple_prompts(), Ordering::Relaxed);
+
     match args.command {
         Commands::Config { op } => match op {
             CommandConfig::Add { default, name, path } => {
@@ -128,14 +685,75 @@ async fn main() -> Result<()> {
                     ""
                 };
 
-                let profile = ctgen.add_profile(profile_name, &path).await?;
+                let profile = if path.ends_with(FILE_EXT_PROFILE_ARCHIVE) {
+                    ctgen.import_profile(profile_name, &path).await?
+                } else {
+                    ctgen.add_profile(profile_name, &path).await?
+                };
 
                 print_info(format!("Added profile {}", style(profile.name()).cyan()));
 
                 Ok(())
             }
-            CommandConfig::List => {
-                list_profiles(&ctgen).await;
+            CommandConfig::Export { output, name } => {
+                let archive_file = output.unwrap_or_else(|| format!("{}{}", name, FILE_EXT_PROFILE_ARCHIVE));
+
+                let count = ctgen.export_profile(&name, &archive_file).await?;
+
+                print_info(format!(
+                    "Exported profile {} with {} files to {}",
+                    style(&name).cyan(),
+                    count,
+                    style(&archive_file).cyan()
+                ));
+
+                Ok(())
+            }
+            CommandConfig::List { format } => {
+                if format == OutputFormat::Json {
+                    println!("{}", serde_json::to_string_pretty(&ctgen.check_profiles().await)?);
+                } else {
+                    list_profiles(&ctgen).await;
+                }
+
+                Ok(())
+            }
+            CommandConfig::Show { format, json, name } => {
+                let inspection = ctgen.inspect_profile(&name).await?;
+
+                if json || format == OutputFormat::Json {
+                    println!("{}", serde_json::to_string_pretty(&inspection)?);
+                } else {
+                    println!("{}", inspection);
+                }
+
+                Ok(())
+            }
+            CommandConfig::Rename { name, new_name } => {
+                ctgen.rename_profile(&name, &new_name).await?;
+
+                print_info(format!("Renamed profile {} to {}", style(name).cyan(), style(new_name).cyan()));
+
+                Ok(())
+            }
+            CommandConfig::Copy { name, profile, path } => {
+                let name = if let Some(name) = name {
+                    name
+                } else {
+                    Path::new(&path)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .ok_or_else(|| CtGenError::ValidationError(format!("Failed to derive a profile name from {}. Pass --name", path)))?
+                };
+
+                let copy = ctgen.copy_profile(&profile, &path, &name).await?;
+
+                print_info(format!(
+                    "Copied profile {} to {} as {}",
+                    style(&profile).cyan(),
+                    style(copy.context_dir()).underlined(),
+                    style(&name).cyan()
+                ));
 
                 Ok(())
             }
@@ -147,209 +765,2123 @@ async fn main() -> Result<()> {
                 Ok(())
             }
         },
-        Commands::Run {
-            profile,
-            env_file,
-            env_var,
-            dsn,
+        Commands::Pack { op } => match op {
+            CommandPack::Add { path } => {
+                let pack = ctgen.add_pack(&path).await?;
+
+                print_info(format!("Added pack {}", style(&pack).cyan()));
+
+                Ok(())
+            }
+            CommandPack::List => {
+                let (packs, warnings) = ctgen.list_packs()?;
+
+                for warning in warnings {
+                    print_fail(warning);
+                }
+
+                if packs.is_empty() {
+                    print_fail("No packs found.");
+                } else {
+                    print_info("Installed packs:");
+
+                    for pack in packs {
+                        println!(
+                            "{}\t{}\t{}",
+                            style(&pack).cyan(),
+                            pack.description().unwrap_or_default(),
+                            style(pack.dir()).underlined()
+                        );
+                    }
+                }
+
+                Ok(())
+            }
+            CommandPack::Rm { name } => {
+                for pack in ctgen.remove_pack(&name).await? {
+                    print_info(format!("Removed pack {}", style(&pack).cyan()));
+                }
+
+                Ok(())
+            }
+        },
+        Commands::Run {
+            profile,
+            env_file,
+            env_var,
+            dsn,
             target_dir,
+            reflect_include,
+            reflect_exclude,
+            jobs,
+            deterministic,
+            strict,
+            no_rollback,
+            allow_external_target_dir,
+            skip_missing,
+            dry_run,
+            diff,
+            yes,
+            on_conflict,
+            stdout,
+            simple_prompts,
+            stdin_answers,
+            non_interactive,
+            answers_file,
+            save_answers,
+            check,
+            archive,
+            notify,
+            notify_after,
+            language,
+            feature,
+            prompt,
+            all_tables,
+            table,
+        } => {
+            let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+            // fall back to user defaults for flags not given
+            let jobs = jobs.or(ctgen.get_defaults().jobs());
+            let deterministic = deterministic || ctgen.get_defaults().deterministic();
+            let skip_missing = skip_missing || ctgen.get_defaults().skip_missing();
+
+            if simple_prompts {
+                SIMPLE_PROMPTS.store(true, Ordering::Relaxed);
+            }
+
+            if non_interactive && diff && !dry_run && !yes {
+                return Err(CtGenError::ValidationError(
+                    "--diff asks before applying changes. Pass --yes or --dry-run with --non-interactive".to_string(),
+                )
+                .into());
+            }
+
+            // rendered output printed to stdout must not mix with progress output, so decide before printing any
+            if stdout || (!dry_run && !check && archive.is_none() && has_stdout_targets(&ctgen, profile_name).await) {
+                STDOUT_MODE.store(true, Ordering::Relaxed);
+            }
+
+            print_info(format!("Loading profile {}", style(profile_name).cyan()));
+
+            print_deprecations(&ctgen, profile_name);
+
+            if skip_missing {
+                for skipped in ctgen.set_current_profile_skip_missing(profile_name).await? {
+                    print_fail(skipped);
+                }
+            } else {
+                ctgen.set_current_profile(profile_name).await?;
+            }
+
+            let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
+
+            // confirm writing outside of the project, unless the profile allows it
+            let mut allow_external_target_dir = allow_external_target_dir;
+
+            if let Some(profile) = ctgen.get_current_profile() {
+                let canonical_target_dir =
+                    CtGenTask::resolve_target_dir(&context_dir, target_dir.as_deref().unwrap_or(profile.configuration().target_dir()));
+
+                if !allow_external_target_dir
+                    && !stdout
+                    && !stdin_answers
+                    && !non_interactive
+                    && !profile.configuration().allow_external_target_dir()
+                    && !CtGen::is_path_within(&context_dir, &canonical_target_dir)
+                {
+                    allow_external_target_dir = ask_confirm(&format!(
+                        "Target dir {} is outside of the working directory. Write there anyway?",
+                        CtGen::normalize_path(&canonical_target_dir).display()
+                    ))?;
+                }
+            }
+
+            let mut profile_overrides: Option<CtGenProfileConfigOverrides> = None;
+
+            if env_file.is_some()
+                || env_var.is_some()
+                || dsn.is_some()
+                || target_dir.is_some()
+                || reflect_include.is_some()
+                || reflect_exclude.is_some()
+                || jobs.is_some()
+                || deterministic
+                || strict
+                || no_rollback
+                || allow_external_target_dir
+                || feature.is_some()
+            {
+                print_info("Overriding profile parameters");
+                profile_overrides = Some(CtGenProfileConfigOverrides::new(
+                    env_file,
+                    env_var,
+                    dsn,
+                    target_dir,
+                    reflect_include,
+                    reflect_exclude,
+                    jobs,
+                    deterministic,
+                    strict,
+                    no_rollback,
+                    allow_external_target_dir,
+                    feature.unwrap_or_default(),
+                ));
+            }
+
+            print_info("Creating ctgen task");
+
+            let mut task = ctgen.create_task(&context_dir, table.as_deref(), profile_overrides.clone()).await?;
+
+            // the language flag is a shorthand for the built-in language prompt answer
+            let prompt = if let Some(language) = language {
+                let mut prompts = prompt.unwrap_or_default();
+                prompts.push((PROMPT_ID_LANGUAGE.to_string(), language));
+
+                Some(prompts)
+            } else {
+                prompt
+            };
+
+            // set pre-defined prompt answers, --prompt values win over the answers file
+            let mut answers = CtGenAnswers::default();
+
+            if let Some(answers_file) = answers_file {
+                print_info(format!("Loading prompt answers from {}", style(&answers_file).cyan()));
+                answers = CtGenAnswers::load(&answers_file).await?;
+            }
+
+            if prompt.is_some() {
+                print_info("Overriding prompt responses");
+            }
+
+            add_prompt_overrides(&task, &mut answers, &prompt.unwrap_or_default());
+
+            // answers read from stdin leave no way to ask about conflicts
+            let on_conflict = if (stdin_answers || non_interactive) && on_conflict == ConflictPolicy::Ask {
+                ConflictPolicy::Fail
+            } else {
+                on_conflict
+            };
+
+            let options = RunOptions {
+                stdout,
+                check,
+                archive,
+                dry_run,
+                diff,
+                yes,
+                on_conflict,
+            };
+
+            // every table gets a task of its own, so a single table reflection is held at a time
+            if all_tables {
+                let tables = list_all_tables(&mut task, &mut answers, stdin_answers || non_interactive).await?;
+                drop(task);
+
+                let mut elapsed = Duration::ZERO;
+                let mut result = Ok(());
+
+                for table in tables {
+                    print_info(format!("Generating table {}", style(&table).cyan()));
+
+                    let mut task = ctgen.create_task(&context_dir, Some(&table), profile_overrides.clone()).await?;
+
+                    if !answers.is_empty() {
+                        task.set_answers(&answers).await?;
+                    }
+
+                    answer_prompts(&mut task, stdin_answers, non_interactive).await?;
+
+                    let started = Instant::now();
+                    result = run_task(&task, options.clone()).await;
+                    elapsed += started.elapsed();
+
+                    if result.is_err() {
+                        break;
+                    }
+                }
+
+                if notify && elapsed >= Duration::from_secs(notify_after) {
+                    notify_run_finished(&result, elapsed);
+                }
+
+                return result;
+            }
+
+            if !answers.is_empty() {
+                task.set_answers(&answers).await?;
+            }
+
+            // ask prompts to prepare context
+            answer_prompts(&mut task, stdin_answers, non_interactive).await?;
+
+            if let Some(save_answers) = save_answers {
+                task.answers().save(&save_answers).await?;
+
+                print_info(format!("Saved prompt answers to {}", style(&save_answers).cyan()));
+            }
+
+            //println!("{}", serde_json::to_string(&task.context())?);
+
+            // long runs can notify on completion, timed from here so waiting for prompt answers doesn't count
+            let started = Instant::now();
+
+            let result = run_task(&task, options).await;
+
+            if notify && started.elapsed() >= Duration::from_secs(notify_after) {
+                notify_run_finished(&result, started.elapsed());
+            }
+
+            result
+        }
+        Commands::Watch {
+            profile,
+            on_conflict,
+            poll_schema,
+            answers_file,
+            prompt,
+            table,
+        } => {
+            let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+            // rendered output printed to stdout must not mix with progress output, so decide before printing any
+            if has_stdout_targets(&ctgen, profile_name).await {
+                STDOUT_MODE.store(true, Ordering::Relaxed);
+            }
+
+            print_info(format!("Loading profile {}", style(profile_name).cyan()));
+
+            print_deprecations(&ctgen, profile_name);
+            let profile = ctgen.set_current_profile(profile_name).await?;
+
+            // templates and scripts of the packs the profile uses render too, and the same dir may hold templates and
+            // scripts
+            let packs = profile.packs()?;
+            let templates_dirs = packs
+                .iter()
+                .map(CtGenPack::templates_dir)
+                .chain(std::iter::once(profile.templates_dir()))
+                .collect::<Vec<String>>();
+            let mut watched_dirs = templates_dirs.clone();
+
+            for scripts_dir in packs
+                .iter()
+                .map(CtGenPack::scripts_dir)
+                .chain(std::iter::once(profile.scripts_dir()))
+            {
+                if !watched_dirs.contains(&scripts_dir) {
+                    watched_dirs.push(scripts_dir);
+                }
+            }
+
+            let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
+
+            print_info("Creating ctgen task");
+
+            let mut task = ctgen.create_task(&context_dir, table.as_deref(), None).await?;
+
+            let mut answers = CtGenAnswers::default();
+
+            if let Some(answers_file) = answers_file {
+                print_info(format!("Loading prompt answers from {}", style(&answers_file).cyan()));
+                answers = CtGenAnswers::load(&answers_file).await?;
+            }
+
+            add_prompt_overrides(&task, &mut answers, &prompt.unwrap_or_default());
+
+            if !answers.is_empty() {
+                task.set_answers(&answers).await?;
+            }
+
+            answer_prompts(&mut task, false, false).await?;
+
+            print_info("Running ctgen task");
+            watch_render(&task, None, on_conflict).await;
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<PathBuf>();
+
+            let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    if event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove() {
+                        // only templates and scripts, not output files written into a watched dir or editor swap files
+                        for path in event.paths.into_iter().filter(|path| {
+                            let path = path.to_string_lossy();
+
+                            path.ends_with(FILE_EXT_HBS) || path.ends_with(FILE_EXT_RHAI)
+                        }) {
+                            let _ = sender.send(path);
+                        }
+                    }
+                }
+            })
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to start file watcher: {}", e)))?;
+
+            // watched paths are reported canonical, so changed templates are matched against the canonical templates dirs
+            let mut canonical_templates_dirs = Vec::new();
+
+            for (index, watched_dir) in watched_dirs.iter().enumerate() {
+                let Ok(watched_dir) = std::fs::canonicalize(watched_dir) else {
+                    continue;
+                };
+
+                watcher
+                    .watch(&watched_dir, RecursiveMode::Recursive)
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to watch {}: {}", watched_dir.display(), e)))?;
+
+                print_info(format!("Watching {}", style(watched_dir.display()).cyan()));
+
+                // watched dirs start with the templates dirs
+                if index < templates_dirs.len() {
+                    canonical_templates_dirs.push(watched_dir.to_string_lossy().to_string());
+                }
+            }
+
+            let mut schema_poll = poll_schema.map(|seconds| tokio::time::interval(Duration::from_secs(seconds.max(1))));
+
+            // the first tick of an interval completes right away
+            if let Some(schema_poll) = schema_poll.as_mut() {
+                schema_poll.tick().await;
+            }
+
+            print_info("Waiting for changes, press Ctrl-C to stop");
+
+            // one Ctrl-C listener for the whole loop, so a Ctrl-C pressed while rendering isn't lost
+            let ctrl_c = tokio::signal::ctrl_c();
+            tokio::pin!(ctrl_c);
+
+            loop {
+                tokio::select! {
+                    Some(path) = receiver.recv() => {
+                        // editors save files in several steps, so changes are collected for a moment
+                        let mut paths = vec![path];
+
+                        tokio::time::sleep(Duration::from_millis(WATCH_DEBOUNCE_MS)).await;
+
+                        while let Ok(path) = receiver.try_recv() {
+                            if !paths.contains(&path) {
+                                paths.push(path);
+                            }
+                        }
+
+                        for path in paths.iter() {
+                            print_info(format!("Changed {}", style(path.display()).cyan()));
+                        }
+
+                        if let Err(e) = task.reload_templates() {
+                            print_fail(e);
+                            continue;
+                        }
+
+                        // scripts and removed templates can change any target
+                        let template_files = canonical_templates_dirs
+                            .iter()
+                            .flat_map(|templates_dir| renderer::template_files(templates_dir))
+                            .collect::<Vec<(String, PathBuf)>>();
+                        let templates = paths
+                            .iter()
+                            .map(|path| {
+                                template_files
+                                    .iter()
+                                    .find(|(_, template_path)| template_path == path)
+                                    .map(|(template_name, _)| template_name.to_string())
+                            })
+                            .collect::<Option<Vec<String>>>();
+
+                        watch_render(&task, templates.as_deref(), on_conflict).await;
+                    }
+                    _ = async {
+                        match schema_poll.as_mut() {
+                            Some(schema_poll) => {
+                                schema_poll.tick().await;
+                            }
+                            None => std::future::pending::<()>().await,
+                        }
+                    } => {
+                        match task.reflect_again().await {
+                            Ok(true) => {
+                                print_info("Schema changed");
+                                watch_render(&task, None, on_conflict).await;
+                            }
+                            Ok(false) => {}
+                            Err(e) => print_fail(e),
+                        }
+                    }
+                    _ = &mut ctrl_c => break,
+                }
+            }
+
+            Ok(())
+        }
+        Commands::Render {
+            profile,
+            expr,
+            file,
+            context,
+            prompt,
+            table,
+        } => {
+            let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+            print_deprecations(&ctgen, profile_name);
+            ctgen.set_current_profile(profile_name).await?;
+
+            let template = if let Some(expr) = expr {
+                expr
+            } else if let Some(file) = file {
+                tokio::fs::read_to_string(&file)
+                    .await
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to read template file {}: {}", file, e)))?
+            } else {
+                return Err(CtGenError::ValidationError("Either --expr or --file is required".to_string()).into());
+            };
+
+            let output = if let Some(context_file) = context {
+                let content = tokio::fs::read_to_string(&context_file)
+                    .await
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to read context file {}: {}", context_file, e)))?;
+
+                let context: Value = serde_json::from_str(&content)
+                    .map_err(|e| CtGenError::ValidationError(format!("Invalid context file {}: {}", context_file, e)))?;
+
+                ctgen.render(&template, &context)?
+            } else {
+                let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
+
+                let mut task = ctgen.create_task(&context_dir, table.as_deref(), None).await?;
+
+                set_prompt_overrides(&mut task, &prompt.unwrap_or_default()).await?;
+
+                if task.context().is_none() {
+                    return Err(CtGenError::ValidationError(
+                        "Context is not ready. Pass a table name and a database with --prompt database=... if the DSN has none".to_string(),
+                    )
+                    .into());
+                }
+
+                task.render(&template)?
+            };
+
+            println!("{}", output);
+
+            Ok(())
+        }
+        Commands::Context {
+            profile,
+            save,
+            prompt,
+            table,
+        } => {
+            let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+            print_deprecations(&ctgen, profile_name);
+            ctgen.set_current_profile(profile_name).await?;
+
+            let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
+
+            let mut task = ctgen.create_task(&context_dir, table.as_deref(), None).await?;
+
+            set_prompt_overrides(&mut task, &prompt.unwrap_or_default()).await?;
+
+            if task.context().is_none() {
+                return Err(CtGenError::ValidationError(
+                    "Context is not ready. Pass a table name and a database with --prompt database=... if the DSN has none".to_string(),
+                )
+                .into());
+            }
+
+            // secret answers stay out of dumps and fixtures
+            let content = serde_json::to_string_pretty(&task.redacted_context()?)?;
+
+            if let Some(file) = save {
+                if let Some(parent) = Path::new(&file).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                    tokio::fs::create_dir_all(parent)
+                        .await
+                        .map_err(|e| CtGenError::RuntimeError(format!("Failed to create directory {}: {}", parent.display(), e)))?;
+                }
+
+                tokio::fs::write(&file, format!("{}\n", content))
+                    .await
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to write context file {}: {}", file, e)))?;
+
+                // prompts left unanswered are missing from the fixture
+                let unanswered = task
+                    .prompts_unanswered()
+                    .iter()
+                    .map(|prompt| prompt.prompt_id().to_string())
+                    .collect::<Vec<String>>();
+
+                if !unanswered.is_empty() {
+                    print_fail(format!(
+                        "Unanswered prompts left out of the context: {}. Answer them with --prompt",
+                        unanswered.join(", ")
+                    ));
+                }
+
+                print_info(format!("Context saved to {}", style(&file).cyan()));
+            } else {
+                println!("{}", content);
+            }
+
+            Ok(())
+        }
+        Commands::Coverage {
+            profile,
+            context,
             prompt,
+            format,
             table,
         } => {
             let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
 
-            print_info(format!("Loading profile {}", style(profile_name).cyan()));
+            let mut contexts = Vec::new();
+
+            if let Some(context_files) = context {
+                for context_file in context_files {
+                    let content = tokio::fs::read_to_string(&context_file)
+                        .await
+                        .map_err(|e| CtGenError::RuntimeError(format!("Failed to read context file {}: {}", context_file, e)))?;
+
+                    contexts.push(
+                        serde_json::from_str::<Value>(&content)
+                            .map_err(|e| CtGenError::ValidationError(format!("Invalid context file {}: {}", context_file, e)))?,
+                    );
+                }
+            } else {
+                print_deprecations(&ctgen, profile_name);
+                ctgen.set_current_profile(profile_name).await?;
+
+                let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
+
+                let mut task = ctgen.create_task(&context_dir, table.as_deref(), None).await?;
+
+                set_prompt_overrides(&mut task, &prompt.unwrap_or_default()).await?;
+
+                if task.context().is_none() {
+                    return Err(CtGenError::ValidationError(
+                        "Context is not ready. Pass a table name and a database with --prompt database=... if the DSN has none".to_string(),
+                    )
+                    .into());
+                }
+
+                contexts.push(serde_json::to_value(task.context())?);
+            }
+
+            let report = ctgen.coverage(profile_name, &contexts).await?;
+
+            if format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", report);
+            }
+
+            Ok(())
+        }
+        Commands::Status { profile, target_dir } => {
+            let manifest = CtGenManifest::load(&resolve_target_dir(&mut ctgen, profile.as_deref(), target_dir.as_deref()).await?).await?;
+
+            if manifest.is_empty() {
+                print_info(format!("No generated files recorded in {}", style(manifest.target_dir()).cyan()));
+
+                return Ok(());
+            }
+
+            for (file, entry) in manifest.files() {
+                let status = manifest.status(file).await?;
+
+                let label = match status {
+                    CtGenFileStatus::Unchanged => style(status.to_string()).green(),
+                    CtGenFileStatus::Modified => style(status.to_string()).yellow(),
+                    CtGenFileStatus::Missing => style(status.to_string()).red(),
+                };
+
+                println!(
+                    "{:>9} {} {}",
+                    label,
+                    file,
+                    style(format!("({}/{})", entry.profile(), entry.target())).dim()
+                );
+            }
+
+            Ok(())
+        }
+        Commands::Clean {
+            profile,
+            target_dir,
+            force,
+            yes,
+        } => {
+            let mut manifest =
+                CtGenManifest::load(&resolve_target_dir(&mut ctgen, profile.as_deref(), target_dir.as_deref()).await?).await?;
+
+            let mut removable = Vec::new();
+
+            for (file, _) in manifest.files() {
+                match manifest.status(file).await? {
+                    CtGenFileStatus::Modified if !force => {
+                        print_info(format!(
+                            "Kept {}, modified since generation. Use --force to remove it",
+                            style(file).yellow()
+                        ));
+                    }
+                    status => removable.push((file.clone(), status)),
+                }
+            }
+
+            if removable.is_empty() {
+                print_info("Nothing to clean");
+
+                return Ok(());
+            }
+
+            for (file, _) in &removable {
+                println!("{} {}", style("remove").red(), file);
+            }
+
+            if !yes && !ask_confirm(&format!("Remove {} generated files?", removable.len()))? {
+                return Ok(());
+            }
+
+            for (file, status) in removable {
+                if status != CtGenFileStatus::Missing {
+                    let path = CtGen::get_filepath(manifest.target_dir(), &file);
+
+                    tokio::fs::remove_file(&path)
+                        .await
+                        .map_err(|e| CtGenError::RuntimeError(format!("Failed to remove generated file {}: {}", path, e)))?;
+                }
+
+                manifest.remove(&file);
+            }
+
+            manifest.save().await?;
+
+            print_info("Cleaned generated files");
+
+            Ok(())
+        }
+        Commands::Prune {
+            profile,
+            force,
+            yes,
+            prompt,
+            table,
+        } => {
+            let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+            print_deprecations(&ctgen, profile_name);
+            ctgen.set_current_profile(profile_name).await?;
+
+            let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
+
+            let (target_dir, planned) = if let Some(table) = table.as_deref() {
+                // plan the table run to find files its targets don't produce anymore
+                let mut task = ctgen.create_task(&context_dir, Some(table), None).await?;
+
+                set_prompt_overrides(&mut task, &prompt.unwrap_or_default()).await?;
+
+                if !task.is_context_ready() {
+                    return Err(CtGenError::ValidationError(
+                        "Context is not ready. Answer all prompts with --prompt, and pass a database with --prompt database=... if the DSN has none"
+                            .to_string(),
+                    )
+                    .into());
+                }
+
+                let outputs = task.plan().await?;
+
+                (
+                    task.target_dir().to_string(),
+                    Some((
+                        table,
+                        outputs.iter().map(|output| output.file().to_string()).collect::<Vec<String>>(),
+                    )),
+                )
+            } else {
+                (resolve_target_dir(&mut ctgen, Some(profile_name), None).await?, None)
+            };
+
+            let mut manifest = CtGenManifest::load(&target_dir).await?;
+
+            let targets = ctgen
+                .get_current_profile()
+                .map(|profile| profile.configuration().targets().clone())
+                .unwrap_or_default();
+
+            let planned_files = planned
+                .as_ref()
+                .map(|(table, files)| (*table, files.iter().map(String::as_str).collect::<Vec<&str>>()));
+
+            let stale = manifest.stale(
+                profile_name,
+                &targets,
+                planned_files.as_ref().map(|(table, files)| (*table, files.as_slice())),
+            );
+
+            let mut removable = Vec::new();
+
+            for file in stale {
+                match manifest.status(&file).await? {
+                    CtGenFileStatus::Modified if !force => {
+                        print_info(format!(
+                            "Kept {}, modified since generation. Use --force to remove it",
+                            style(&file).yellow()
+                        ));
+                    }
+                    status => removable.push((file, status)),
+                }
+            }
+
+            if removable.is_empty() {
+                print_info("Nothing to prune");
+
+                return Ok(());
+            }
+
+            for (file, _) in &removable {
+                println!("{} {}", style("remove").red(), file);
+            }
+
+            if !yes && !ask_confirm(&format!("Remove {} files no longer generated?", removable.len()))? {
+                return Ok(());
+            }
+
+            for (file, status) in removable {
+                if status != CtGenFileStatus::Missing {
+                    let path = CtGen::get_filepath(manifest.target_dir(), &file);
+
+                    tokio::fs::remove_file(&path)
+                        .await
+                        .map_err(|e| CtGenError::RuntimeError(format!("Failed to remove generated file {}: {}", path, e)))?;
+                }
+
+                manifest.remove(&file);
+            }
+
+            manifest.save().await?;
+
+            print_info("Pruned stale generated files");
+
+            Ok(())
+        }
+        Commands::Verify {
+            profile,
+            prompt,
+            format,
+            table,
+        } => {
+            let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+            print_deprecations(&ctgen, profile_name);
+            ctgen.set_current_profile(profile_name).await?;
+
+            let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
+
+            let task = if let Some(table) = table.as_deref() {
+                let mut task = ctgen.create_task(&context_dir, Some(table), None).await?;
+
+                set_prompt_overrides(&mut task, &prompt.unwrap_or_default()).await?;
+
+                if !task.is_context_ready() {
+                    return Err(CtGenError::ValidationError(
+                        "Context is not ready. Answer all prompts with --prompt, and pass a database with --prompt database=... if the DSN has none"
+                            .to_string(),
+                    )
+                    .into());
+                }
+
+                Some(task)
+            } else {
+                None
+            };
+
+            let target_dir = if let Some(task) = task.as_ref() {
+                task.target_dir().to_string()
+            } else {
+                resolve_target_dir(&mut ctgen, Some(profile_name), None).await?
+            };
+
+            let manifest = CtGenManifest::load(&target_dir).await?;
+
+            let profile = ctgen
+                .get_current_profile()
+                .ok_or_else(|| CtGenError::RuntimeError("Profile is not loaded".to_string()))?;
+
+            let report = verify::verify(&manifest, profile, task.as_ref()).await?;
+
+            if format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", report);
+            }
+
+            if !report.is_verified() {
+                return Err(CtGenError::ValidationError(format!("Generated files in {} failed verification", report.target_dir())).into());
+            }
+
+            Ok(())
+        }
+        Commands::Lint { profile, format } => {
+            let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+            if format == OutputFormat::Text {
+                print_info(format!("Linting profile {}", style(profile_name).cyan()));
+            }
+
+            let issues = ctgen.lint_profile(profile_name).await?;
+            let errors = issues.iter().filter(|issue| issue.is_error()).count();
+
+            if format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&issues)?);
+            } else if issues.is_empty() {
+                print_info("No issues found.");
+            } else {
+                for issue in issues.iter() {
+                    print_fail(issue);
+                }
+            }
+
+            if errors > 0 {
+                return Err(CtGenError::ValidationError(format!("Found {} lint error(s)", errors)).into());
+            }
+
+            Ok(())
+        }
+        Commands::Test { profile, update, format } => {
+            let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+            let report = ctgen.test(profile_name, update).await?;
+
+            if format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                if !update {
+                    for file in report.files().iter().filter(|file| !file.diff().is_empty()) {
+                        print_diff(file.diff());
+                    }
+                }
+
+                println!("{}", report);
+            }
+
+            if !update && !report.is_passed() {
+                return Err(CtGenError::ValidationError(
+                    "Rendered outputs differ from expected outputs. Run with --update to accept them".to_string(),
+                )
+                .into());
+            }
+
+            Ok(())
+        }
+        Commands::TestHelpers { profile, format } => {
+            let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+            let report = ctgen.test_helpers(profile_name).await?;
+
+            if format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", report);
+            }
+
+            if !report.is_passed() {
+                return Err(CtGenError::ValidationError("Helper tests failed".to_string()).into());
+            }
+
+            Ok(())
+        }
+        Commands::Explain { profile, target } => {
+            let explanation = ctgen.explain_target(&profile, &target).await?;
+
+            println!("{}", explanation);
+
+            Ok(())
+        }
+        Commands::Helpers { op } => match op {
+            CommandHelpers::List { profile } => {
+                let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+                print_info(format!("Helpers available to profile {}:", style(profile_name).cyan()));
+
+                for helper in ctgen.list_helpers(profile_name).await? {
+                    println!(
+                        "{}\t{}{}\t{}",
+                        style(helper.kind()).dim(),
+                        style(helper.name()).cyan(),
+                        if helper.memoized() { " (memoized)" } else { "" },
+                        style(helper.file().unwrap_or_default()).underlined()
+                    );
+                }
+
+                Ok(())
+            }
+        },
+        Commands::New { op } => match op {
+            CommandNew::Target {
+                profile,
+                template,
+                copy,
+                condition,
+                formatter,
+                name,
+                target,
+            } => {
+                let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+                let template = template.unwrap_or_else(|| name.clone());
+
+                let target = if let Some(target) = target {
+                    target
+                } else if copy.is_some() {
+                    return Err(CtGenError::ValidationError("Copy targets need an output path".to_string()).into());
+                } else {
+                    ctgen
+                        .template_meta(profile_name, &template)
+                        .await?
+                        .and_then(|meta| meta.target().map(str::to_string))
+                        .ok_or_else(|| {
+                            CtGenError::ValidationError(format!(
+                                "No output path given and template {} declares no default target in its front matter",
+                                template
+                            ))
+                        })?
+                };
+
+                let mut new_target = if let Some(source) = copy.as_deref() {
+                    CtGenTarget::new_copy(source, &target)
+                } else {
+                    CtGenTarget::new(&template, &target)
+                };
+                new_target.set_condition(condition.as_deref()).set_formatter(formatter.as_deref());
+
+                ctgen.add_target(profile_name, &name, &new_target).await?;
+                ctgen.refresh_registration(profile_name).await?;
+
+                print_info(format!(
+                    "Added target {} to profile {}",
+                    style(&name).cyan(),
+                    style(profile_name).cyan()
+                ));
+
+                Ok(())
+            }
+            CommandNew::Prompt {
+                profile,
+                condition,
+                options,
+                default,
+                multiple,
+                required,
+                secret,
+                name,
+                prompt,
+            } => {
+                let profile_name = if let Some(p) = profile.as_deref() { p } else { CONFIG_NAME_DEFAULT };
+
+                let mut new_prompt = CtGenPrompt::new(&prompt);
+                new_prompt
+                    .set_condition(condition.as_deref())
+                    .set_default(default.as_deref())
+                    .set_multiple(multiple)
+                    .set_required(required)
+                    .set_secret(secret);
+
+                if let Some(options) = options {
+                    new_prompt.set_options(toml::Value::String(options));
+                }
+
+                ctgen.add_prompt(profile_name, &name, &new_prompt).await?;
+                ctgen.refresh_registration(profile_name).await?;
+
+                print_info(format!(
+                    "Added prompt {} to profile {}",
+                    style(&name).cyan(),
+                    style(profile_name).cyan()
+                ));
+
+                Ok(())
+            }
+        },
+        Commands::Serve {
+            address,
+            token,
+            allow_dsn,
+            idle_timeout,
+        } => {
+            let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
+
+            let token = token
+                .or_else(|| std::env::var(ENV_SERVER_TOKEN).ok())
+                .filter(|token| !token.is_empty())
+                .unwrap_or_else(|| {
+                    let token = CtGenServer::generate_token();
+                    print_info(format!("Token {}", style(&token).cyan()));
+                    token
+                });
+
+            let mut server = CtGenServer::new(ctgen, &context_dir, &token);
+            server.set_allow_dsn(allow_dsn).set_idle_timeout(Duration::from_secs(idle_timeout));
+
+            print_info(format!("Listening on {}", style(format!("http://{}", address)).cyan()));
+
+            server.serve(&address).await
+        }
+        Commands::Init {
+            name,
+            kit,
+            from_table,
+            dsn,
+            path,
+        } => {
+            let name = if let Some(name) = name {
+                name
+            } else {
+                //CONFIG_NAME_DEFAULT.to_string()
+                let default_name = if ctgen.get_profiles().contains_key(CONFIG_NAME_DEFAULT) {
+                    // there's already a default profile, so we better suggest something else, like for example the path, if it's alphanumeric, or the base directory name of the CWD
+                    if CtGen::get_name_regex().is_match(&path) {
+                        path.clone()
+                    } else {
+                        Path::new(&CtGen::get_current_working_dir()?)
+                            .file_name()
+                            .and_then(OsStr::to_str)
+                            .unwrap_or_default()
+                            .to_string()
+                    }
+                } else {
+                    CONFIG_NAME_DEFAULT.to_string()
+                };
+
+                loop {
+                    let answer = ask_prompt(
+                        "Enter profile name:",
+                        Some(&Value::String(default_name.clone())),
+                        false,
+                        &[],
+                        &CtGenPromptDefaults::default(),
+                    )
+                    .await;
+
+                    if answer.as_ref().is_ok_and(|v| v.as_str().is_some_and(|s| !s.is_empty())) {
+                        break answer.ok().and_then(|a| a.as_str().map(str::to_string)).unwrap_or_default();
+                    }
+                }
+            };
+
+            print_info(format!("Creating profile {}", style(&name).cyan()));
+
+            let kit = if let Some(kit) = kit {
+                kit
+            } else {
+                let options = Value::Object(
+                    STARTER_KITS
+                        .iter()
+                        .map(|kit| (kit.name().to_string(), Value::String(kit.to_string())))
+                        .collect(),
+                );
+
+                ask_prompt("Select starter kit:", Some(&options), false, &[], &CtGenPromptDefaults::default())
+                    .await?
+                    .as_str()
+                    .unwrap_or(KIT_BLANK)
+                    .to_string()
+            };
+
+            let _profile = match (from_table, dsn) {
+                (Some(table), Some(dsn)) => ctgen.init_profile_from_table(&path, &name, &kit, &dsn, &table).await?,
+                _ => ctgen.init_profile_with_kit(&path, &name, &kit).await?,
+            };
+
+            print_info(format!("Created and registered profile {}", style(&name).cyan()));
+
+            Ok(())
+        }
+    }
+}
+
+/// How a run treats the rendered targets
+#[derive(Clone)]
+struct RunOptions {
+    /// Print rendered targets to stdout instead of writing them
+    stdout: bool,
+    /// Only check whether generated files are up to date
+    check: bool,
+    /// Archive file to collect the rendered targets into instead of writing them
+    archive: Option<String>,
+    /// Only plan the targets
+    dry_run: bool,
+    /// Show a diff for every existing output file and ask whether to apply it
+    diff: bool,
+    /// Apply all diffs without asking
+    yes: bool,
+    /// What to do with output files modified by hand
+    on_conflict: ConflictPolicy,
+}
+
+/// Render the targets of a task with answered prompts and write, print, check or archive them
+async fn run_task(task: &CtGenTask<'_>, options: RunOptions) -> Result<()> {
+    if options.stdout {
+        print_info("Rendering targets");
+
+        let outputs = task.plan().await?;
+        print_outputs(outputs.iter())?;
+
+        print_info(format!("{} target(s) printed, no files written", outputs.len()));
+
+        return Ok(());
+    }
+
+    if options.check {
+        print_info("Checking targets");
+
+        let outputs = task.plan().await?;
+        let stale = task.check(&outputs).await?;
+
+        for output in stale.iter() {
+            println!(
+                "{}\t{}",
+                if output.overwrite() {
+                    style("changed").yellow()
+                } else {
+                    style("missing").red()
+                },
+                output.file()
+            );
+        }
+
+        if !stale.is_empty() {
+            return Err(CtGenError::ValidationError(format!("{} of {} generated file(s) out of date", stale.len(), outputs.len())).into());
+        }
+
+        print_info(format!("{} target(s) up to date", outputs.len()));
+
+        return Ok(());
+    }
+
+    if let Some(archive) = options.archive {
+        print_info("Rendering targets");
+
+        let outputs = task.plan().await?;
+        task.archive(&outputs, &archive).await?;
+
+        print_info(format!(
+            "{} target(s) archived into {}",
+            outputs.iter().filter(|output| !output.is_stdout()).count(),
+            style(&archive).cyan()
+        ));
+
+        return Ok(());
+    }
+
+    if !options.dry_run && !options.diff {
+        print_info("Running ctgen task");
+
+        let outputs = resolve_conflicts(task, task.plan().await?, options.on_conflict).await?;
+        print_results(&task.write(&outputs).await?)?;
+
+        return Ok(());
+    }
+
+    print_info("Rendering targets");
+
+    let outputs = task.plan().await?;
+
+    if options.dry_run {
+        for output in outputs.iter() {
+            if options.diff {
+                if let Some(output_diff) = output.diff() {
+                    print_diff(&output_diff);
+                }
+            }
+
+            println!(
+                "{}\t{}\t{}",
+                match output.action() {
+                    CtGenTargetAction::Create => style(output.action()).green(),
+                    CtGenTargetAction::Overwrite | CtGenTargetAction::Append => style(output.action()).yellow(),
+                    CtGenTargetAction::Skip => style(output.action()).dim(),
+                    CtGenTargetAction::Conflict => style(output.action()).red(),
+                },
+                style(format!("{} bytes", output.size())).dim(),
+                output.file()
+            );
+        }
+
+        print_info(format!("{} target(s) planned, no files written", outputs.len()));
+
+        return Ok(());
+    }
+
+    // review changes to existing files before writing
+    let mut approved = Vec::new();
+
+    for output in resolve_conflicts(task, outputs, options.on_conflict).await? {
+        match output.action() {
+            CtGenTargetAction::Create => {
+                approved.push(output);
+                continue;
+            }
+            CtGenTargetAction::Skip => {
+                print_info(format!("Kept existing {}", style(output.file()).cyan()));
+                continue;
+            }
+            CtGenTargetAction::Conflict => {
+                return Err(CtGenError::ValidationError(format!(
+                    "Target `{}` output file {} already exists",
+                    output.name(),
+                    output.file()
+                ))
+                .into());
+            }
+            CtGenTargetAction::Overwrite | CtGenTargetAction::Append => {}
+        }
+
+        if let Some(output_diff) = output.diff() {
+            print_diff(&output_diff);
+
+            if options.yes || ask_confirm(&format!("Apply changes to {}?", output.file()))? {
+                approved.push(output);
+            } else {
+                print_fail(format!("Skipped {}", style(output.file()).cyan()));
+            }
+        } else {
+            print_info(format!("Unchanged {}", style(output.file()).cyan()));
+        }
+    }
+
+    print_info("Writing targets");
+    print_results(&task.write(&approved).await?)?;
+
+    Ok(())
+}
+
+/// Show a desktop notification with the outcome of a run. Failing to show it is only logged
+fn notify_run_finished(result: &Result<()>, elapsed: Duration) {
+    let body = match result {
+        Ok(()) => format!("Run finished in {}s", elapsed.as_secs()),
+        Err(e) => format!("Run failed after {}s: {}", elapsed.as_secs(), e),
+    };
+
+    if let Err(e) = Notification::new().summary("ctgen").body(&body).show() {
+        debug!("Failed to show desktop notification: {}", e);
+    }
+}
+
+/// Ask a rendered generic prompt, or one item of an enumerated prompt. Piped answers are not re-asked, invalid ones fail
+/// the run. Otherwise the prompt is asked again until the answer passes validation
+async fn ask_generic_prompt(
+    task: &CtGenTask<'_>,
+    prompt: &CtGenTaskPrompt,
+    rendered_prompt: &CtGenRenderedPrompt,
+    item: Option<&str>,
+    stdin_answers: bool,
+) -> Result<Value> {
+    if stdin_answers {
+        return read_stdin_answer(prompt);
+    }
+
+    loop {
+        let options = rendered_prompt.options();
+
+        let answer = match prompt {
+            _ if rendered_prompt.secret() => ask_secret(rendered_prompt.prompt())?,
+            // path prompts are typed with completion, unless picked from a list of options
+            CtGenTaskPrompt::PromptGeneric { prompt_id: _, prompt_data }
+                if prompt_data.kind() == CtGenPromptType::Path && !options.is_array() && !options.is_object() =>
+            {
+                ask_path(rendered_prompt.prompt(), options.as_str(), rendered_prompt.appearance())?
+            }
+            // confirm prompts are yes/no questions, unless picked from their custom labels or skip option
+            CtGenTaskPrompt::PromptGeneric { prompt_id: _, prompt_data }
+                if prompt_data.kind() == CtGenPromptType::Confirm && prompt_data.labels().is_empty() =>
+            {
+                ask_yes_no(rendered_prompt.prompt(), rendered_prompt.appearance())?
+            }
+            // editor prompts start from their string options
+            CtGenTaskPrompt::PromptGeneric { prompt_id: _, prompt_data } if prompt_data.kind() == CtGenPromptType::Editor => {
+                ask_editor(rendered_prompt.prompt(), options.as_str().unwrap_or_default())?
+            }
+            _ => {
+                ask_prompt(
+                    rendered_prompt.prompt(),
+                    Some(options),
+                    rendered_prompt.multiple(),
+                    rendered_prompt.selected(),
+                    rendered_prompt.appearance(),
+                )
+                .await?
+            }
+        };
+
+        // item answers are checked on their own, the answers by item once every item is answered
+        let validated = match item {
+            Some(item) => task.validate_item_answer(prompt, item, &answer).await,
+            None => task.validate_answer(prompt, &answer).await,
+        };
+
+        match validated {
+            Ok(()) => return Ok(answer),
+            Err(e) => print_fail(e),
+        }
+    }
+}
+
+/// Ask the unanswered prompts of a task, in prompt order. Piped answers are read from stdin instead, and in
+/// non-interactive mode the prompts that would be asked fail the run. Interactively asked answers can be reviewed and
+/// changed before the task runs
+async fn answer_prompts(task: &mut CtGenTask<'_>, stdin_answers: bool, non_interactive: bool) -> Result<()> {
+    let mut prompt_group: Option<String> = None;
+    let mut asked = false;
+
+    loop {
+        let unanswered_prompts = task.prompts_unanswered(); // TODO clone not great
+
+        if unanswered_prompts.is_empty() {
+            // targets rendered with the answers are shown, and answers can be changed before rendering, once
+            // prompts were asked interactively
+            if asked {
+                print_preview(&task.preview()?);
+            }
+
+            if asked && review_answers(task).await? {
+                prompt_group = None;
+                continue;
+            }
+
+            break;
+        }
+
+        // list every prompt that would be asked instead of asking it
+        if non_interactive {
+            let missing = unasked_prompts(task, unanswered_prompts).await?;
+
+            if missing.is_empty() {
+                continue;
+            }
+
+            return Err(CtGenError::ValidationError(format!(
+                "Unanswered prompts in non-interactive mode: {}. Answer them with --prompt, --answers or CTGEN_PROMPT_<ID> variables",
+                missing.join(", ")
+            ))
+            .into());
+        }
+
+        print_info("Preparing prompts");
+
+        asked = asked || !stdin_answers;
+
+        for unanswered_prompt in unanswered_prompts {
+            match unanswered_prompt.clone() {
+                CtGenTaskPrompt::PromptDatabase => {
+                    let options = Value::from(task.reflection_adapter().list_database_names().await?);
+
+                    let answer = if stdin_answers {
+                        read_stdin_answer(&unanswered_prompt)?
+                    } else {
+                        ask_prompt("Enter database name:", Some(&options), false, &[], task.profile().prompt_defaults()).await?
+                    };
+
+                    task.set_prompt_answer(&unanswered_prompt, answer).await?;
+                }
+                CtGenTaskPrompt::PromptTable => {
+                    let answer = if stdin_answers {
+                        read_stdin_answer(&unanswered_prompt)?
+                    } else {
+                        ask_table(task.list_table_names().await?, task.profile().prompt_defaults()).await?
+                    };
+
+                    task.set_prompt_answer(&unanswered_prompt, answer).await?;
+                }
+                CtGenTaskPrompt::PromptGeneric { prompt_id: _, prompt_data } => {
+                    // prompts come after the prompts they reference, so they render with those answers
+                    let rendered_prompt = task.render_prompt(&prompt_data)?;
+
+                    let mut answer = Value::from("");
+                    if rendered_prompt.should_ask() {
+                        // group headers are printed when a group of asked prompts starts
+                        if prompt_data.group() != prompt_group.as_deref() {
+                            if let Some(group) = prompt_data.group() {
+                                print_group(group);
+                            }
+                            prompt_group = prompt_data.group().map(str::to_string);
+                        }
+
+                        if let Some(items) = rendered_prompt.enumerate() {
+                            // enumerated prompts are asked once per item and answered by item
+                            let mut answers = Map::new();
+
+                            for item in items {
+                                let item_prompt = task.render_prompt_item(&prompt_data, item)?;
+                                let item_answer =
+                                    ask_generic_prompt(task, &unanswered_prompt, &item_prompt, Some(item), stdin_answers).await?;
+
+                                answers.insert(item.to_string(), item_answer);
+                            }
+
+                            answer = Value::Object(answers);
+                        } else {
+                            answer = ask_generic_prompt(task, &unanswered_prompt, &rendered_prompt, None, stdin_answers).await?;
+                        }
+                    }
+
+                    task.set_prompt_answer(&unanswered_prompt, answer).await?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Render the targets of a watched task again, all of them or the ones using the changed templates, and write them.
+/// Failures are printed, so watching goes on
+async fn watch_render(task: &CtGenTask<'_>, templates: Option<&[String]>, on_conflict: ConflictPolicy) {
+    let result: Result<()> = async {
+        let outputs = match templates {
+            Some(templates) => task.plan_templates(templates).await?,
+            None => task.plan().await?,
+        };
+
+        let results = task.write(&resolve_conflicts(task, outputs, on_conflict).await?).await?;
+
+        print_results(&results)?;
+        print_info(format!("{} target(s) rendered", results.len()));
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        print_fail(e);
+    }
+}
+
+/// Show the answers of the asked prompts and let the user pick one to change. The picked prompt and the prompts depending
+/// on it are cleared, to be asked again. Returns false once the user continues with the given answers
+async fn review_answers(task: &mut CtGenTask<'_>) -> Result<bool> {
+    let mut prompt_ids = Vec::new();
+    let mut items = vec!["Continue".to_string()];
+
+    for prompt in task.prompts() {
+        let answer = match prompt {
+            CtGenTaskPrompt::PromptTable => task.table().map(str::to_string),
+            CtGenTaskPrompt::PromptGeneric { prompt_id, prompt_data } => task.prompt_answer(prompt_id).map(|answer| {
+                if prompt_data.secret() {
+                    "********".to_string()
+                } else {
+                    answer.as_str().map(str::to_string).unwrap_or_else(|| answer.to_string())
+                }
+            }),
+            // the database is picked before the table list can be loaded, so it can't be changed here
+            CtGenTaskPrompt::PromptDatabase => None,
+        };
+
+        if let Some(answer) = answer {
+            prompt_ids.push(prompt.prompt_id().to_string());
+            items.push(format!("{}: {}", prompt.prompt_id(), answer));
+        }
+    }
+
+    let prompt_text = "Review answers. Pick one to change or continue:";
+
+    let selection = if simple_prompts() {
+        ask_plain_select(prompt_text, &items)?
+    } else {
+        Select::with_theme(cli_theme().prompts())
+            .with_prompt(prompt_text)
+            .items(&items[..])
+            .default(0)
+            .report(false)
+            .interact()
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to render select prompt `{}`: {}", prompt_text, e)))?
+    };
+
+    let Some(prompt_id) = selection.checked_sub(1).and_then(|index| prompt_ids.get(index)) else {
+        return Ok(false);
+    };
+
+    let cleared = task.clear_prompt_answer(prompt_id)?;
+
+    if cleared.len() > 1 {
+        print_info(format!("Asking again {}", cleared.join(", ")));
+    }
+
+    Ok(true)
+}
+
+/// Collect the ids of unanswered prompts that would be asked. Prompts whose condition isn't met, and enumerated prompts
+/// without items, are answered with empty values on the way, as when asked interactively
+async fn unasked_prompts(task: &mut CtGenTask<'_>, unanswered_prompts: Vec<CtGenTaskPrompt>) -> Result<Vec<String>> {
+    let mut missing = Vec::new();
+
+    for unanswered_prompt in unanswered_prompts {
+        if let CtGenTaskPrompt::PromptGeneric { prompt_id: _, prompt_data } = &unanswered_prompt {
+            let rendered_prompt = task.render_prompt(prompt_data)?;
+
+            if !rendered_prompt.should_ask() {
+                task.set_prompt_answer(&unanswered_prompt, Value::from("")).await?;
+                continue;
+            }
+
+            if rendered_prompt.enumerate().is_some_and(Vec::is_empty) {
+                task.set_prompt_answer(&unanswered_prompt, Value::Object(Map::new())).await?;
+                continue;
+            }
+        }
+
+        missing.push(unanswered_prompt.prompt_id().to_string());
+    }
+
+    Ok(missing)
+}
+
+/// Set pre-defined prompt answers and `CTGEN_PROMPT_<ID>` environment variable answers. Comma-separated values are split
+/// for generic prompts
+async fn set_prompt_overrides(task: &mut CtGenTask<'_>, prompts: &[(String, String)]) -> Result<()> {
+    let mut answers = CtGenAnswers::default();
+
+    add_prompt_overrides(task, &mut answers, prompts);
+
+    task.set_answers(&answers).await
+}
+
+/// Add pre-defined prompt answers of unanswered prompts, replacing answers given before. The last value of a prompt wins.
+/// Prompts without a pre-defined answer take the value of their `CTGEN_PROMPT_<ID>` environment variable, if set
+fn add_prompt_overrides(task: &CtGenTask<'_>, answers: &mut CtGenAnswers, prompts: &[(String, String)]) {
+    for unanswered_prompt in task.prompts_unanswered().iter() {
+        let answered_prompt_answer = prompts
+            .iter()
+            .rev()
+            .find(|(answered_prompt_id, _)| answered_prompt_id == unanswered_prompt.prompt_id())
+            .map(|(_, answered_prompt_answer)| answered_prompt_answer.to_string())
+            .or_else(|| std::env::var(unanswered_prompt.env_var()).ok());
+
+        if let Some(answered_prompt_answer) = answered_prompt_answer {
+            answers.insert(
+                unanswered_prompt.prompt_id(),
+                prompt_answer_value(unanswered_prompt, &answered_prompt_answer),
+            );
+        }
+    }
+}
+
+/// List the tables of an `--all-tables` run. The database is answered first, asked for if needed, and kept in the answers
+/// of the tasks run per table
+async fn list_all_tables(task: &mut CtGenTask<'_>, answers: &mut CtGenAnswers, unattended: bool) -> Result<Vec<String>> {
+    if task
+        .prompts_unanswered()
+        .iter()
+        .any(|prompt| matches!(prompt, CtGenTaskPrompt::PromptDatabase))
+    {
+        let database = match answers.get(PROMPT_ID_DATABASE) {
+            Some(database) => database.clone(),
+            None if unattended => {
+                return Err(CtGenError::ValidationError(
+                    "--all-tables needs a database. Set it in the DSN or pass --prompt \"database=...\"".to_string(),
+                )
+                .into());
+            }
+            None => {
+                let options = Value::from(task.reflection_adapter().list_database_names().await?);
+
+                ask_prompt("Enter database name:", Some(&options), false, &[], task.profile().prompt_defaults()).await?
+            }
+        };
+
+        task.set_prompt_answer(&CtGenTaskPrompt::PromptDatabase, database.clone()).await?;
+        answers.insert(PROMPT_ID_DATABASE, database);
+    }
+
+    task.list_table_names().await
+}
+
+/// Ask for a table name. Lists longer than a page are narrowed down by a name filter first, then picked page by page
+async fn ask_table(tables: Vec<String>, appearance: &CtGenPromptDefaults) -> Result<Value> {
+    if tables.len() <= appearance.max_options() {
+        return ask_prompt("Enter table name:", Some(&Value::from(tables)), false, &[], appearance).await;
+    }
+
+    loop {
+        let filter_prompt = format!("Filter {} tables by name, or leave empty to list all:", tables.len());
+
+        let filter = if simple_prompts() {
+            read_plain_line(&filter_prompt)?
+        } else {
+            Input::<String>::with_theme(cli_theme().prompts())
+                .with_prompt(filter_prompt)
+                .allow_empty(true)
+                .report(appearance.report())
+                .interact_text()
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to render table filter prompt: {}", e)))?
+        }
+        .to_lowercase();
 
-            ctgen.set_current_profile(profile_name).await?;
+        let matching = tables
+            .iter()
+            .filter(|table| table.to_lowercase().contains(&filter))
+            .cloned()
+            .collect::<Vec<String>>();
 
-            let mut profile_overrides: Option<CtGenProfileConfigOverrides> = None;
+        if matching.is_empty() {
+            print_fail(format!("No table matches {}", filter));
+            continue;
+        }
 
-            if env_file.is_some() || env_var.is_some() || dsn.is_some() || target_dir.is_some() {
-                print_info("Overriding profile parameters");
-                profile_overrides = Some(CtGenProfileConfigOverrides::new(env_file, env_var, dsn, target_dir));
+        // pages leave room for the previous and next page items
+        let pages = matching
+            .chunks(appearance.max_options().saturating_sub(2).max(1))
+            .collect::<Vec<&[String]>>();
+        let mut page = 0;
+
+        loop {
+            let mut items = pages[page].to_vec();
+
+            if page > 0 {
+                items.insert(0, PROMPT_PAGE_PREVIOUS.to_string());
             }
 
-            let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
+            if page + 1 < pages.len() {
+                items.push(PROMPT_PAGE_NEXT.to_string());
+            }
 
-            print_info("Creating ctgen task");
+            let answer = ask_prompt(
+                &format!("Enter table name (page {} of {}):", page + 1, pages.len()),
+                Some(&Value::from(items)),
+                false,
+                &[],
+                appearance,
+            )
+            .await?;
+
+            match answer.as_str() {
+                Some(PROMPT_PAGE_PREVIOUS) => page -= 1,
+                Some(PROMPT_PAGE_NEXT) => page += 1,
+                _ => return Ok(answer),
+            }
+        }
+    }
+}
 
-            let mut task = ctgen.create_task(&context_dir, table.as_deref(), profile_overrides).await?;
+/// Resolve planned outputs whose files were modified by hand since generation and would change. Returns the outputs to write
+async fn resolve_conflicts(
+    task: &CtGenTask<'_>,
+    outputs: Vec<CtGenTargetOutput>,
+    policy: ConflictPolicy,
+) -> Result<Vec<CtGenTargetOutput>> {
+    task.resolve_conflicts_with(outputs, |output| {
+        let resolution = match policy.resolution() {
+            Some(resolution) => resolution,
+            None => ask_conflict(output)?,
+        };
+
+        match resolution {
+            CtGenConflictPolicy::Keep => print_info(format!("Kept modified {}", style(output.file()).cyan())),
+            CtGenConflictPolicy::Markers => print_info(format!("Marked conflicts in {}", style(output.file()).yellow())),
+            CtGenConflictPolicy::Overwrite | CtGenConflictPolicy::Fail => {}
+        }
 
-            // set pre-defined prompt answer
-            if let Some(prompts) = prompt {
-                print_info("Overriding prompt responses");
-                let unanswered_prompts = task.prompts_unanswered(); // TODO clone not great
+        Ok(resolution)
+    })
+    .await
+}
 
-                for (answered_prompt_id, answered_prompt_answer) in prompts {
-                    if let Some(unanswered_prompt) = unanswered_prompts.iter().find(|p| {
-                        if let CtGenTaskPrompt::PromptGeneric { prompt_id, prompt_data: _ } = p {
-                            return prompt_id == &answered_prompt_id;
-                        }
-                        false
-                    }) {
-                        // TODO unless prompts_unanswered is a cloned set we wouldn't be able to call mutable method
-
-                        if answered_prompt_answer.contains(',') {
-                            task.set_prompt_answer(
-                                unanswered_prompt,
-                                Value::from(answered_prompt_answer.split(',').map(str::to_string).collect::<Vec<String>>()),
-                            )
-                            .await?;
-                        } else {
-                            task.set_prompt_answer(unanswered_prompt, Value::from(answered_prompt_answer))
-                                .await?;
-                        }
-                    }
+/// Ask how to resolve an output file modified by hand since generation. Viewing the diff asks again
+fn ask_conflict(output: &CtGenTargetOutput) -> Result<CtGenConflictPolicy> {
+    let prompt_text = format!("{} was modified since it was generated. What do you want to do?", output.file());
+    let items = ["Keep mine", "Take generated", "View diff", "Write conflict markers"].map(str::to_string);
+
+    loop {
+        let selection = if simple_prompts() {
+            ask_plain_select(&prompt_text, &items)?
+        } else {
+            Select::with_theme(cli_theme().prompts())
+                .with_prompt(&prompt_text)
+                .items(&items[..])
+                .default(0)
+                .report(true)
+                .interact()
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to render select prompt `{}`: {}", prompt_text, e)))?
+        };
+
+        match selection {
+            0 => return Ok(CtGenConflictPolicy::Keep),
+            1 => return Ok(CtGenConflictPolicy::Overwrite),
+            2 => {
+                if let Some(output_diff) = output.diff() {
+                    print_diff(&output_diff);
                 }
             }
+            _ => return Ok(CtGenConflictPolicy::Markers),
+        }
+    }
+}
 
-            // ask prompts to prepare context
-            loop {
-                let unanswered_prompts = task.prompts_unanswered(); // TODO clone not great
+/// Convert a textual prompt answer. Comma-separated values are split for generic prompts
+fn prompt_answer_value(prompt: &CtGenTaskPrompt, answer: &str) -> Value {
+    // enumerated prompts take their answers by item as a JSON object
+    if let CtGenTaskPrompt::PromptGeneric { prompt_id: _, prompt_data } = prompt {
+        if prompt_data.enumerate().is_some() {
+            if let Ok(answers @ Value::Object(_)) = serde_json::from_str::<Value>(answer) {
+                return answers;
+            }
+        }
+    }
 
-                if unanswered_prompts.is_empty() {
-                    break;
-                }
+    if answer.contains(',') && matches!(prompt, CtGenTaskPrompt::PromptGeneric { .. }) {
+        Value::from(answer.split(',').map(str::to_string).collect::<Vec<String>>())
+    } else {
+        Value::from(answer)
+    }
+}
 
-                print_info("Preparing prompts");
+/// Read the next prompt answer line from stdin
+fn read_stdin_answer(prompt: &CtGenTaskPrompt) -> Result<Value> {
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(CtGenError::RuntimeError(format!("No answer for prompt `{}`: end of input", prompt.prompt_id())).into());
+    }
 
-                for unanswered_prompt in unanswered_prompts {
-                    match unanswered_prompt.clone() {
-                        CtGenTaskPrompt::PromptDatabase => {
-                            let options = Value::from(task.reflection_adapter().list_database_names().await?);
+    Ok(prompt_answer_value(prompt, line.trim_end_matches(['\r', '\n'])))
+}
 
-                            let answer = ask_prompt("Enter database name:", Some(&options), false, false).await?;
+/// CLI theme, built from user defaults on start
+static CLI_THEME: OnceLock<CtGenCliTheme> = OnceLock::new();
 
-                            task.set_prompt_answer(&unanswered_prompt, answer).await?;
-                        }
-                        CtGenTaskPrompt::PromptTable => {
-                            let options = Value::from(task.reflection_adapter().list_table_names().await?);
+/// Get CLI theme
+fn cli_theme() -> &'static CtGenCliTheme {
+    CLI_THEME.get_or_init(|| CtGenCliTheme::new(&CtGenDefaults::default()))
+}
 
-                            let answer = ask_prompt("Enter table name:", Some(&options), false, false).await?;
+/// Plain-text prompt mode flag, set from user defaults or `--simple-prompts`
+static SIMPLE_PROMPTS: AtomicBool = AtomicBool::new(false);
 
-                            task.set_prompt_answer(&unanswered_prompt, answer).await?;
-                        }
-                        CtGenTaskPrompt::PromptGeneric { prompt_id: _, prompt_data } => {
-                            let rendered_prompt = task.render_prompt(&prompt_data)?;
-
-                            // TODO handle enumerations
-
-                            let mut answer = Value::from("");
-                            if rendered_prompt.should_ask() {
-                                answer = ask_prompt(
-                                    rendered_prompt.prompt(),
-                                    Some(rendered_prompt.options()),
-                                    rendered_prompt.multiple(),
-                                    rendered_prompt.ordered(),
-                                )
-                                .await?;
-                            }
+/// Check whether prompts should be asked as plain text
+fn simple_prompts() -> bool {
+    SIMPLE_PROMPTS.load(Ordering::Relaxed)
+}
 
-                            task.set_prompt_answer(&unanswered_prompt, answer).await?;
-                        }
-                    }
-                }
-            }
+/// Standard output mode flag, set by `--stdout` or targets printing to stdout. Progress output goes to stderr then
+static STDOUT_MODE: AtomicBool = AtomicBool::new(false);
+/// Editor command of editor prompts, from user defaults, `$VISUAL` or `$EDITOR`
+static EDITOR: OnceLock<Option<String>> = OnceLock::new();
 
-            //println!("{}", serde_json::to_string(&task.context())?);
+/// Get editor command, if any
+fn editor() -> Option<&'static str> {
+    EDITOR.get().and_then(Option::as_deref)
+}
 
-            // run
-            print_info("Running ctgen task");
-            Ok(task.run().await?)
-        }
-        Commands::Init { name, path } => {
-            let name = if let Some(name) = name {
-                name
-            } else {
-                //CONFIG_NAME_DEFAULT.to_string()
-                let default_name = if ctgen.get_profiles().contains_key(CONFIG_NAME_DEFAULT) {
-                    // there's already a default profile, so we better suggest something else, like for example the path, if it's alphanumeric, or the base directory name of the CWD
-                    if CtGen::get_name_regex().is_match(&path) {
-                        path.clone()
-                    } else {
-                        Path::new(&CtGen::get_current_working_dir()?)
-                            .file_name()
-                            .and_then(OsStr::to_str)
-                            .unwrap_or_default()
-                            .to_string()
-                    }
-                } else {
-                    CONFIG_NAME_DEFAULT.to_string()
-                };
+/// Resolve the target dir of a profile, or an explicit one, relative to the working directory
+async fn resolve_target_dir(ctgen: &mut CtGen, profile: Option<&str>, target_dir: Option<&str>) -> Result<String> {
+    let context_dir = CtGen::get_realpath(&CtGen::get_current_working_dir()?).await?;
 
-                loop {
-                    let answer = ask_prompt("Enter profile name:", Some(&Value::String(default_name.clone())), false, false).await;
+    let target_dir = if let Some(target_dir) = target_dir {
+        target_dir.to_string()
+    } else {
+        let profile_name = if let Some(p) = profile { p } else { CONFIG_NAME_DEFAULT };
 
-                    if answer.as_ref().is_ok_and(|v| v.as_str().is_some_and(|s| !s.is_empty())) {
-                        break answer.ok().and_then(|a| a.as_str().map(str::to_string)).unwrap_or_default();
-                    }
-                }
-            };
+        print_deprecations(ctgen, profile_name);
 
-            print_info(format!("Creating profile {}", style(&name).cyan()));
+        ctgen
+            .set_current_profile(profile_name)
+            .await?
+            .configuration()
+            .target_dir()
+            .to_string()
+    };
 
-            let _profile = ctgen.init_profile(&path, &name).await?;
+    Ok(CtGenTask::resolve_target_dir(&context_dir, &target_dir))
+}
 
-            print_info(format!("Created and registered profile {}", style(&name).cyan()));
+/// Check if a registered profile has targets printing to stdout. Profiles that fail to load have none, their error
+/// is reported when they are loaded for the run
+async fn has_stdout_targets(ctgen: &CtGen, profile_name: &str) -> bool {
+    let Ok((profile_name, _)) = ctgen.resolve_profile_name(profile_name) else {
+        return false;
+    };
 
-            Ok(())
+    match ctgen.get_profiles().get(&profile_name) {
+        Some(profile_file) => CtGenProfile::load(profile_file, &profile_name)
+            .await
+            .is_ok_and(|profile| profile.has_stdout_targets()),
+        None => false,
+    }
+}
+
+/// Print a progress line to stdout, or to stderr in standard output mode
+fn print_line(line: impl Display) {
+    if STDOUT_MODE.load(Ordering::Relaxed) {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+/// Print the deprecation notices of a profile and the aliases leading to it. Unknown profiles are reported when loaded
+fn print_deprecations(ctgen: &CtGen, profile_name: &str) {
+    if let Ok((_, notices)) = ctgen.resolve_profile_name(profile_name) {
+        for notice in notices {
+            print_info(notice);
         }
     }
 }
 
 /// Print info label
 fn print_info(label: impl Display) {
-    println!("{} {}", style("❯".to_string()).for_stderr().green(), label);
+    print_line(format!("{} {}", cli_theme().info(), label));
+}
+
+/// Print prompt group header
+fn print_group(group: &str) {
+    print_line(format!("\n{}", style(group).bold().underlined()));
+}
+
+/// Print the output files of the targets rendered with the current answers, and why the others render nothing
+fn print_preview(previews: &[CtGenTargetPreview]) {
+    print_info("Targets to render");
+
+    for preview in previews {
+        match preview.skip() {
+            Some(skip) => print_line(format!("  {}", style(format!("{} ({})", preview.name(), skip)).dim())),
+            None => print_line(format!("  {} {}", style(preview.name()).green(), preview.files().join(", "))),
+        }
+    }
 }
 
 /// Print fail label
 fn print_fail(label: impl Display) {
-    println!("{} {}", style("?".to_string()).for_stderr().yellow(), label);
+    print_line(format!("{} {}", cli_theme().fail(), label));
+}
+
+/// Print rendered target outputs to stdout, one after another
+fn print_outputs<'a>(outputs: impl Iterator<Item = &'a CtGenTargetOutput>) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+
+    for output in outputs {
+        stdout.write_all(output.bytes())?;
+    }
+
+    stdout.flush()?;
+
+    Ok(())
+}
+
+/// Print formatter output of written targets, then the outputs of `-` targets to stdout
+fn print_results(results: &[CtGenTargetResult]) -> Result<()> {
+    for result in results {
+        if let Some(formatter) = result.formatter() {
+            if !formatter.stdout().trim().is_empty() {
+                print_line(format!(
+                    "Target {} formatter output: {}",
+                    result.output().file(),
+                    formatter.stdout().trim()
+                ));
+            }
+        }
+    }
+
+    print_outputs(
+        results
+            .iter()
+            .filter(|result| result.status() == CtGenTargetStatus::Printed)
+            .map(CtGenTargetResult::output),
+    )
+}
+
+/// Print unified diff with added and removed lines highlighted
+fn print_diff(diff: &str) {
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            print_line(style(line).bold());
+        } else if line.starts_with('+') {
+            print_line(style(line).green());
+        } else if line.starts_with('-') {
+            print_line(style(line).red());
+        } else if line.starts_with("@@") {
+            print_line(style(line).cyan());
+        } else {
+            print_line(line);
+        }
+    }
 }
 
 /// List profiles
 async fn list_profiles(ctgen: &CtGen) {
-    if !ctgen.get_profiles().is_empty() {
+    let statuses = ctgen.check_profiles().await;
+
+    if !statuses.is_empty() {
         print_info("Installed profiles:");
 
-        let total = ctgen.get_profiles().len();
-        for (idx, (profile_name, profile_file)) in ctgen.get_profiles().iter().enumerate() {
+        let total = statuses.len();
+        for (idx, status) in statuses.iter().enumerate() {
             let idx_label = format!("[{}/{}]", (idx + 1), total);
 
-            let profile_name_label = if CtGenProfile::load(profile_file, profile_name).await.is_ok() {
-                if profile_name == CONFIG_NAME_DEFAULT {
-                    style(profile_name).cyan().bold()
+            let profile = CtGenProfile::load(status.file(), status.name()).await.ok();
+
+            let profile_name_label = if profile.is_some() {
+                if status.name() == CONFIG_NAME_DEFAULT {
+                    style(status.name()).cyan().bold()
                 } else {
-                    style(profile_name).cyan()
+                    style(status.name()).cyan()
                 }
             } else {
-                style(profile_name).red().blink()
+                style(status.name()).red().blink()
             };
 
+            let version_label = profile
+                .as_ref()
+                .and_then(|profile| profile.configuration().version())
+                .map(|version| format!(" {}", style(version).dim()))
+                .unwrap_or_default();
+
+            let alias_label = status
+                .registration()
+                .alias_of()
+                .map(|alias_of| format!(" -> {}", style(alias_of).cyan()))
+                .unwrap_or_default();
+
             println!(
-                "{}\t{}\t{}",
+                "{}\t{}{}{}\t{}",
                 style(idx_label).dim(),
                 profile_name_label,
-                style(profile_file).underlined()
+                alias_label,
+                version_label,
+                style(status.file()).underlined()
             );
+
+            for issue in status.issues() {
+                println!("\t{}", style(format!("! {}", issue)).yellow());
+            }
         }
     } else {
         print_fail("No profiles found.");
     }
 }
 
-/// Ask prompt
-async fn ask_prompt(prompt_text: &str, options: Option<&Value>, multiple: bool, ordered: bool) -> Result<Value> {
+/// Ask for a secret with masked input. The answer is never echoed
+fn ask_secret(prompt_text: &str) -> Result<Value> {
+    let input = Password::with_theme(cli_theme().prompts())
+        .with_prompt(prompt_text)
+        .allow_empty_password(true)
+        .report(false)
+        .interact()
+        .map_err(|e| CtGenError::RuntimeError(format!("Failed to render secret prompt `{}`: {}", prompt_text, e)))?;
+
+    Ok(Value::from(input))
+}
+
+/// Ask for a filesystem path, completing file and directory names with TAB
+fn ask_path(prompt_text: &str, default: Option<&str>, appearance: &CtGenPromptDefaults) -> Result<Value> {
+    let input: String = if simple_prompts() {
+        let input = match default {
+            Some(default) => read_plain_line(&format!("{} [{}]", prompt_text, default))?,
+            None => read_plain_line(prompt_text)?,
+        };
+
+        if input.is_empty() {
+            default.unwrap_or_default().to_string()
+        } else {
+            input
+        }
+    } else {
+        let completion = CtGenPathCompletion;
+        let mut input = Input::with_theme(cli_theme().prompts())
+            .with_prompt(prompt_text)
+            .completion_with(&completion)
+            .allow_empty(true)
+            .report(appearance.report());
+
+        if let Some(default) = default {
+            input = input.default(default.to_string());
+        }
+
+        input
+            .interact_text()
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to render path prompt `{}`: {}", prompt_text, e)))?
+    };
+
+    Ok(Value::from(input))
+}
+
+/// Completion of file and directory names of path prompts. Completes up to the longest common prefix of the matching
+/// names, directories end with a separator
+struct CtGenPathCompletion;
+
+impl Completion for CtGenPathCompletion {
+    fn get(&self, input: &str) -> Option<String> {
+        let (dir, prefix) = match input.rfind(std::path::is_separator) {
+            Some(index) => input.split_at(index + 1),
+            None => ("", input),
+        };
+
+        let read_dir = match dir {
+            "" => ".".to_string(),
+            dir => CtGen::expand_home(dir),
+        };
+
+        let names = std::fs::read_dir(read_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+
+                // hidden entries are only completed when asked for
+                (name.starts_with(prefix) && (!name.starts_with('.') || prefix.starts_with('.'))).then(|| {
+                    if is_dir {
+                        format!("{}{}", name, std::path::MAIN_SEPARATOR)
+                    } else {
+                        name
+                    }
+                })
+            })
+            .collect::<Vec<String>>();
+
+        let common = names.iter().skip(1).fold(names.first()?.clone(), |common, name| {
+            common
+                .chars()
+                .zip(name.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        });
+
+        (common.len() > prefix.len()).then(|| format!("{}{}", dir, common))
+    }
+}
+
+/// Ask prompt. Multiple choice prompts start with the `selected` answer values checked
+async fn ask_prompt(
+    prompt_text: &str,
+    options: Option<&Value>,
+    multiple: bool,
+    selected: &[String],
+    appearance: &CtGenPromptDefaults,
+) -> Result<Value> {
+    let ordered = appearance.ordered();
+    let wait_for_newline = appearance.confirm() == CtGenConfirmStyle::Newline;
+
     return if let Some(options) = options {
         if options.is_string() {
             //input with default suggestion
 
-            let input: String = Input::with_theme(&ColorfulTheme::default())
-                .with_prompt(prompt_text)
-                .default(options.as_str().map(str::to_string).unwrap_or_default())
-                .report(true)
-                .interact_text()
-                .map_err(|e| CtGenError::RuntimeError(format!("Failed to render input prompt `{}`: {}", prompt_text, e)))?;
+            let default = options.as_str().map(str::to_string).unwrap_or_default();
+
+            let input: String = if simple_prompts() {
+                let input = read_plain_line(&format!("{} [{}]", prompt_text, default))?;
+
+                if input.is_empty() {
+                    default
+                } else {
+                    input
+                }
+            } else {
+                Input::with_theme(cli_theme().prompts())
+                    .with_prompt(prompt_text)
+                    .default(default)
+                    .report(appearance.report())
+                    .interact_text()
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to render input prompt `{}`: {}", prompt_text, e)))?
+            };
 
             return Ok(Value::from(input));
         } else if !options.is_object() && !options.is_array() {
@@ -373,45 +2905,79 @@ async fn ask_prompt(prompt_text: &str, options: Option<&Value>, multiple: bool,
                     .collect::<Vec<String>>()
             };
 
-            print_info(format!("Note: Use {} before {}.", style("SPACE").cyan(), style("ENTER").cyan()));
-
-            let selections = MultiSelect::with_theme(&ColorfulTheme::default())
-                .with_prompt(prompt_text)
-                .items(&multiselected[..])
-                .max_length(20)
-                .report(true)
-                .interact()
-                .map_err(|e| CtGenError::RuntimeError(format!("Failed to render multi-select prompt `{}`: {}", prompt_text, e)))?;
-
-            let (multiselected, selections) = if ordered
-                && selections.len() > 1
-                && Confirm::with_theme(&ColorfulTheme::default())
-                    .with_prompt("Would you like to sort this selection?")
-                    .wait_for_newline(true)
-                    .report(true)
-                    .interact()
-                    .map_err(|e| {
-                        CtGenError::RuntimeError(format!("Failed to render reorder sub-prompt for prompt `{}`: {}", prompt_text, e))
-                    })? {
-                let subset = multiselected
-                    .iter()
-                    .enumerate()
-                    .filter(|(idx, _v)| selections.contains(idx))
-                    .map(|(_k, v)| v.clone())
-                    .collect::<Vec<String>>();
+            let (multiselected, selections) = if simple_prompts() {
+                // typed order doubles as sort order
+                let selections = ask_plain_multi_select(prompt_text, &multiselected, ordered)?;
 
+                (multiselected, selections)
+            } else {
                 print_info(format!("Note: Use {} before {}.", style("SPACE").cyan(), style("ENTER").cyan()));
 
-                let subset_sort = Sort::with_theme(&ColorfulTheme::default())
-                    .with_prompt("Sort the selected items:")
-                    .items(&subset[..])
+                // answers are option keys of object options, and the options themselves of array options
+                let checked = match options.as_object() {
+                    Some(options) => options.keys().map(|key| selected.contains(key)).collect::<Vec<bool>>(),
+                    None => multiselected.iter().map(|value| selected.contains(value)).collect::<Vec<bool>>(),
+                };
+
+                // longer lists start with an item checking every option at once
+                let all = multiselected.len() > 1;
+
+                let (items, checked) = if all {
+                    (
+                        [vec![PROMPT_SELECT_ALL.to_string()], multiselected.clone()].concat(),
+                        [vec![checked.iter().all(|checked| *checked)], checked].concat(),
+                    )
+                } else {
+                    (multiselected.clone(), checked)
+                };
+
+                let selections = MultiSelect::with_theme(cli_theme().prompts())
+                    .with_prompt(prompt_text)
+                    .items(&items[..])
+                    .defaults(&checked[..])
+                    .max_length(appearance.max_options())
+                    .report(appearance.report())
                     .interact()
-                    .map_err(|e| {
-                        CtGenError::RuntimeError(format!("Failed to render sort sub-prompt for prompt `{}`: {}", prompt_text, e))
-                    })?;
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to render multi-select prompt `{}`: {}", prompt_text, e)))?;
+
+                let selections = match all {
+                    true if selections.contains(&0) => (0..multiselected.len()).collect::<Vec<usize>>(),
+                    true => selections.into_iter().map(|selection| selection - 1).collect::<Vec<usize>>(),
+                    false => selections,
+                };
+
+                let (multiselected, selections) = if ordered
+                    && selections.len() > 1
+                    && Confirm::with_theme(cli_theme().prompts())
+                        .with_prompt("Would you like to sort this selection?")
+                        .wait_for_newline(wait_for_newline)
+                        .report(appearance.report())
+                        .interact()
+                        .map_err(|e| {
+                            CtGenError::RuntimeError(format!("Failed to render reorder sub-prompt for prompt `{}`: {}", prompt_text, e))
+                        })? {
+                    let subset = multiselected
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _v)| selections.contains(idx))
+                        .map(|(_k, v)| v.clone())
+                        .collect::<Vec<String>>();
+
+                    print_info(format!("Note: Use {} before {}.", style("SPACE").cyan(), style("ENTER").cyan()));
+
+                    let subset_sort = Sort::with_theme(cli_theme().prompts())
+                        .with_prompt("Sort the selected items:")
+                        .items(&subset[..])
+                        .interact()
+                        .map_err(|e| {
+                            CtGenError::RuntimeError(format!("Failed to render sort sub-prompt for prompt `{}`: {}", prompt_text, e))
+                        })?;
+
+                    (subset, subset_sort)
+                } else {
+                    (multiselected, selections)
+                };
 
-                (subset, subset_sort)
-            } else {
                 (multiselected, selections)
             };
 
@@ -447,26 +3013,6 @@ async fn ask_prompt(prompt_text: &str, options: Option<&Value>, multiple: bool,
 
                 Ok(Value::from(results))
             }
-        } else if options.is_object()
-            && options
-                .as_object()
-                .ok_or_else(|| CtGenError::RuntimeError("Failed to parse confirm options object".to_string()))?
-                .keys()
-                .all(|e| ["0", "1"].contains(&e.as_str()))
-        {
-            // confirm
-
-            if Confirm::with_theme(&ColorfulTheme::default())
-                .with_prompt(prompt_text)
-                .wait_for_newline(true)
-                .report(true)
-                .interact()
-                .map_err(|e| CtGenError::RuntimeError(format!("Failed to render confirm prompt `{}`: {}", prompt_text, e)))?
-            {
-                Ok(Value::from("1"))
-            } else {
-                Ok(Value::from("0"))
-            }
         } else {
             // select
 
@@ -486,13 +3032,17 @@ async fn ask_prompt(prompt_text: &str, options: Option<&Value>, multiple: bool,
                     .collect::<Vec<String>>()
             };
 
-            let selection = Select::with_theme(&ColorfulTheme::default())
-                .with_prompt(prompt_text)
-                .max_length(20)
-                .items(&selections[..])
-                .report(true)
-                .interact()
-                .map_err(|e| CtGenError::RuntimeError(format!("Failed to render select prompt `{}`: {}", prompt_text, e)))?;
+            let selection = if simple_prompts() {
+                ask_plain_select(prompt_text, &selections)?
+            } else {
+                Select::with_theme(cli_theme().prompts())
+                    .with_prompt(prompt_text)
+                    .max_length(appearance.max_options())
+                    .items(&selections[..])
+                    .report(appearance.report())
+                    .interact()
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to render select prompt `{}`: {}", prompt_text, e)))?
+            };
 
             if options.is_object() {
                 let value = selections
@@ -513,11 +3063,199 @@ async fn ask_prompt(prompt_text: &str, options: Option<&Value>, multiple: bool,
     } else {
         //input
 
-        let input: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt(prompt_text)
-            .interact_text()
-            .map_err(|e| CtGenError::RuntimeError(format!("Failed to render input prompt `{}`: {}", prompt_text, e)))?;
+        let input: String = if simple_prompts() {
+            read_plain_line(prompt_text)?
+        } else {
+            Input::with_theme(cli_theme().prompts())
+                .with_prompt(prompt_text)
+                .interact_text()
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to render input prompt `{}`: {}", prompt_text, e)))?
+        };
 
         Ok(Value::from(input))
     };
 }
+
+/// Ask for multi-line text in the editor, like `git commit`. The editor opens a temporary file with the initial text,
+/// and the answer is the saved file content without trailing whitespace
+fn ask_editor(prompt_text: &str, initial: &str) -> Result<Value> {
+    let editor = editor().ok_or_else(|| {
+        CtGenError::RuntimeError(format!(
+            "No editor for prompt `{}`. Set `editor` in {}, $VISUAL or $EDITOR",
+            prompt_text, DEFAULTS_FILE_NAME
+        ))
+    })?;
+
+    print_info(format!("{} {}", prompt_text, style("Waiting for the editor to close...").dim()));
+
+    // a fresh, unpredictable file, never one planted in its place
+    let file = std::env::temp_dir().join(format!(
+        "ctgen-prompt-{}.md",
+        Alphanumeric.sample_string(&mut rand::thread_rng(), 16)
+    ));
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&file)
+        .and_then(|mut editor_file| editor_file.write_all(initial.as_bytes()))
+        .map_err(|e| CtGenError::RuntimeError(format!("Failed to create editor file: {}", e)))?;
+
+    // the editor command runs through the shell like git editors, so it may come with arguments, like `code --wait`,
+    // and quoted paths with spaces
+    let status = if cfg!(target_os = "windows") {
+        std::process::Command::new("cmd")
+            .arg("/C")
+            .arg(format!("{} \"{}\"", editor, file.display()))
+            .status()
+    } else {
+        std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$@\"", editor))
+            .arg("sh")
+            .arg(&file)
+            .status()
+    };
+
+    let content = std::fs::read_to_string(&file);
+    let _ = std::fs::remove_file(&file);
+
+    let status = status.map_err(|e| CtGenError::RuntimeError(format!("Failed to open editor `{}`: {}", editor, e)))?;
+    if !status.success() {
+        return Err(CtGenError::RuntimeError(format!("Editor `{}` exited with {}", editor, status)).into());
+    }
+
+    let content = content.map_err(|e| CtGenError::RuntimeError(format!("Failed to read editor file: {}", e)))?;
+
+    Ok(Value::from(content.trim_end()))
+}
+
+/// Ask yes/no question of a confirm prompt, answered `1` or `0`
+fn ask_yes_no(prompt_text: &str, appearance: &CtGenPromptDefaults) -> Result<Value> {
+    let confirmed = if simple_prompts() {
+        ask_plain_confirm(prompt_text)?
+    } else {
+        Confirm::with_theme(cli_theme().prompts())
+            .with_prompt(prompt_text)
+            .wait_for_newline(appearance.confirm() == CtGenConfirmStyle::Newline)
+            .report(appearance.report())
+            .interact()
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to render confirm prompt `{}`: {}", prompt_text, e)))?
+    };
+
+    Ok(Value::from(if confirmed { "1" } else { "0" }))
+}
+
+/// Ask yes/no question
+fn ask_confirm(prompt_text: &str) -> Result<bool> {
+    if simple_prompts() {
+        return ask_plain_confirm(prompt_text);
+    }
+
+    Ok(Confirm::with_theme(cli_theme().prompts())
+        .with_prompt(prompt_text)
+        .wait_for_newline(true)
+        .report(true)
+        .interact()
+        .map_err(|e| CtGenError::RuntimeError(format!("Failed to render confirm prompt `{}`: {}", prompt_text, e)))?)
+}
+
+/// Read a trimmed line from stdin after printing a plain-text prompt
+fn read_plain_line(prompt_text: &str) -> Result<String> {
+    eprint!("{} ", prompt_text);
+    io::stderr().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(CtGenError::RuntimeError(format!("No answer for prompt `{}`: end of input", prompt_text)).into());
+    }
+
+    Ok(line.trim().to_string())
+}
+
+/// Print a numbered list of items for plain-text prompts
+fn print_plain_items(prompt_text: &str, items: &[String]) {
+    eprintln!("{}", prompt_text);
+    for (idx, item) in items.iter().enumerate() {
+        eprintln!("  {}) {}", idx + 1, item);
+    }
+}
+
+/// Parse a 1-based item number into an item index
+fn parse_plain_item(answer: &str, total: usize) -> Option<usize> {
+    answer
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|n| *n >= 1 && *n <= total)
+        .map(|n| n - 1)
+}
+
+/// Ask a plain-text yes/no question
+fn ask_plain_confirm(prompt_text: &str) -> Result<bool> {
+    loop {
+        match read_plain_line(&format!("{} [y/n]", prompt_text))?.to_lowercase().as_str() {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => print_fail("Please answer y or n."),
+        }
+    }
+}
+
+/// Ask a plain-text numbered single choice question. Returns the selected item index
+fn ask_plain_select(prompt_text: &str, items: &[String]) -> Result<usize> {
+    print_plain_items(prompt_text, items);
+
+    loop {
+        let answer = read_plain_line(&format!("Enter a number from 1 to {}:", items.len()))?;
+
+        match parse_plain_item(&answer, items.len()) {
+            Some(selection) => return Ok(selection),
+            None => print_fail(format!("Invalid choice `{}`.", answer)),
+        }
+    }
+}
+
+/// Ask a plain-text numbered multiple choice question. Returns the selected item indexes, in typed order if `ordered`
+fn ask_plain_multi_select(prompt_text: &str, items: &[String], ordered: bool) -> Result<Vec<usize>> {
+    print_plain_items(prompt_text, items);
+
+    let label = if ordered {
+        "Enter numbers separated by commas, in the order you want them, `all`, or leave empty for none:"
+    } else {
+        "Enter numbers separated by commas, `all`, or leave empty for none:"
+    };
+
+    loop {
+        let answer = read_plain_line(label)?;
+
+        if answer.trim().eq_ignore_ascii_case("all") {
+            return Ok((0..items.len()).collect());
+        }
+
+        let mut selections: Vec<usize> = Vec::new();
+        let mut invalid: Option<&str> = None;
+
+        for part in answer.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            match parse_plain_item(part, items.len()) {
+                Some(selection) if !selections.contains(&selection) => selections.push(selection),
+                Some(_) => {}
+                None => {
+                    invalid = Some(part);
+                    break;
+                }
+            }
+        }
+
+        if let Some(part) = invalid {
+            print_fail(format!("Invalid choice `{}`.", part));
+            continue;
+        }
+
+        if !ordered {
+            selections.sort_unstable();
+        }
+
+        return Ok(selections);
+    }
+}
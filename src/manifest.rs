@@ -0,0 +1,214 @@
+use crate::consts::MANIFEST_FILE_NAME;
+use crate::error::CtGenError;
+use crate::CtGen;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::btree_map::Iter;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Record of generated files, kept as `.ctgen-manifest.json` in the target dir
+pub struct CtGenManifest {
+    #[serde(skip)]
+    /// Manifest file path
+    file: String,
+    #[serde(default)]
+    /// Generated files by path relative to the target dir
+    files: BTreeMap<String, CtGenManifestEntry>,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Generated file record
+pub struct CtGenManifestEntry {
+    /// Profile name
+    profile: String,
+    /// Target name
+    target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Table the file was generated for
+    table: Option<String>,
+    /// SHA-256 hash of the file content after writing and formatting
+    hash: String,
+    /// Generation time, RFC3339
+    timestamp: String,
+}
+
+impl CtGenManifestEntry {
+    /// Profile name
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+    /// Target name
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+    /// Table the file was generated for
+    pub fn table(&self) -> Option<&str> {
+        self.table.as_deref()
+    }
+    /// SHA-256 hash of the file content after writing and formatting
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+    /// Generation time, RFC3339
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// State of a generated file compared to its manifest record
+pub enum CtGenFileStatus {
+    /// File content matches the recorded hash
+    Unchanged,
+    /// File was edited since generation
+    Modified,
+    /// File was removed since generation
+    Missing,
+}
+
+impl Display for CtGenFileStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenFileStatus::Unchanged => write!(f, "unchanged"),
+            CtGenFileStatus::Modified => write!(f, "modified"),
+            CtGenFileStatus::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+impl CtGenManifest {
+    /// Load manifest of a target dir. A missing manifest yields an empty one
+    pub async fn load(target_dir: &str) -> Result<Self> {
+        let file = CtGen::get_filepath(target_dir, MANIFEST_FILE_NAME);
+
+        if !CtGen::file_exists(&file).await {
+            return Ok(Self {
+                file,
+                ..Default::default()
+            });
+        }
+
+        let content = tokio::fs::read_to_string(&file)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to load manifest file {}: {}", file, e)))?;
+
+        let mut manifest: CtGenManifest = serde_json::from_str(&content)
+            .map_err(|e| CtGenError::ValidationError(format!("Failed to parse manifest file {}: {}", file, e)))?;
+        manifest.file = file;
+
+        Ok(manifest)
+    }
+
+    /// Save manifest. An empty manifest removes the manifest file
+    pub async fn save(&self) -> Result<()> {
+        if self.files.is_empty() {
+            if CtGen::file_exists(&self.file).await {
+                tokio::fs::remove_file(&self.file)
+                    .await
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to remove manifest file {}: {}", self.file, e)))?;
+            }
+
+            return Ok(());
+        }
+
+        tokio::fs::write(&self.file, serde_json::to_string_pretty(self)?)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to save manifest file {}: {}", self.file, e)))?;
+
+        Ok(())
+    }
+
+    /// Manifest file path
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Target dir of the manifest
+    pub fn target_dir(&self) -> &str {
+        self.file
+            .strip_suffix(MANIFEST_FILE_NAME)
+            .map(|dir| dir.trim_end_matches(std::path::MAIN_SEPARATOR))
+            .unwrap_or_default()
+    }
+
+    /// Generated files by path relative to the target dir
+    pub fn files(&self) -> Iter<'_, String, CtGenManifestEntry> {
+        self.files.iter()
+    }
+
+    /// Generated file record
+    pub fn entry(&self, file: &str) -> Option<&CtGenManifestEntry> {
+        self.files.get(file)
+    }
+
+    /// Check if nothing is recorded
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Record a generated file with its current content
+    pub fn record(&mut self, file: &str, profile: &str, target: &str, table: Option<&str>, content: &[u8], timestamp: &str) {
+        self.files.insert(
+            file.to_string(),
+            CtGenManifestEntry {
+                profile: profile.to_string(),
+                target: target.to_string(),
+                table: table.map(str::to_string),
+                hash: CtGenManifest::hash(content),
+                timestamp: timestamp.to_string(),
+            },
+        );
+    }
+
+    /// Files recorded for a profile that it no longer generates: their target was removed from the profile, or, when a render
+    /// plan for a table is given, they were generated for that table but aren't among the planned files anymore
+    pub fn stale(&self, profile: &str, targets: &[String], planned: Option<(&str, &[&str])>) -> Vec<String> {
+        self.files
+            .iter()
+            .filter(|(_, entry)| entry.profile == profile)
+            .filter(|(file, entry)| {
+                !targets.contains(&entry.target)
+                    || planned.is_some_and(|(table, planned)| entry.table.as_deref() == Some(table) && !planned.contains(&file.as_str()))
+            })
+            .map(|(file, _)| file.clone())
+            .collect()
+    }
+
+    /// Forget a generated file
+    pub fn remove(&mut self, file: &str) -> Option<CtGenManifestEntry> {
+        self.files.remove(file)
+    }
+
+    /// Compare a generated file on disk with its record
+    pub async fn status(&self, file: &str) -> Result<CtGenFileStatus> {
+        let entry = self
+            .files
+            .get(file)
+            .ok_or_else(|| CtGenError::ValidationError(format!("File {} is not in the manifest", file)))?;
+
+        let path = CtGen::get_filepath(self.target_dir(), file);
+
+        if !CtGen::file_exists(&path).await {
+            return Ok(CtGenFileStatus::Missing);
+        }
+
+        let content = tokio::fs::read(&path)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to read generated file {}: {}", path, e)))?;
+
+        if CtGenManifest::hash(&content) == entry.hash {
+            Ok(CtGenFileStatus::Unchanged)
+        } else {
+            Ok(CtGenFileStatus::Modified)
+        }
+    }
+
+    /// SHA-256 hash of a file content as lowercase hex
+    pub fn hash(content: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(content))
+    }
+}
@@ -0,0 +1,254 @@
+use crate::consts::{PACKS_DIR_NAME, PACK_DEFAULT_FILENAME, PACK_SCRIPTS_DIR_DEFAULT, PACK_TEMPLATES_DIR_DEFAULT};
+use crate::error::CtGenError;
+use crate::CtGen;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use walkdir::WalkDir;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Package of shared templates and rhai script helpers, used by profiles as partials and helpers
+pub struct CtGenPack {
+    /// The pack parameters
+    pack: CtGenPackConfig,
+
+    #[serde(skip)]
+    /// Canonical pack directory; The directory containing the pack manifest
+    dir: String,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct CtGenPackConfig {
+    /// Pack name, referenced by profile `uses`
+    name: String,
+    /// Pack version, like `1.2.0`
+    version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Short description of what the pack provides
+    description: Option<String>,
+    #[serde(default, rename = "templates-dir", skip_serializing_if = "Option::is_none")]
+    /// Templates dir relative to the pack dir. Defaults to `templates`
+    templates_dir: Option<String>,
+    #[serde(default, rename = "scripts-dir", skip_serializing_if = "Option::is_none")]
+    /// Scripts dir relative to the pack dir. Defaults to `scripts`
+    scripts_dir: Option<String>,
+}
+
+impl CtGenPack {
+    /// Load pack from a pack dir, or from its manifest file
+    pub fn load(path: &str) -> Result<Self> {
+        let (dir, file) = if path.ends_with(PACK_DEFAULT_FILENAME) {
+            (
+                Path::new(path)
+                    .parent()
+                    .map(|dir| dir.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                path.to_string(),
+            )
+        } else {
+            (path.to_string(), CtGen::get_filepath(path, PACK_DEFAULT_FILENAME))
+        };
+
+        let content = std::fs::read_to_string(&file)
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to load pack manifest {}: {}", file, e)))?;
+
+        let mut pack: CtGenPack =
+            toml::from_str(&content).map_err(|e| CtGenError::RuntimeError(format!("Failed to parse pack manifest {}: {}", file, e)))?;
+
+        if !CtGen::get_name_regex().is_match(pack.name()) {
+            return Err(CtGenError::ValidationError(format!("Invalid pack name `{}` in {}", pack.name(), file)).into());
+        }
+
+        if parse_version(pack.version()).is_none() {
+            return Err(CtGenError::ValidationError(format!(
+                "Invalid pack version `{}` in {}. Expected a version like \"1.2.0\"",
+                pack.version(),
+                file
+            ))
+            .into());
+        }
+
+        pack.dir = dir;
+
+        Ok(pack)
+    }
+
+    /// Pack name, referenced by profile `uses`
+    pub fn name(&self) -> &str {
+        &self.pack.name
+    }
+    /// Pack version, like `1.2.0`
+    pub fn version(&self) -> &str {
+        &self.pack.version
+    }
+    /// Short description of what the pack provides
+    pub fn description(&self) -> Option<&str> {
+        self.pack.description.as_deref()
+    }
+    /// Canonical pack directory
+    pub fn dir(&self) -> &str {
+        &self.dir
+    }
+
+    /// Canonical templates directory
+    pub fn templates_dir(&self) -> String {
+        CtGen::get_filepath(&self.dir, self.pack.templates_dir.as_deref().unwrap_or(PACK_TEMPLATES_DIR_DEFAULT))
+    }
+
+    /// Canonical scripts directory
+    pub fn scripts_dir(&self) -> String {
+        CtGen::get_filepath(&self.dir, self.pack.scripts_dir.as_deref().unwrap_or(PACK_SCRIPTS_DIR_DEFAULT))
+    }
+
+    /// Check if the pack satisfies a `uses` reference, like `acme-helpers`, `acme-helpers@1` or `acme-helpers@1.2`.
+    /// Versions match by their leading components
+    pub fn satisfies(&self, reference: &str) -> bool {
+        let (name, version) = reference.split_once('@').unwrap_or((reference, ""));
+
+        if name.trim() != self.name() {
+            return false;
+        }
+
+        if version.trim().is_empty() {
+            return true;
+        }
+
+        match (parse_version(version), parse_version(self.version())) {
+            (Some(required), Some(current)) => current.starts_with(&required),
+            _ => false,
+        }
+    }
+}
+
+impl Display for CtGenPack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.name(), self.version())
+    }
+}
+
+/// Version components, like `[1, 2, 0]` for `1.2.0`
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect()
+}
+
+/// Directory packs are installed into
+pub fn packs_dir() -> Result<String> {
+    Ok(CtGen::get_filepath(&CtGen::get_config_dir()?, PACKS_DIR_NAME))
+}
+
+/// Load every installed pack, sorted by name and version. Malformed packs are skipped, with a warning each, so one broken
+/// pack doesn't take the others down
+pub fn installed() -> Result<(Vec<CtGenPack>, Vec<String>)> {
+    let packs_dir = packs_dir()?;
+
+    let mut packs = Vec::new();
+    let mut warnings = Vec::new();
+
+    if let Ok(entries) = std::fs::read_dir(&packs_dir) {
+        for entry in entries.filter_map(|e| e.ok()).filter(|e| e.path().is_dir()) {
+            match CtGenPack::load(&entry.path().to_string_lossy()) {
+                Ok(pack) => packs.push(pack),
+                Err(e) => warnings.push(format!("Skipped malformed pack {}: {}", entry.path().display(), e)),
+            }
+        }
+    }
+
+    packs.sort_by(|a, b| {
+        a.name()
+            .cmp(b.name())
+            .then_with(|| parse_version(a.version()).cmp(&parse_version(b.version())))
+    });
+
+    Ok((packs, warnings))
+}
+
+/// Resolve profile `uses` references to installed packs, picking the newest matching version of each
+pub fn resolve(uses: &[String]) -> Result<Vec<CtGenPack>> {
+    if uses.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (installed, warnings) = installed()?;
+
+    uses.iter()
+        .map(|reference| {
+            installed
+                .iter()
+                .rev()
+                .find(|pack| pack.satisfies(reference))
+                .cloned()
+                .ok_or_else(|| {
+                    // a malformed copy of the pack would otherwise pass for a missing one
+                    let mut message = format!("Pack `{}` is not installed. Install it with `ctgen pack add <path>`", reference);
+
+                    for warning in warnings.iter() {
+                        message.push_str(&format!("\n{}", warning));
+                    }
+
+                    CtGenError::ValidationError(message).into()
+                })
+        })
+        .collect()
+}
+
+/// Copy a pack manifest, templates and scripts into the packs dir, replacing a previously installed copy of the same
+/// version. Hidden files are skipped
+pub async fn install(pack: &CtGenPack) -> Result<CtGenPack> {
+    let pack_dir = CtGen::get_filepath(&packs_dir()?, &pack.to_string());
+
+    // already installed from this very dir
+    if CtGen::normalize_path(pack.dir()) == CtGen::normalize_path(&pack_dir) {
+        return Ok(pack.clone());
+    }
+
+    if CtGen::file_exists(&pack_dir).await {
+        tokio::fs::remove_dir_all(&pack_dir)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to remove previously installed pack {}: {}", pack_dir, e)))?;
+    }
+
+    let mut files = vec![(
+        CtGen::get_filepath(pack.dir(), PACK_DEFAULT_FILENAME),
+        PACK_DEFAULT_FILENAME.to_string(),
+    )];
+
+    for dir in [pack.templates_dir(), pack.scripts_dir()] {
+        if !CtGen::is_path_within(pack.dir(), &dir) {
+            return Err(CtGenError::ValidationError(format!("Pack dir {} is outside of the pack {}", dir, pack.dir())).into());
+        }
+
+        for entry in WalkDir::new(&dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            if let Ok(relative) = entry.path().strip_prefix(pack.dir()) {
+                files.push((entry.path().to_string_lossy().to_string(), relative.to_string_lossy().to_string()));
+            }
+        }
+    }
+
+    for (source, relative) in files {
+        let file = CtGen::get_filepath(&pack_dir, &relative);
+
+        if let Some(parent) = Path::new(&file).parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to create directory {}: {}", parent.display(), e)))?;
+        }
+
+        tokio::fs::copy(&source, &file)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to copy pack file {}: {}", source, e)))?;
+    }
+
+    CtGenPack::load(&pack_dir)
+}
@@ -1,10 +1,28 @@
+pub mod editor;
+pub mod package;
+pub mod schema;
+pub mod starter;
+pub mod when;
+
+use crate::consts::{
+    CONFIRM_LABEL_NO, CONFIRM_LABEL_YES, CTGEN_VERSION, EXEC_TIMEOUT_DEFAULT, FILE_EXT_HBS, PLATFORMS, PROMPT_MAX_OPTIONS_DEFAULT,
+    PROMPT_REFERENCE_PATTERN, TABLE_REFERENCE_PATTERN, TARGET_STDOUT,
+};
 use crate::error::CtGenError;
+use crate::lint::{CtGenLintIssue, CtGenLintIssueKind};
+use crate::pack::{self, CtGenPack};
+use crate::task::{frontmatter, renderer};
 use crate::CtGen;
 use anyhow::Result;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::path::Path;
 use std::slice::Iter;
+use std::sync::LazyLock;
 use toml::map::Map;
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
@@ -18,6 +36,27 @@ pub struct CtGenProfile {
     prompt: HashMap<String, CtGenPrompt>,
     /// List of profile targets
     target: HashMap<String, CtGenTarget>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    /// List of additional named database connections
+    connection: HashMap<String, CtGenConnection>,
+    #[serde(default, skip_serializing_if = "CtGenHelpersConfig::is_empty")]
+    /// Bundled helpers configuration
+    helpers: CtGenHelpersConfig,
+    #[serde(default, skip_serializing_if = "CtGenHooksConfig::is_empty")]
+    /// Run-level shell command hooks
+    hooks: CtGenHooksConfig,
+    #[serde(default, rename = "prompt-defaults", skip_serializing_if = "CtGenPromptDefaults::is_empty")]
+    /// Prompt appearance defaults applied to all prompts unless overridden
+    prompt_defaults: CtGenPromptDefaults,
+    #[serde(default, skip_serializing_if = "CtGenOutputConfig::is_empty")]
+    /// Output file rules
+    output: CtGenOutputConfig,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    /// Output language presets by language name
+    language: HashMap<String, CtGenLanguage>,
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    /// Profile variables, available to prompts, targets and templates under `vars`. String values are templates
+    vars: toml::Table,
 
     #[serde(skip)]
     /// Canonical context dir
@@ -29,9 +68,24 @@ impl CtGenProfile {
     pub async fn load(file: &str, name: &str) -> Result<Self> {
         match tokio::fs::read_to_string(file).await {
             Ok(c) => {
+                // check the required version before parsing, so profiles using newer config fail with a clear message
+                if let Some(requires_ctgen) = c
+                    .parse::<toml::Table>()
+                    .ok()
+                    .and_then(|config| config.get("profile")?.get("requires-ctgen")?.as_str().map(str::to_string))
+                {
+                    CtGenProfile::check_requires_ctgen(&requires_ctgen)?;
+                }
+
+                schema::validate(file, &c)?;
+
                 let mut profile: CtGenProfile =
                     toml::from_str(&c).map_err(|e| CtGenError::RuntimeError(format!("Failed to parse profile config: {}", e)))?;
 
+                if let Ok(config) = c.parse::<toml::Table>() {
+                    profile.apply_prompt_defaults(&config);
+                }
+
                 if !name.is_empty() {
                     profile.set_name(name);
                 } else {
@@ -47,36 +101,108 @@ impl CtGenProfile {
 
                 profile.set_context_dir(context_dir);
 
+                if profile.configuration().auto_targets() {
+                    profile.add_auto_targets().await;
+                }
+
                 Ok(profile)
             }
             Err(e) => Err(CtGenError::RuntimeError(format!("Failed to load profile config: {}", e)).into()),
         }
     }
 
-    pub fn new(path: &str, name: &str) -> CtGenProfile {
-        let mut options_table = Map::new();
-        options_table.insert("1".to_string(), toml::Value::String("Yes".to_string()));
-        options_table.insert("0".to_string(), toml::Value::String("No".to_string()));
+    /// Apply `[prompt-defaults]` flags to the prompts that leave them out of their own table in the raw profile config
+    fn apply_prompt_defaults(&mut self, config: &toml::Table) {
+        let ordered = self.prompt_defaults.ordered();
+
+        for (prompt_id, prompt) in self.prompt.iter_mut() {
+            let declared = config
+                .get("prompt")
+                .and_then(|prompts| prompts.get(prompt_id))
+                .and_then(toml::Value::as_table);
+
+            if !declared.is_some_and(|declared| declared.contains_key("ordered")) {
+                prompt.ordered = ordered;
+            }
+        }
+    }
+
+    /// Check that this ctgen binary is at least the required version
+    fn check_requires_ctgen(requires_ctgen: &str) -> Result<()> {
+        let parse = |version: &str| -> Option<Vec<u64>> {
+            version
+                .trim()
+                .trim_start_matches(">=")
+                .trim_start()
+                .trim_start_matches('v')
+                .split(['-', '+'])
+                .next()?
+                .split('.')
+                .map(|part| part.parse::<u64>().ok())
+                .collect()
+        };
 
+        let required = parse(requires_ctgen).ok_or_else(|| {
+            CtGenError::ValidationError(format!(
+                "Invalid requires-ctgen version `{}`. Expected a version like \"0.2\" or \"0.2.1\"",
+                requires_ctgen
+            ))
+        })?;
+        let current = parse(CTGEN_VERSION).unwrap_or_default();
+
+        // compare component-wise, missing components count as zero
+        let len = required.len().max(current.len());
+        let pad = |mut version: Vec<u64>| {
+            version.resize(len, 0);
+            version
+        };
+
+        if pad(current) < pad(required) {
+            return Err(CtGenError::ValidationError(format!(
+                "Profile requires ctgen {} or newer, but this is ctgen {}. Update ctgen to use this profile",
+                requires_ctgen, CTGEN_VERSION
+            ))
+            .into());
+        }
+
+        Ok(())
+    }
+
+    pub fn new(path: &str, name: &str) -> CtGenProfile {
         let dummy_prompt = CtGenPrompt {
             condition: None,
             enumerate: None,
             prompt: "Would you like to render the dummy target?".to_string(),
-            options: toml::Value::Table(options_table),
+            default: None,
+            transform: None,
+            options: CtGenPrompt::default_options(),
             multiple: false,
             ordered: false,
             required: false,
+            pattern: None,
+            min: None,
+            max: None,
+            report: None,
+            max_options: None,
+            confirm: None,
+            platforms: Vec::new(),
+            when: None,
+            secret: false,
+            kind: CtGenPromptType::Confirm,
+            must_exist: false,
+            create_if_missing: false,
+            source: None,
+            filter: CtGenPromptFilter::default(),
+            order: None,
+            group: None,
+            labels: CtGenConfirmLabels::default(),
         };
 
         let mut prompts = HashMap::new();
         prompts.insert("dummy".to_string(), dummy_prompt);
 
-        let dummy_target = CtGenTarget {
-            condition: Some("{{#if (eq prompts/dummy \"1\")}}1{{/if}}".to_string()),
-            template: "dummy".to_string(),
-            target: "dummy.md".to_string(),
-            formatter: None,
-        };
+        let mut dummy_target = CtGenTarget::new("dummy", "dummy.md");
+        dummy_target.set_condition(Some("{{#if prompts/dummy}}1{{/if}}"));
 
         let mut targets = HashMap::new();
         targets.insert("dummy".to_string(), dummy_target);
@@ -93,46 +219,307 @@ impl CtGenProfile {
                 scripts_dir: "assets/scripts".to_string(),
                 prompts: vec!["dummy".to_string()],
                 targets: vec!["dummy".to_string()],
+                reflect_include: Vec::new(),
+                reflect_exclude: Vec::new(),
+                parallelism: None,
+                strict: false,
+                allow_external_target_dir: false,
+                generation_log: String::new(),
+                auto_targets: false,
+                command_dir: CtGenCommandDir::default(),
+                languages: Vec::new(),
+                reflect_mode: CtGenReflectMode::default(),
+                version: None,
+                requires_ctgen: None,
+                features: Vec::new(),
+                uses: Vec::new(),
             },
             prompt: prompts,
             target: targets,
+            connection: HashMap::new(),
+            helpers: CtGenHelpersConfig::default(),
+            hooks: CtGenHooksConfig::default(),
+            prompt_defaults: CtGenPromptDefaults::default(),
+            output: CtGenOutputConfig::default(),
+            language: HashMap::new(),
+            vars: toml::Table::new(),
             context_dir: path.to_string(),
         }
     }
 
-    /// Check declared paths validity
+    /// Check declared paths validity. Fails with all error findings at once
     pub async fn validate(&self) -> Result<()> {
+        let errors = self
+            .findings()
+            .await
+            .into_iter()
+            .filter(CtGenLintIssue::is_error)
+            .map(|finding| finding.message().to_string())
+            .collect::<Vec<String>>();
+
+        if !errors.is_empty() {
+            return Err(CtGenError::ValidationError(errors.join(" ")).into());
+        }
+
+        Ok(())
+    }
+
+    /// Check declared paths validity and collect all findings
+    pub async fn findings(&self) -> Vec<CtGenLintIssue> {
+        let mut findings = Vec::new();
+
         // validate templates dir existence and read permissions
         let canonical_templates_dir = self.templates_dir();
 
         if !CtGen::file_exists(&canonical_templates_dir).await {
-            return Err(CtGenError::ValidationError("Invalid templates-dir specified.".to_string()).into());
+            findings.push(CtGenLintIssue::new(
+                CtGenLintIssueKind::MissingDirectory,
+                "profile.templates-dir",
+                "Invalid templates-dir specified.".to_string(),
+            ));
         }
 
         // validate scripts dir existence and read permissions
         let canonical_scripts_dir = self.scripts_dir();
 
         if !CtGen::file_exists(&canonical_scripts_dir).await {
-            return Err(CtGenError::ValidationError("Invalid scripts-dir specified.".to_string()).into());
+            findings.push(CtGenLintIssue::new(
+                CtGenLintIssueKind::MissingDirectory,
+                "profile.scripts-dir",
+                "Invalid scripts-dir specified.".to_string(),
+            ));
+        }
+
+        // validate prompts declaration and answer patterns
+        for prompt_name in self.prompts() {
+            match self.prompt(prompt_name) {
+                Some(prompt) => {
+                    if let Some(Err(e)) = prompt.pattern().map(Regex::new) {
+                        findings.push(CtGenLintIssue::new(
+                            CtGenLintIssueKind::InvalidConfig,
+                            &format!("prompt.{}.pattern", prompt_name),
+                            format!("Invalid pattern for prompt {}: {}", prompt_name, e),
+                        ));
+                    }
+
+                    if let Some(platform) = prompt.platforms().iter().find(|platform| !PLATFORMS.contains(&platform.as_str())) {
+                        findings.push(CtGenLintIssue::new(
+                            CtGenLintIssueKind::InvalidConfig,
+                            &format!("prompt.{}.platforms", prompt_name),
+                            format!(
+                                "Invalid platform `{}` for prompt {}. Expected one of {}.",
+                                platform,
+                                prompt_name,
+                                PLATFORMS.join(", ")
+                            ),
+                        ));
+                    }
+
+                    if let Some(Err(e)) = prompt.when().map(when::validate) {
+                        findings.push(CtGenLintIssue::new(
+                            CtGenLintIssueKind::InvalidConfig,
+                            &format!("prompt.{}.when", prompt_name),
+                            e,
+                        ));
+                    }
+                }
+                None => findings.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::MissingPrompt,
+                    &format!("prompt.{}", prompt_name),
+                    format!(
+                        "Invalid prompt `{}`. Make sure all included prompts are actually declared.",
+                        prompt_name
+                    ),
+                )),
+            }
+        }
+
+        // validate prompt dependencies
+        if let Err(e) = self.ordered_prompts() {
+            findings.push(CtGenLintIssue::new(
+                CtGenLintIssueKind::InvalidConfig,
+                "profile.prompts",
+                e.to_string(),
+            ));
+        }
+
+        // validate output exclude patterns
+        for pattern in self.output.exclude() {
+            if let Err(e) = CtGenOutputConfig::glob(pattern) {
+                findings.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::InvalidConfig,
+                    "output.exclude",
+                    e.to_string(),
+                ));
+            }
+        }
+
+        // validate used packs are installed
+        if let Err(e) = self.packs() {
+            findings.push(CtGenLintIssue::new(
+                CtGenLintIssueKind::InvalidConfig,
+                "profile.uses",
+                e.to_string(),
+            ));
         }
 
         // validate targets template existence
         for target_name in self.targets() {
-            let target = self.target(target_name).ok_or_else(|| {
-                CtGenError::ValidationError(format!(
-                    "Invalid target `{}`. Make sure all included targets are actually declared.",
-                    target_name
-                ))
-            })?;
+            let Some(target) = self.target(target_name) else {
+                findings.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::MissingTarget,
+                    &format!("target.{}", target_name),
+                    format!(
+                        "Invalid target `{}`. Make sure all included targets are actually declared.",
+                        target_name
+                    ),
+                ));
+
+                continue;
+            };
+
+            if target.is_copy() {
+                match self.target_source_file(target) {
+                    Some(source_canonical_path) => {
+                        if !CtGen::file_exists(&source_canonical_path).await {
+                            findings.push(CtGenLintIssue::new(
+                                CtGenLintIssueKind::MissingTemplate,
+                                &format!("target.{}.source", target_name),
+                                format!("Source file not found for copy target {}.", target_name),
+                            ));
+                        }
+                    }
+                    None => findings.push(CtGenLintIssue::new(
+                        CtGenLintIssueKind::InvalidConfig,
+                        &format!("target.{}.source", target_name),
+                        format!("Missing source for copy target {}.", target_name),
+                    )),
+                }
+            } else if !self.target_template_exists(target).await {
+                findings.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::MissingTemplate,
+                    &format!("target.{}.template", target_name),
+                    format!("Template file not found for target {}.", target_name),
+                ));
+            }
+
+            if let Some(platform) = target.platforms().iter().find(|platform| !PLATFORMS.contains(&platform.as_str())) {
+                findings.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::InvalidConfig,
+                    &format!("target.{}.platforms", target_name),
+                    format!(
+                        "Invalid platform `{}` for target {}. Expected one of {}.",
+                        platform,
+                        target_name,
+                        PLATFORMS.join(", ")
+                    ),
+                ));
+            }
+
+            if let Some(Err(e)) = target.when().map(when::validate) {
+                findings.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::InvalidConfig,
+                    &format!("target.{}.when", target_name),
+                    e,
+                ));
+            }
+
+            for language in target.languages() {
+                if !self.profile.languages.contains(language) {
+                    findings.push(CtGenLintIssue::new(
+                        CtGenLintIssueKind::InvalidConfig,
+                        &format!("target.{}.languages", target_name),
+                        format!(
+                            "Invalid language `{}` for target {}. Make sure it is listed in the profile languages.",
+                            language, target_name
+                        ),
+                    ));
+                }
+            }
 
-            let template_canonical_path = CtGen::get_filepath(&canonical_templates_dir, &format!("{}.hbs", target.template()));
+            if target.delimiters.is_some() && target.delimiters().is_none() {
+                findings.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::InvalidConfig,
+                    &format!("target.{}.delimiters", target_name),
+                    format!(
+                        "Invalid delimiters for target {}. Expected a pair of non-empty strings.",
+                        target_name
+                    ),
+                ));
+            }
 
-            if !CtGen::file_exists(&template_canonical_path).await {
-                return Err(CtGenError::ValidationError(format!("Template file not found for target {}.", target_name)).into());
+            if target.permissions.is_some() && target.permissions().is_none() {
+                findings.push(CtGenLintIssue::new(
+                    CtGenLintIssueKind::InvalidConfig,
+                    &format!("target.{}.permissions", target_name),
+                    format!(
+                        "Invalid permissions for target {}. Expected an octal mode like \"0644\".",
+                        target_name
+                    ),
+                ));
             }
         }
 
-        Ok(())
+        findings
+    }
+
+    /// Disable targets whose template file is missing and collect a warning for each of them
+    pub async fn skip_missing_targets(&mut self) -> Vec<CtGenLintIssue> {
+        let mut skipped = Vec::new();
+        let mut skipped_targets = Vec::new();
+        for target_name in self.targets() {
+            if let Some(target) = self.target(target_name) {
+                let exists = if target.is_copy() {
+                    CtGen::file_exists(&self.target_source_file(target).unwrap_or_default()).await
+                } else {
+                    self.target_template_exists(target).await
+                };
+
+                if !exists {
+                    skipped.push(CtGenLintIssue::new(
+                        CtGenLintIssueKind::SkippedTarget,
+                        &format!("target.{}.template", target_name),
+                        format!("Template file not found for target {}. Target skipped.", target_name),
+                    ));
+                    skipped_targets.push(target_name.to_string());
+                }
+            }
+        }
+
+        self.profile.targets.retain(|target_name| !skipped_targets.contains(target_name));
+
+        skipped
+    }
+
+    /// Add a target for every template whose front matter declares a default output path, named after the template.
+    /// Declared targets take precedence. Templates with invalid front matter are left to lint, and templates whose name
+    /// isn't a valid target id, like language variants in subdirectories, are skipped
+    async fn add_auto_targets(&mut self) {
+        let mut template_files = renderer::template_files(&self.templates_dir());
+        template_files.sort();
+
+        for (template_name, template_path) in template_files {
+            if self.target.contains_key(&template_name) || editor::CtGenProfileEditor::validate_id(&template_name).is_err() {
+                continue;
+            }
+
+            let meta = if let Ok(content) = tokio::fs::read_to_string(&template_path).await {
+                frontmatter::parse(&content).ok().flatten()
+            } else {
+                None
+            };
+
+            if let Some(output_path) = meta.as_ref().and_then(|meta| meta.target()) {
+                let mut target = CtGenTarget::new(&template_name, output_path);
+                target.set_condition(meta.as_ref().and_then(|meta| meta.condition()));
+
+                self.target.insert(template_name.clone(), target);
+
+                if !self.profile.targets.contains(&template_name) {
+                    self.profile.targets.push(template_name);
+                }
+            }
+        }
     }
 
     /// Set profile given name
@@ -177,6 +564,11 @@ impl CtGenProfile {
         }
     }
 
+    /// Canonical asset file path of a copy target, if the target has a source
+    pub fn target_source_file(&self, target: &CtGenTarget) -> Option<String> {
+        target.source().map(|source| CtGen::get_filepath(self.context_dir(), source))
+    }
+
     /// Profile config
     pub fn configuration(&self) -> &CtGenProfileConfig {
         &self.profile
@@ -187,6 +579,122 @@ impl CtGenProfile {
         self.profile.prompts.iter()
     }
 
+    /// Profile prompts in asking order. Prompts are sorted by `order`, keeping the `prompts` list order of prompts with
+    /// the same order, then grouped prompts are moved up to the first prompt of their group. Prompts are asked after the
+    /// prompts they depend on, dependency cycles fail
+    pub fn ordered_prompts(&self) -> Result<Vec<&String>> {
+        let mut prompts = self.prompts().collect::<Vec<&String>>();
+
+        // sort_by_key is stable
+        prompts.sort_by_key(|prompt_name| self.prompt(prompt_name).and_then(|prompt| prompt.order()).unwrap_or_default());
+
+        let group = |prompt_name: &str| self.prompt(prompt_name).and_then(|prompt| prompt.group());
+
+        let mut ordered: Vec<&String> = Vec::with_capacity(prompts.len());
+
+        for &prompt_name in prompts.iter() {
+            if ordered.contains(&prompt_name) {
+                continue;
+            }
+
+            match group(prompt_name) {
+                Some(prompt_group) => ordered.extend(prompts.iter().filter(|&&other| group(other) == Some(prompt_group)).copied()),
+                None => ordered.push(prompt_name),
+            }
+        }
+
+        // the first prompt whose dependencies are asked goes next, keeping the order of the rest
+        let mut pending = ordered;
+        let mut ordered: Vec<&String> = Vec::with_capacity(pending.len());
+
+        while !pending.is_empty() {
+            match pending.iter().position(|prompt_name| {
+                self.prompt_dependencies(prompt_name)
+                    .iter()
+                    .all(|dependency| ordered.contains(dependency))
+            }) {
+                Some(index) => ordered.push(pending.remove(index)),
+                None => {
+                    return Err(CtGenError::ValidationError(format!(
+                        "Prompt dependency cycle: {}",
+                        self.prompt_cycle(&pending)
+                            .iter()
+                            .map(|prompt_name| prompt_name.as_str())
+                            .collect::<Vec<&str>>()
+                            .join(" -> ")
+                    ))
+                    .into())
+                }
+            }
+        }
+
+        Ok(ordered)
+    }
+
+    /// Profile prompts referenced as `prompts/<id>` by the templates of a prompt, which are asked before it
+    pub fn prompt_dependencies(&self, prompt_name: &str) -> Vec<&String> {
+        static RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(PROMPT_REFERENCE_PATTERN)
+                .unwrap_or_else(|_| panic!("Failed to compile prompt reference regex pattern: {}", PROMPT_REFERENCE_PATTERN))
+        });
+
+        let Some(prompt) = self.prompt(prompt_name) else {
+            return Vec::new();
+        };
+
+        let references = prompt
+            .templates()
+            .flat_map(|template| {
+                RE.captures_iter(template)
+                    .filter_map(|captures| captures.get(1))
+                    .map(|reference| reference.as_str())
+            })
+            .collect::<HashSet<&str>>();
+
+        self.prompts().filter(|other| references.contains(other.as_str())).collect()
+    }
+
+    /// Whether a prompt depends on the task table, as a `columns` source prompt or with templates referencing `table/`
+    pub fn prompt_depends_on_table(&self, prompt_name: &str) -> bool {
+        static RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(TABLE_REFERENCE_PATTERN)
+                .unwrap_or_else(|_| panic!("Failed to compile table reference regex pattern: {}", TABLE_REFERENCE_PATTERN))
+        });
+
+        let Some(prompt) = self.prompt(prompt_name) else {
+            return false;
+        };
+
+        prompt.source() == Some(CtGenPromptSource::Columns) || prompt.templates().any(|template| RE.is_match(template))
+    }
+
+    /// Dependency cycle of prompts that wait for each other, from a prompt back to itself
+    fn prompt_cycle<'a>(&'a self, pending: &[&'a String]) -> Vec<&'a String> {
+        let mut path: Vec<&String> = Vec::new();
+        let mut current = pending[0];
+
+        loop {
+            if let Some(start) = path.iter().position(|&prompt_name| prompt_name == current) {
+                let mut cycle = path.split_off(start);
+                cycle.push(current);
+
+                return cycle;
+            }
+
+            path.push(current);
+
+            // every pending prompt depends on another pending prompt
+            match self
+                .prompt_dependencies(current)
+                .into_iter()
+                .find(|dependency| pending.contains(dependency))
+            {
+                Some(dependency) => current = dependency,
+                None => return path,
+            }
+        }
+    }
+
     /// Profile prompt by name
     pub fn prompt(&self, prompt: &str) -> Option<&CtGenPrompt> {
         self.prompt.get(prompt)
@@ -201,6 +709,83 @@ impl CtGenProfile {
     pub fn target(&self, target: &str) -> Option<&CtGenTarget> {
         self.target.get(target)
     }
+
+    /// Check if any profile target prints to standard output instead of writing a file
+    pub fn has_stdout_targets(&self) -> bool {
+        self.targets().filter_map(|target| self.target(target)).any(CtGenTarget::is_stdout)
+    }
+
+    /// Additional named database connections
+    pub fn connections(&self) -> std::collections::hash_map::Iter<'_, String, CtGenConnection> {
+        self.connection.iter()
+    }
+
+    /// Additional database connection by name
+    pub fn connection(&self, connection: &str) -> Option<&CtGenConnection> {
+        self.connection.get(connection)
+    }
+
+    /// Bundled helpers configuration
+    pub fn helpers(&self) -> &CtGenHelpersConfig {
+        &self.helpers
+    }
+
+    /// Run-level shell command hooks
+    pub fn hooks(&self) -> &CtGenHooksConfig {
+        &self.hooks
+    }
+
+    /// Prompt appearance defaults applied to all prompts unless overridden
+    pub fn prompt_defaults(&self) -> &CtGenPromptDefaults {
+        &self.prompt_defaults
+    }
+
+    /// Output file rules
+    pub fn output(&self) -> &CtGenOutputConfig {
+        &self.output
+    }
+
+    /// Get output language preset by language name
+    pub fn language(&self, name: &str) -> Option<&CtGenLanguage> {
+        self.language.get(name)
+    }
+
+    /// Profile variables in declaration order. String values are templates
+    pub fn vars(&self) -> &toml::Table {
+        &self.vars
+    }
+
+    /// Installed packs resolved from the profile `uses`, in declaration order
+    pub fn packs(&self) -> Result<Vec<CtGenPack>> {
+        pack::resolve(self.configuration().uses())
+    }
+
+    /// Canonical template file paths a target may render: the template and its variants in the subdirectory of every profile
+    /// language, like `rust/model.hbs` for template `model`
+    pub fn target_template_files(&self, target: &CtGenTarget) -> Vec<String> {
+        let templates_dir = self.templates_dir();
+
+        std::iter::once(target.template().to_string())
+            .chain(
+                self.profile
+                    .languages
+                    .iter()
+                    .map(|language| format!("{}/{}", language, target.template())),
+            )
+            .map(|template| CtGen::get_filepath(&templates_dir, &format!("{}{}", template, FILE_EXT_HBS)))
+            .collect()
+    }
+
+    /// Check if a template target has a template file, or a variant of it for at least one profile language
+    async fn target_template_exists(&self, target: &CtGenTarget) -> bool {
+        for template_file in self.target_template_files(target) {
+            if CtGen::file_exists(&template_file).await {
+                return true;
+            }
+        }
+
+        false
+    }
 }
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
@@ -228,6 +813,48 @@ pub struct CtGenProfileConfig {
     prompts: Vec<String>,
     /// List of target ids to use
     targets: Vec<String>,
+    #[serde(default, rename = "reflect-include", skip_serializing_if = "Vec::is_empty")]
+    /// Table name patterns to reflect. All tables are reflected if empty
+    reflect_include: Vec<String>,
+    #[serde(default, rename = "reflect-exclude", skip_serializing_if = "Vec::is_empty")]
+    /// Table name patterns to skip during reflection
+    reflect_exclude: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Max number of concurrent reflections, target renders and formatters. Defaults to available cores
+    parallelism: Option<usize>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    /// Fail rendering on missing context variables instead of rendering empty strings
+    strict: bool,
+    #[serde(default, rename = "allow-external-target-dir", skip_serializing_if = "std::ops::Not::not")]
+    /// Allow the target dir to resolve outside of the context dir
+    allow_external_target_dir: bool,
+    #[serde(default, rename = "generation-log", skip_serializing_if = "String::is_empty")]
+    /// Generation log file relative to CWD, appended after successful runs. `.json` logs hold JSON, others markdown
+    generation_log: String,
+    #[serde(default, rename = "auto-targets", skip_serializing_if = "std::ops::Not::not")]
+    /// Derive targets from templates declaring a default output path in their front matter
+    auto_targets: bool,
+    #[serde(default, rename = "command-dir", skip_serializing_if = "CtGenCommandDir::is_default")]
+    /// Working directory of formatters and hooks
+    command_dir: CtGenCommandDir,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Output languages to choose from with the built-in `language` prompt
+    languages: Vec<String>,
+    #[serde(default, rename = "reflect-mode", skip_serializing_if = "CtGenReflectMode::is_default")]
+    /// How much of the database is reflected into the task context
+    reflect_mode: CtGenReflectMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Profile version, for keeping track of profile releases
+    version: Option<String>,
+    #[serde(default, rename = "requires-ctgen", skip_serializing_if = "Option::is_none")]
+    /// Minimum ctgen version the profile works with, like `0.2` or `0.2.1`
+    requires_ctgen: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Feature flags enabled by default, available to templates under `features`
+    features: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Installed packs whose templates and scripts are available as partials and helpers, like `acme-helpers@1`
+    uses: Vec<String>,
 }
 
 impl CtGenProfileConfig {
@@ -267,6 +894,132 @@ impl CtGenProfileConfig {
     pub fn targets(&self) -> &Vec<String> {
         &self.targets
     }
+    /// Table name patterns to reflect. All tables are reflected if empty
+    pub fn reflect_include(&self) -> &Vec<String> {
+        &self.reflect_include
+    }
+    /// Table name patterns to skip during reflection
+    pub fn reflect_exclude(&self) -> &Vec<String> {
+        &self.reflect_exclude
+    }
+    /// Max number of concurrent reflections, target renders and formatters
+    pub fn parallelism(&self) -> Option<usize> {
+        self.parallelism
+    }
+    /// Fail rendering on missing context variables
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+    /// Allow the target dir to resolve outside of the context dir
+    pub fn allow_external_target_dir(&self) -> bool {
+        self.allow_external_target_dir
+    }
+    /// Generation log file relative to CWD, appended after successful runs
+    pub fn generation_log(&self) -> &str {
+        &self.generation_log
+    }
+    /// Derive targets from templates declaring a default output path in their front matter
+    pub fn auto_targets(&self) -> bool {
+        self.auto_targets
+    }
+    /// Working directory of formatters and hooks
+    pub fn command_dir(&self) -> CtGenCommandDir {
+        self.command_dir
+    }
+    /// Output languages to choose from with the built-in `language` prompt
+    pub fn languages(&self) -> &Vec<String> {
+        &self.languages
+    }
+    /// How much of the database is reflected into the task context
+    pub fn reflect_mode(&self) -> CtGenReflectMode {
+        self.reflect_mode
+    }
+    /// Profile version, for keeping track of profile releases
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+    /// Minimum ctgen version the profile works with
+    pub fn requires_ctgen(&self) -> Option<&str> {
+        self.requires_ctgen.as_deref()
+    }
+    /// Feature flags enabled by default, available to templates under `features`
+    pub fn features(&self) -> &Vec<String> {
+        &self.features
+    }
+    /// Installed packs whose templates and scripts are available as partials and helpers, like `acme-helpers@1`
+    pub fn uses(&self) -> &Vec<String> {
+        &self.uses
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Working directory formatters and hooks run in
+pub enum CtGenCommandDir {
+    #[default]
+    /// The directory ctgen was started in
+    Inherit,
+    /// The target dir
+    Target,
+    /// The context dir of the run, which the target dir is relative to
+    Context,
+    /// The profile directory, containing `Ctgen.toml`
+    Profile,
+}
+
+impl CtGenCommandDir {
+    /// Check if this is the default working directory
+    pub fn is_default(&self) -> bool {
+        *self == CtGenCommandDir::default()
+    }
+
+    /// Pick the working directory among the run's target, context and profile dirs. `None` inherits the process one
+    pub fn resolve<'a>(&self, target_dir: &'a str, context_dir: &'a str, profile_dir: &'a str) -> Option<&'a str> {
+        match self {
+            CtGenCommandDir::Inherit => None,
+            CtGenCommandDir::Target => Some(target_dir),
+            CtGenCommandDir::Context => Some(context_dir),
+            CtGenCommandDir::Profile => Some(profile_dir),
+        }
+    }
+}
+
+impl Display for CtGenCommandDir {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenCommandDir::Inherit => write!(f, "inherit"),
+            CtGenCommandDir::Target => write!(f, "target"),
+            CtGenCommandDir::Context => write!(f, "context"),
+            CtGenCommandDir::Profile => write!(f, "profile"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// How much of the database is reflected into the task context
+pub enum CtGenReflectMode {
+    #[default]
+    /// Every table, with all constraints between them
+    Full,
+    /// The task table only, fetched on demand. Keeps memory and time bounded for schemas with thousands of tables
+    Table,
+}
+
+impl CtGenReflectMode {
+    /// Check if this is the default reflect mode
+    pub fn is_default(&self) -> bool {
+        *self == CtGenReflectMode::default()
+    }
+}
+
+impl Display for CtGenReflectMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenReflectMode::Full => write!(f, "full"),
+            CtGenReflectMode::Table => write!(f, "table"),
+        }
+    }
 }
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
@@ -279,16 +1032,53 @@ pub struct CtGenProfileConfigOverrides {
     dsn: Option<String>,
     /// Override default target dir
     target_dir: Option<String>,
+    /// Override table name patterns to reflect
+    reflect_include: Option<Vec<String>>,
+    /// Override table name patterns to skip during reflection
+    reflect_exclude: Option<Vec<String>>,
+    /// Override max number of concurrent jobs
+    jobs: Option<usize>,
+    /// Freeze the context timestamp for byte-identical output
+    deterministic: bool,
+    /// Enable strict rendering regardless of the profile setting
+    strict: bool,
+    /// Keep files written before a failed write or formatter, instead of restoring their previous contents
+    no_rollback: bool,
+    /// Allow the target dir to resolve outside of the context dir regardless of the profile setting
+    allow_external_target_dir: bool,
+    /// Feature flags to enable, or to disable when prefixed with `!`, on top of the profile features
+    features: Vec<String>,
 }
 
 impl CtGenProfileConfigOverrides {
     /// Create a new set of override parameters
-    pub fn new(env_file: Option<String>, env_var: Option<String>, dsn: Option<String>, target_dir: Option<String>) -> Self {
+    pub fn new(
+        env_file: Option<String>,
+        env_var: Option<String>,
+        dsn: Option<String>,
+        target_dir: Option<String>,
+        reflect_include: Option<Vec<String>>,
+        reflect_exclude: Option<Vec<String>>,
+        jobs: Option<usize>,
+        deterministic: bool,
+        strict: bool,
+        no_rollback: bool,
+        allow_external_target_dir: bool,
+        features: Vec<String>,
+    ) -> Self {
         Self {
             env_file,
             env_var,
             dsn,
             target_dir,
+            reflect_include,
+            reflect_exclude,
+            jobs,
+            deterministic,
+            strict,
+            no_rollback,
+            allow_external_target_dir,
+            features,
         }
     }
     /// Override default env file name
@@ -307,6 +1097,38 @@ impl CtGenProfileConfigOverrides {
     pub fn target_dir(&self) -> Option<&str> {
         self.target_dir.as_deref()
     }
+    /// Override table name patterns to reflect
+    pub fn reflect_include(&self) -> Option<&Vec<String>> {
+        self.reflect_include.as_ref()
+    }
+    /// Override table name patterns to skip during reflection
+    pub fn reflect_exclude(&self) -> Option<&Vec<String>> {
+        self.reflect_exclude.as_ref()
+    }
+    /// Override max number of concurrent jobs
+    pub fn jobs(&self) -> Option<usize> {
+        self.jobs
+    }
+    /// Freeze the context timestamp for byte-identical output
+    pub fn deterministic(&self) -> bool {
+        self.deterministic
+    }
+    /// Enable strict rendering regardless of the profile setting
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+    /// Keep files written before a failed write or formatter, instead of restoring their previous contents
+    pub fn no_rollback(&self) -> bool {
+        self.no_rollback
+    }
+    /// Allow the target dir to resolve outside of the context dir regardless of the profile setting
+    pub fn allow_external_target_dir(&self) -> bool {
+        self.allow_external_target_dir
+    }
+    /// Feature flags to enable, or to disable when prefixed with `!`, on top of the profile features
+    pub fn features(&self) -> &Vec<String> {
+        &self.features
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -314,10 +1136,20 @@ impl CtGenProfileConfigOverrides {
 pub struct CtGenPrompt {
     /// Handlebars template that receives the up-to-date context. Must render to "1" to proceed
     condition: Option<String>,
-    /// Handlebars template that receives the up-to-date context. Produces comma-separated list. Enumerate prompt for each of the following values
+    /// Handlebars template that receives the up-to-date context. Produces a JSON array or comma-separated list. The prompt is asked once per item, available as `item`
     enumerate: Option<String>,
     /// Handlebars template that receives the up-to-date context. Renders the actual text for the prompt
     prompt: String,
+    #[serde(
+        default,
+        deserialize_with = "CtGenPrompt::deserialize_default",
+        skip_serializing_if = "Option::is_none"
+    )]
+    /// Handlebars template that receives the up-to-date context. Renders the answer used when the prompt is skipped by its condition or answered empty. Multiple choice prompts take an array of selected values too
+    default: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Handlebars template that receives the up-to-date context and the raw answer as `answer`. Renders the stored answer
+    transform: Option<String>,
     #[serde(default = "CtGenPrompt::default_options")]
     /// List of options, either handlebars template that produces comma-separated list, or toml array/map
     options: toml::Value,
@@ -325,14 +1157,111 @@ pub struct CtGenPrompt {
     /// Flag that controls whether we allow a single answer or multiple choice
     multiple: bool,
     #[serde(default = "CtGenPrompt::default_ordered")]
-    /// Flag that controls whether we care about the order of multiple valued prompts
+    /// Flag that controls whether we care about the order of multiple valued prompts. Prompts leaving it out take it from `[prompt-defaults]`
     ordered: bool,
     #[serde(default = "CtGenPrompt::default_required")]
     /// Flag that controls whether empty answers are allowed
     required: bool,
+    /// Regex pattern every non-empty answer value must match
+    pattern: Option<String>,
+    /// Minimum numeric value. When set, every non-empty answer value must be a number
+    min: Option<f64>,
+    /// Maximum numeric value. When set, every non-empty answer value must be a number
+    max: Option<f64>,
+    /// Flag that controls whether the answer is echoed once the prompt is answered. Falls back to `[prompt-defaults]`
+    report: Option<bool>,
+    #[serde(rename = "max-options")]
+    /// Max number of options visible at once in select prompts. Falls back to `[prompt-defaults]`
+    max_options: Option<usize>,
+    /// How confirm prompts take their answer. Falls back to `[prompt-defaults]`
+    confirm: Option<CtGenConfirmStyle>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Platforms the prompt is asked on, like `["linux", "macos"]` or `["unix"]`. Asked on every platform if empty
+    platforms: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Expression over platforms and feature flags, like `unix && !feature(legacy)`, that must hold for the prompt to be
+    /// asked. Evaluated before anything is rendered
+    when: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    /// Flag that masks the input and keeps the answer out of context dumps and saved answer files
+    secret: bool,
+    #[serde(default, rename = "type", alias = "kind", skip_serializing_if = "CtGenPromptType::is_default")]
+    /// Type of the answer in the task context
+    kind: CtGenPromptType,
+    #[serde(default, rename = "must-exist", skip_serializing_if = "std::ops::Not::not")]
+    /// Flag that requires `path` answers to exist, relative paths resolved against the context dir
+    must_exist: bool,
+    #[serde(default, rename = "create-if-missing", skip_serializing_if = "std::ops::Not::not")]
+    /// Flag that creates missing directories of `path` answers once answered
+    create_if_missing: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Built-in options of the prompt, picked from the reflected database instead of `options`
+    source: Option<CtGenPromptSource>,
+    #[serde(default, skip_serializing_if = "CtGenPromptFilter::is_empty")]
+    /// Filter of the built-in options of `source` prompts
+    filter: CtGenPromptFilter,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Asking order, lower first. Prompts with the same order, 0 by default, keep their `prompts` list order
+    order: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Group name, printed as a header. Prompts of a group are asked together, where the first of them is asked
+    group: Option<String>,
+    #[serde(default, skip_serializing_if = "CtGenConfirmLabels::is_empty")]
+    /// Option labels of `confirm` prompts
+    labels: CtGenConfirmLabels,
 }
 
 impl CtGenPrompt {
+    /// Create a new prompt with default options and flags
+    pub fn new(prompt: &str) -> Self {
+        Self {
+            condition: None,
+            enumerate: None,
+            prompt: prompt.to_string(),
+            default: None,
+            transform: None,
+            options: CtGenPrompt::default_options(),
+            multiple: CtGenPrompt::default_multiple(),
+            ordered: CtGenPrompt::default_ordered(),
+            required: CtGenPrompt::default_required(),
+            pattern: None,
+            min: None,
+            max: None,
+            report: None,
+            max_options: None,
+            confirm: None,
+            platforms: Vec::new(),
+            when: None,
+            secret: false,
+            kind: CtGenPromptType::default(),
+            must_exist: false,
+            create_if_missing: false,
+            source: None,
+            filter: CtGenPromptFilter::default(),
+            order: None,
+            group: None,
+            labels: CtGenConfirmLabels::default(),
+        }
+    }
+
+    /// Read a default template, or an array of selected values of multiple choice prompts as the comma-separated list the
+    /// template would render
+    fn deserialize_default<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum CtGenPromptDefault {
+            Template(String),
+            Selected(Vec<String>),
+        }
+
+        Ok(
+            Option::<CtGenPromptDefault>::deserialize(deserializer)?.map(|default| match default {
+                CtGenPromptDefault::Template(template) => template,
+                CtGenPromptDefault::Selected(selected) => selected.join(","),
+            }),
+        )
+    }
+
     /// Default options value
     pub fn default_options() -> toml::Value {
         toml::Value::Boolean(false)
@@ -354,7 +1283,7 @@ impl CtGenPrompt {
     pub fn condition(&self) -> Option<&str> {
         self.condition.as_deref()
     }
-    /// Prompt enumerator template. If it doesn't evaluate to a comma-separated list, prompt will be skipped
+    /// Prompt enumerator template. The prompt is asked once per item it evaluates to, answers are kept by item
     pub fn enumerate(&self) -> Option<&str> {
         self.enumerate.as_deref()
     }
@@ -362,14 +1291,40 @@ impl CtGenPrompt {
     pub fn prompt(&self) -> &str {
         &self.prompt
     }
+    /// Default answer template. Renders the answer of prompts skipped by their condition or answered empty
+    pub fn default(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+    /// Answer transform template. Renders the stored answer from the raw answer, value by value
+    pub fn transform(&self) -> Option<&str> {
+        self.transform.as_deref()
+    }
     /// Either a template that outputs a comma-separated list, or a toml map/array
     pub fn options(&self) -> &toml::Value {
         &self.options
     }
+    /// Inline templates of the prompt, which may reference other prompts and the context
+    fn templates(&self) -> impl Iterator<Item = &str> {
+        [
+            self.condition(),
+            self.enumerate(),
+            Some(self.prompt()),
+            self.default(),
+            self.transform(),
+            self.options().as_str(),
+        ]
+        .into_iter()
+        .flatten()
+    }
     /// Flag that controls the number of answers allowed
     pub fn multiple(&self) -> bool {
         self.multiple
     }
+    /// Whether the answer is a list of values, for `multiple` prompts and `columns` source prompts, which are always
+    /// multi-selects
+    pub fn is_multiple_choice(&self) -> bool {
+        self.multiple || self.source == Some(CtGenPromptSource::Columns)
+    }
     /// Flag that controls whether we care about the order of multiple valued prompts
     pub fn ordered(&self) -> bool {
         self.ordered
@@ -378,35 +1333,1133 @@ impl CtGenPrompt {
     pub fn required(&self) -> bool {
         self.required
     }
-}
+    /// Flag that masks the input and keeps the answer out of context dumps and saved answer files
+    pub fn secret(&self) -> bool {
+        self.secret
+    }
+    /// Type of the answer in the task context
+    pub fn kind(&self) -> CtGenPromptType {
+        self.kind
+    }
+    /// Flag that requires `path` answers to exist
+    pub fn must_exist(&self) -> bool {
+        self.must_exist
+    }
+    /// Flag that creates missing directories of `path` answers once answered
+    pub fn create_if_missing(&self) -> bool {
+        self.create_if_missing
+    }
+    /// Built-in options of the prompt, picked from the reflected database instead of `options`
+    pub fn source(&self) -> Option<CtGenPromptSource> {
+        self.source
+    }
+    /// Filter of the built-in options of `source` prompts
+    pub fn filter(&self) -> &CtGenPromptFilter {
+        &self.filter
+    }
+    /// Regex pattern every non-empty answer value must match
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+    /// Minimum numeric value of the answer
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+    /// Maximum numeric value of the answer
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
+    /// Flag that controls whether the answer is echoed, unless left to `[prompt-defaults]`
+    pub fn report(&self) -> Option<bool> {
+        self.report
+    }
+    /// Max number of visible select options, unless left to `[prompt-defaults]`
+    pub fn max_options(&self) -> Option<usize> {
+        self.max_options
+    }
+    /// How confirm prompts take their answer, unless left to `[prompt-defaults]`
+    pub fn confirm(&self) -> Option<CtGenConfirmStyle> {
+        self.confirm
+    }
+    /// Platforms the prompt is asked on. Asked on every platform if empty
+    pub fn platforms(&self) -> &Vec<String> {
+        &self.platforms
+    }
+    /// Expression over platforms and feature flags that must hold for the prompt to be asked
+    pub fn when(&self) -> Option<&str> {
+        self.when.as_deref()
+    }
+    /// Check if the prompt is asked on the platform ctgen runs on with the enabled feature flags
+    pub fn asked_on_current_platform(&self, features: &BTreeMap<String, bool>) -> Result<bool> {
+        Ok(CtGen::is_current_platform(&self.platforms) && self.when.as_deref().map_or(Ok(true), |when| when::evaluate(when, features))?)
+    }
+    /// Asking order, lower first
+    pub fn order(&self) -> Option<i64> {
+        self.order
+    }
+    /// Group name, printed as a header
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    /// Option labels of `confirm` prompts
+    pub fn labels(&self) -> &CtGenConfirmLabels {
+        &self.labels
+    }
 
-#[derive(Clone, Default, Debug, Serialize, Deserialize)]
-pub struct CtGenTarget {
-    /// Handlebars template that receives the up-to-date context. Must render to "1" to proceed
-    condition: Option<String>,
-    /// Template name. Relative to templates dir, no file extension.
-    template: String,
-    /// Handlebars template that receives the up-to-date context. Output file path relative to target dir.
-    target: String,
-    /// Handlebars template that receives the up-to-date context. Renders an optional shell command to execute after target rendering is completed
-    formatter: Option<String>,
-}
+    /// Set prompt condition template
+    pub fn set_condition(&mut self, condition: Option<&str>) -> &mut Self {
+        self.condition = condition.map(str::to_string);
 
-impl CtGenTarget {
-    /// Handlebars template that receives the up-to-date context. Must render to "1" to proceed
-    pub fn condition(&self) -> Option<&str> {
-        self.condition.as_deref()
+        self
     }
-    /// Template name. Relative to templates dir, no file extension.
-    pub fn template(&self) -> &str {
-        &self.template
+    /// Set prompt enumerator template
+    pub fn set_enumerate(&mut self, enumerate: Option<&str>) -> &mut Self {
+        self.enumerate = enumerate.map(str::to_string);
+
+        self
     }
-    /// Handlebars template that receives the up-to-date context. Output file path relative to target dir.
-    pub fn target(&self) -> &str {
-        &self.target
+    /// Set default answer template
+    pub fn set_default(&mut self, default: Option<&str>) -> &mut Self {
+        self.default = default.map(str::to_string);
+
+        self
     }
-    /// Handlebars template that receives the up-to-date context. Renders an optional shell command to execute after target rendering is completed
-    pub fn formatter(&self) -> Option<&str> {
-        self.formatter.as_deref()
+    /// Set prompt options, either a template that outputs a comma-separated list, or a toml map/array
+    pub fn set_options(&mut self, options: toml::Value) -> &mut Self {
+        self.options = options;
+
+        self
+    }
+    /// Set multiple answers flag
+    pub fn set_multiple(&mut self, multiple: bool) -> &mut Self {
+        self.multiple = multiple;
+
+        self
+    }
+    /// Set ordered answers flag
+    pub fn set_ordered(&mut self, ordered: bool) -> &mut Self {
+        self.ordered = ordered;
+
+        self
+    }
+    /// Set required answer flag
+    pub fn set_required(&mut self, required: bool) -> &mut Self {
+        self.required = required;
+
+        self
+    }
+    /// Set secret flag
+    pub fn set_secret(&mut self, secret: bool) -> &mut Self {
+        self.secret = secret;
+
+        self
+    }
+    /// Set answer type
+    pub fn set_kind(&mut self, kind: CtGenPromptType) -> &mut Self {
+        self.kind = kind;
+
+        self
+    }
+    /// Set built-in options source
+    pub fn set_source(&mut self, source: Option<CtGenPromptSource>) -> &mut Self {
+        self.source = source;
+
+        self
+    }
+    /// Set answer regex pattern
+    pub fn set_pattern(&mut self, pattern: Option<&str>) -> &mut Self {
+        self.pattern = pattern.map(str::to_string);
+
+        self
+    }
+    /// Set numeric answer bounds
+    pub fn set_range(&mut self, min: Option<f64>, max: Option<f64>) -> &mut Self {
+        self.min = min;
+        self.max = max;
+
+        self
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Type of prompt answers in the task context
+pub enum CtGenPromptType {
+    #[default]
+    /// Text, as answered
+    String,
+    /// JSON number, integer or float
+    Number,
+    /// JSON integer
+    Integer,
+    /// JSON boolean. `1`, `true`, `yes`, `y` and `on` are true, `0`, `false`, `no`, `n` and `off` are false
+    Boolean,
+    /// Filesystem path, with a leading `~` expanded to the home directory. Asked with filesystem completion
+    Path,
+    /// Yes or no question, answered `1` or `0`, with an optional third option that skips it. JSON boolean, or `null`
+    /// when skipped
+    Confirm,
+    /// Multi-line text, written in the editor like a `git commit` message. Text, as answered
+    Editor,
+}
+
+impl CtGenPromptType {
+    /// Check if this is the default type
+    pub fn is_default(&self) -> bool {
+        *self == CtGenPromptType::default()
+    }
+
+    /// Check if answers of this type are kept as text
+    pub fn is_text(&self) -> bool {
+        matches!(self, CtGenPromptType::String | CtGenPromptType::Editor)
+    }
+
+    /// Parse a non-empty answer value into this type
+    pub fn parse(&self, value: &str) -> Option<Value> {
+        let value = value.trim();
+
+        match self {
+            CtGenPromptType::String | CtGenPromptType::Editor => Some(Value::from(value)),
+            CtGenPromptType::Number => value
+                .parse::<i64>()
+                .map(Value::from)
+                .ok()
+                .or_else(|| value.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(Value::Number)),
+            CtGenPromptType::Integer => value.parse::<i64>().map(Value::from).ok(),
+            CtGenPromptType::Boolean | CtGenPromptType::Confirm => match value.to_lowercase().as_str() {
+                "1" | "true" | "yes" | "y" | "on" => Some(Value::Bool(true)),
+                "0" | "false" | "no" | "n" | "off" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            CtGenPromptType::Path => Some(Value::from(CtGen::expand_home(value))),
+        }
+    }
+
+    /// Convert an answer into this type. Multiple choice and enumerated answers are converted value by value, empty
+    /// values become `null` and values that don't parse are kept as they are
+    pub fn convert(&self, answer: &Value) -> Value {
+        match answer {
+            _ if self.is_text() => answer.clone(),
+            Value::String(value) if value.trim().is_empty() => Value::Null,
+            Value::String(value) => self.parse(value).unwrap_or_else(|| answer.clone()),
+            Value::Array(values) => Value::Array(values.iter().map(|value| self.convert(value)).collect()),
+            Value::Object(answers) => Value::Object(answers.iter().map(|(item, answer)| (item.clone(), self.convert(answer))).collect()),
+            _ => answer.clone(),
+        }
+    }
+}
+
+impl Display for CtGenPromptType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenPromptType::String => write!(f, "string"),
+            CtGenPromptType::Number => write!(f, "number"),
+            CtGenPromptType::Integer => write!(f, "integer"),
+            CtGenPromptType::Boolean => write!(f, "boolean"),
+            CtGenPromptType::Path => write!(f, "path"),
+            CtGenPromptType::Confirm => write!(f, "confirm"),
+            CtGenPromptType::Editor => write!(f, "editor"),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Option labels of `confirm` prompts
+pub struct CtGenConfirmLabels {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Label of the option answered `1`. Defaults to `Yes`
+    yes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Label of the option answered `0`. Defaults to `No`
+    no: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Label of a third option that skips the prompt, answered empty. Only offered when set
+    skip: Option<String>,
+}
+
+impl CtGenConfirmLabels {
+    /// Check if no label is customized, so the prompt is a plain yes/no question
+    pub fn is_empty(&self) -> bool {
+        self.yes.is_none() && self.no.is_none() && self.skip.is_none()
+    }
+
+    /// Label of the option answered `1`
+    pub fn yes(&self) -> &str {
+        self.yes.as_deref().unwrap_or(CONFIRM_LABEL_YES)
+    }
+    /// Label of the option answered `0`
+    pub fn no(&self) -> &str {
+        self.no.as_deref().unwrap_or(CONFIRM_LABEL_NO)
+    }
+    /// Label of the option that skips the prompt
+    pub fn skip(&self) -> Option<&str> {
+        self.skip.as_deref()
+    }
+
+    /// Options of the prompt by answer, in yes, no, skip order
+    pub fn options(&self) -> Value {
+        let mut options = serde_json::Map::new();
+        options.insert("1".to_string(), Value::from(self.yes()));
+        options.insert("0".to_string(), Value::from(self.no()));
+
+        if let Some(skip) = self.skip() {
+            options.insert(String::new(), Value::from(skip));
+        }
+
+        Value::Object(options)
+    }
+}
+
+impl Display for CtGenConfirmLabels {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} / {}", self.yes(), self.no())?;
+
+        if let Some(skip) = self.skip() {
+            write!(f, " / {}", skip)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Built-in options of a prompt, picked from the reflected database
+pub enum CtGenPromptSource {
+    /// Multi-select of the task table columns. Answers are the selected column objects in the context
+    Columns,
+    /// Select of the database tables. Answers are the selected table reflections in the context
+    Tables,
+}
+
+impl Display for CtGenPromptSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenPromptSource::Columns => write!(f, "columns"),
+            CtGenPromptSource::Tables => write!(f, "tables"),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Filter of the built-in options of `source` prompts
+pub struct CtGenPromptFilter {
+    #[serde(
+        default,
+        deserialize_with = "CtGenPromptFilter::deserialize_datatype",
+        serialize_with = "CtGenPromptFilter::serialize_datatype",
+        skip_serializing_if = "Option::is_none"
+    )]
+    /// Regex the column datatype must match, like `^(var)?char`. Compiled once, when the profile is loaded
+    datatype: Option<Regex>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Nullability the column must have
+    nullable: Option<bool>,
+}
+
+impl CtGenPromptFilter {
+    /// Check if the filter lets every option through
+    pub fn is_empty(&self) -> bool {
+        self.datatype.is_none() && self.nullable.is_none()
+    }
+
+    /// Regex pattern the column datatype must match, like `^(var)?char`
+    pub fn datatype(&self) -> Option<&str> {
+        self.datatype.as_ref().map(Regex::as_str)
+    }
+    /// Nullability the column must have
+    pub fn nullable(&self) -> Option<bool> {
+        self.nullable
+    }
+
+    /// Check if a reflected column, as JSON, passes the filter
+    pub fn matches_column(&self, column: &Value) -> bool {
+        if let Some(regex) = self.datatype.as_ref() {
+            if !regex.is_match(column.get("datatype").and_then(Value::as_str).unwrap_or_default()) {
+                return false;
+            }
+        }
+
+        if let Some(nullable) = self.nullable {
+            return column.get("nullable").and_then(Value::as_bool).unwrap_or_default() == nullable;
+        }
+
+        true
+    }
+
+    /// Read and compile the datatype regex
+    fn deserialize_datatype<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Regex>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|datatype| {
+                Regex::new(&datatype).map_err(|e| serde::de::Error::custom(format!("Invalid datatype filter {}: {}", datatype, e)))
+            })
+            .transpose()
+    }
+
+    /// Write the datatype regex as its pattern
+    fn serialize_datatype<S: serde::Serializer>(datatype: &Option<Regex>, serializer: S) -> Result<S::Ok, S::Error> {
+        datatype.as_ref().map(Regex::as_str).serialize(serializer)
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// How confirm prompts take their answer
+pub enum CtGenConfirmStyle {
+    #[default]
+    /// Wait for ENTER after `y` or `n`
+    Newline,
+    /// Answer as soon as `y` or `n` is pressed
+    Keypress,
+}
+
+impl Display for CtGenConfirmStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenConfirmStyle::Newline => write!(f, "newline"),
+            CtGenConfirmStyle::Keypress => write!(f, "keypress"),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Prompt appearance defaults, applied to every prompt that doesn't set its own
+pub struct CtGenPromptDefaults {
+    /// Echo answers once prompts are answered. Defaults to true
+    report: Option<bool>,
+    #[serde(rename = "max-options")]
+    /// Max number of options visible at once in select prompts
+    max_options: Option<usize>,
+    /// How confirm prompts take their answer
+    confirm: Option<CtGenConfirmStyle>,
+    /// Ask to sort the answers of multiple valued prompts
+    ordered: Option<bool>,
+}
+
+impl CtGenPromptDefaults {
+    /// Check if nothing is configured
+    pub fn is_empty(&self) -> bool {
+        self.report.is_none() && self.max_options.is_none() && self.confirm.is_none() && self.ordered.is_none()
+    }
+    /// Echo answers once prompts are answered
+    pub fn report(&self) -> bool {
+        self.report.unwrap_or(true)
+    }
+    /// Max number of options visible at once in select prompts
+    pub fn max_options(&self) -> usize {
+        self.max_options.unwrap_or(PROMPT_MAX_OPTIONS_DEFAULT)
+    }
+    /// How confirm prompts take their answer
+    pub fn confirm(&self) -> CtGenConfirmStyle {
+        self.confirm.unwrap_or_default()
+    }
+    /// Ask to sort the answers of multiple valued prompts
+    pub fn ordered(&self) -> bool {
+        self.ordered.unwrap_or(CtGenPrompt::default_ordered())
+    }
+
+    /// Resolve the appearance of a prompt, its own settings taking precedence over these defaults
+    pub fn for_prompt(&self, prompt: &CtGenPrompt) -> CtGenPromptDefaults {
+        CtGenPromptDefaults {
+            report: prompt.report().or(self.report),
+            max_options: prompt.max_options().or(self.max_options),
+            confirm: prompt.confirm().or(self.confirm),
+            ordered: Some(prompt.ordered()),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct CtGenTarget {
+    #[serde(default, rename = "type", skip_serializing_if = "CtGenTargetKind::is_default")]
+    /// Whether the target renders a template or copies an asset file
+    kind: CtGenTargetKind,
+    /// Handlebars template that receives the up-to-date context. Must render to "1" to proceed
+    condition: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    /// Template name. Relative to templates dir, no file extension.
+    template: String,
+    /// Asset file path relative to the profile directory. Copy targets only
+    source: Option<String>,
+    /// Handlebars template that receives the up-to-date context. Output file path relative to target dir.
+    target: String,
+    /// Handlebars template rendering a JSON array or a comma-separated list. The target renders once per item, available as `item`
+    enumerate: Option<String>,
+    /// Handlebars template that receives the up-to-date context. Renders an optional shell command to execute after target rendering is completed
+    formatter: Option<String>,
+    #[serde(default, rename = "formatter-on-error", skip_serializing_if = "CtGenFormatterOnError::is_default")]
+    /// What a formatter exiting with an error does to the run
+    formatter_on_error: CtGenFormatterOnError,
+    /// Optional pair of alternate template delimiters, for example `["<%", "%>"]`. Literal `{{` and `}}` are left untouched.
+    delimiters: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    /// Static values merged into the context under `params` while rendering this target only
+    params: Map<String, toml::Value>,
+    #[serde(default, skip_serializing_if = "CtGenTargetMode::is_default")]
+    /// How an existing output file is treated
+    mode: CtGenTargetMode,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    /// Make the output file executable, like `chmod +x`. Unix only
+    executable: bool,
+    /// Octal mode of the output file, for example `"0640"`. Unix only
+    permissions: Option<String>,
+    #[serde(rename = "on-success")]
+    /// Handlebars template that renders an optional shell command to execute after the output file is written and formatted
+    on_success: Option<String>,
+    #[serde(rename = "on-failure")]
+    /// Handlebars template that renders an optional shell command to execute if writing or formatting the output file fails
+    on_failure: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Languages the target renders for. Renders for every language if empty
+    languages: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// Platforms the target renders on, like `["linux", "macos"]` or `["windows"]`. Renders on every platform if empty
+    platforms: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Expression over platforms and feature flags, like `windows || feature(powershell)`, that must hold for the target
+    /// to render. Evaluated before anything is rendered
+    when: Option<String>,
+}
+
+impl CtGenTarget {
+    /// Create a new target rendering a template into an output file path template
+    pub fn new(template: &str, target: &str) -> Self {
+        Self {
+            template: template.to_string(),
+            target: target.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Create a new target copying an asset file of the profile directory into an output file path template
+    pub fn new_copy(source: &str, target: &str) -> Self {
+        Self {
+            kind: CtGenTargetKind::Copy,
+            source: Some(source.to_string()),
+            target: target.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the target renders a template or copies an asset file
+    pub fn kind(&self) -> CtGenTargetKind {
+        self.kind
+    }
+    /// Check if the target copies an asset file instead of rendering a template
+    pub fn is_copy(&self) -> bool {
+        self.kind == CtGenTargetKind::Copy
+    }
+    /// Handlebars template that receives the up-to-date context. Must render to "1" to proceed
+    pub fn condition(&self) -> Option<&str> {
+        self.condition.as_deref()
+    }
+    /// Template name. Relative to templates dir, no file extension.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+    /// Asset file path relative to the profile directory. Copy targets only
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+    /// Handlebars template that receives the up-to-date context. Output file path relative to target dir.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+    /// Handlebars template rendering a JSON array or a comma-separated list. The target renders once per item, available as `item`
+    pub fn enumerate(&self) -> Option<&str> {
+        self.enumerate.as_deref()
+    }
+    /// Handlebars template that receives the up-to-date context. Renders an optional shell command to execute after target rendering is completed
+    pub fn formatter(&self) -> Option<&str> {
+        self.formatter.as_deref()
+    }
+    /// What a formatter exiting with an error does to the run
+    pub fn formatter_on_error(&self) -> CtGenFormatterOnError {
+        self.formatter_on_error
+    }
+    /// Pair of alternate template delimiters, if a valid pair is configured
+    pub fn delimiters(&self) -> Option<(&str, &str)> {
+        match self.delimiters.as_deref() {
+            Some([open, close]) if !open.is_empty() && !close.is_empty() => Some((open.as_str(), close.as_str())),
+            _ => None,
+        }
+    }
+    /// Static values merged into the context under `params` while rendering this target only
+    pub fn params(&self) -> &Map<String, toml::Value> {
+        &self.params
+    }
+    /// How an existing output file is treated
+    pub fn mode(&self) -> CtGenTargetMode {
+        self.mode
+    }
+    /// Make the output file executable, like `chmod +x`. Unix only
+    pub fn executable(&self) -> bool {
+        self.executable
+    }
+    /// Handlebars template that renders an optional shell command to execute after the output file is written and formatted
+    pub fn on_success(&self) -> Option<&str> {
+        self.on_success.as_deref()
+    }
+    /// Handlebars template that renders an optional shell command to execute if writing or formatting the output file fails
+    pub fn on_failure(&self) -> Option<&str> {
+        self.on_failure.as_deref()
+    }
+    /// Languages the target renders for. Renders for every language if empty
+    pub fn languages(&self) -> &Vec<String> {
+        &self.languages
+    }
+    /// Check if the target renders for a chosen language. Without a chosen language, every target renders
+    pub fn renders_for(&self, language: Option<&str>) -> bool {
+        match language {
+            Some(language) => self.languages.is_empty() || self.languages.iter().any(|l| l == language),
+            None => true,
+        }
+    }
+    /// Platforms the target renders on. Renders on every platform if empty
+    pub fn platforms(&self) -> &Vec<String> {
+        &self.platforms
+    }
+    /// Expression over platforms and feature flags that must hold for the target to render
+    pub fn when(&self) -> Option<&str> {
+        self.when.as_deref()
+    }
+    /// Check if the target renders on the platform ctgen runs on with the enabled feature flags
+    pub fn renders_on_current_platform(&self, features: &BTreeMap<String, bool>) -> Result<bool> {
+        Ok(CtGen::is_current_platform(&self.platforms) && self.when.as_deref().map_or(Ok(true), |when| when::evaluate(when, features))?)
+    }
+    /// Mode of the output file, if a valid octal mode is configured. Unix only
+    pub fn permissions(&self) -> Option<u32> {
+        self.permissions
+            .as_deref()
+            .and_then(|permissions| u32::from_str_radix(permissions.trim_start_matches("0o"), 8).ok())
+            .filter(|permissions| *permissions <= 0o7777)
+    }
+    /// Check if the target prints to standard output instead of writing a file
+    pub fn is_stdout(&self) -> bool {
+        self.target == TARGET_STDOUT
+    }
+
+    /// Set target condition template
+    pub fn set_condition(&mut self, condition: Option<&str>) -> &mut Self {
+        self.condition = condition.map(str::to_string);
+
+        self
+    }
+    /// Set enumerate template
+    pub fn set_enumerate(&mut self, enumerate: Option<&str>) -> &mut Self {
+        self.enumerate = enumerate.map(str::to_string);
+
+        self
+    }
+    /// Set formatter command template
+    pub fn set_formatter(&mut self, formatter: Option<&str>) -> &mut Self {
+        self.formatter = formatter.map(str::to_string);
+
+        self
+    }
+    /// Set formatter error policy
+    pub fn set_formatter_on_error(&mut self, formatter_on_error: CtGenFormatterOnError) -> &mut Self {
+        self.formatter_on_error = formatter_on_error;
+
+        self
+    }
+    /// Set alternate template delimiters
+    pub fn set_delimiters(&mut self, delimiters: Option<(&str, &str)>) -> &mut Self {
+        self.delimiters = delimiters.map(|(open, close)| vec![open.to_string(), close.to_string()]);
+
+        self
+    }
+    /// Set static target params
+    pub fn set_params(&mut self, params: Map<String, toml::Value>) -> &mut Self {
+        self.params = params;
+
+        self
+    }
+    /// Set existing output file policy
+    pub fn set_mode(&mut self, mode: CtGenTargetMode) -> &mut Self {
+        self.mode = mode;
+
+        self
+    }
+    /// Set success and failure hook command templates
+    pub fn set_hooks(&mut self, on_success: Option<&str>, on_failure: Option<&str>) -> &mut Self {
+        self.on_success = on_success.map(str::to_string);
+        self.on_failure = on_failure.map(str::to_string);
+
+        self
+    }
+    /// Set output file executable flag
+    pub fn set_executable(&mut self, executable: bool) -> &mut Self {
+        self.executable = executable;
+
+        self
+    }
+    /// Set output file octal mode
+    pub fn set_permissions(&mut self, permissions: Option<u32>) -> &mut Self {
+        self.permissions = permissions.map(|permissions| format!("{:04o}", permissions));
+
+        self
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// What a target produces its output file from
+pub enum CtGenTargetKind {
+    #[default]
+    /// Render a handlebars template
+    Template,
+    /// Copy an asset file as is, for images, lockfiles and other non-template files
+    Copy,
+}
+
+impl CtGenTargetKind {
+    /// Check if this is the default kind
+    pub fn is_default(&self) -> bool {
+        *self == CtGenTargetKind::default()
+    }
+}
+
+impl Display for CtGenTargetKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenTargetKind::Template => write!(f, "template"),
+            CtGenTargetKind::Copy => write!(f, "copy"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// How a target treats an existing output file
+pub enum CtGenTargetMode {
+    #[default]
+    /// Replace the file
+    Overwrite,
+    /// Leave the file untouched, for scaffold-once files
+    SkipIfExists,
+    /// Append the rendered output to the file
+    Append,
+    /// Fail the run
+    ErrorIfExists,
+}
+
+impl CtGenTargetMode {
+    /// Check if this is the default mode
+    pub fn is_default(&self) -> bool {
+        *self == CtGenTargetMode::default()
+    }
+}
+
+impl Display for CtGenTargetMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenTargetMode::Overwrite => write!(f, "overwrite"),
+            CtGenTargetMode::SkipIfExists => write!(f, "skip-if-exists"),
+            CtGenTargetMode::Append => write!(f, "append"),
+            CtGenTargetMode::ErrorIfExists => write!(f, "error-if-exists"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// What a formatter exiting with an error does to the run
+pub enum CtGenFormatterOnError {
+    #[default]
+    /// Fail the run
+    Fail,
+    /// Report the error and continue
+    Warn,
+    /// Continue silently
+    Ignore,
+}
+
+impl CtGenFormatterOnError {
+    /// Check if this is the default policy
+    pub fn is_default(&self) -> bool {
+        *self == CtGenFormatterOnError::default()
+    }
+}
+
+impl Display for CtGenFormatterOnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenFormatterOnError::Fail => write!(f, "fail"),
+            CtGenFormatterOnError::Warn => write!(f, "warn"),
+            CtGenFormatterOnError::Ignore => write!(f, "ignore"),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Additional database connection reflected into the context under its own name
+pub struct CtGenConnection {
+    #[serde(default, rename = "env-file")]
+    /// The .env file name to look for
+    env_file: String,
+    #[serde(default, rename = "env-var")]
+    /// The environment variable to look for
+    env_var: String,
+    #[serde(default)]
+    /// Skips env files and uses this DSN string. The DSN must include a database name
+    dsn: String,
+}
+
+impl CtGenConnection {
+    /// The .env file name to look for
+    pub fn env_file(&self) -> &str {
+        &self.env_file
+    }
+    /// The environment variable to look for
+    pub fn env_var(&self) -> &str {
+        &self.env_var
+    }
+    /// Skips env files and uses this DSN string
+    pub fn dsn(&self) -> &str {
+        &self.dsn
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Bundled helpers configuration
+pub struct CtGenHelpersConfig {
+    #[serde(default)]
+    /// `exec` helper configuration
+    exec: CtGenExecHelperConfig,
+    #[serde(default)]
+    /// Helper names whose output is cached by params for the duration of a run
+    memoize: Vec<String>,
+    #[serde(default)]
+    /// WASM plugin helpers by helper name. Module paths are relative to the profile config dir
+    plugins: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// SQL dialect used by `quote_ident` when no `dialect` param is given. Defaults to the dialect of the reflected server
+    dialect: Option<CtGenSqlDialect>,
+}
+
+impl CtGenHelpersConfig {
+    /// Check if nothing is configured
+    pub fn is_empty(&self) -> bool {
+        self.exec.allow.is_empty()
+            && self.exec.timeout.is_none()
+            && self.memoize.is_empty()
+            && self.plugins.is_empty()
+            && self.dialect.is_none()
+    }
+    /// `exec` helper configuration
+    pub fn exec(&self) -> &CtGenExecHelperConfig {
+        &self.exec
+    }
+    /// Helper names whose output is cached by params for the duration of a run
+    pub fn memoize(&self) -> &Vec<String> {
+        &self.memoize
+    }
+    /// WASM plugin helpers by helper name
+    pub fn plugins(&self) -> std::collections::hash_map::Iter<'_, String, String> {
+        self.plugins.iter()
+    }
+    /// SQL dialect used by `quote_ident`, unless left to the dialect of the reflected server
+    pub fn dialect(&self) -> Option<CtGenSqlDialect> {
+        self.dialect
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// SQL dialect deciding how `quote_ident` quotes identifiers
+pub enum CtGenSqlDialect {
+    #[default]
+    #[serde(alias = "mariadb")]
+    /// Backticks. Matches the reflected MariaDB/MySQL server
+    Mysql,
+    #[serde(alias = "postgresql", alias = "sqlite", alias = "ansi")]
+    /// ANSI double quotes
+    Postgres,
+    #[serde(alias = "sqlserver")]
+    /// Square brackets
+    Mssql,
+}
+
+impl CtGenSqlDialect {
+    /// Detect the dialect of a server by the scheme of its DSN, like `postgres://user@host/database`
+    pub fn from_dsn(dsn: &str) -> Option<Self> {
+        let (scheme, _) = dsn.trim().split_once(':')?;
+
+        match scheme.to_lowercase().as_str() {
+            "mysql" | "mariadb" => Some(CtGenSqlDialect::Mysql),
+            "postgres" | "postgresql" | "sqlite" => Some(CtGenSqlDialect::Postgres),
+            "mssql" | "sqlserver" => Some(CtGenSqlDialect::Mssql),
+            _ => None,
+        }
+    }
+
+    /// Quote an identifier, escaping embedded closing quote characters by doubling them
+    pub fn quote(&self, ident: &str) -> String {
+        match self {
+            CtGenSqlDialect::Mysql => format!("`{}`", ident.replace('`', "``")),
+            CtGenSqlDialect::Postgres => format!("\"{}\"", ident.replace('"', "\"\"")),
+            CtGenSqlDialect::Mssql => format!("[{}]", ident.replace(']', "]]")),
+        }
+    }
+}
+
+impl Display for CtGenSqlDialect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenSqlDialect::Mysql => write!(f, "mysql"),
+            CtGenSqlDialect::Postgres => write!(f, "postgres"),
+            CtGenSqlDialect::Mssql => write!(f, "mssql"),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Run-level shell command hooks. Each is a handlebars template rendered with the full task context
+pub struct CtGenHooksConfig {
+    #[serde(rename = "pre-run")]
+    /// Command to execute before any target output file is written. Failing aborts the run
+    pre_run: Option<String>,
+    #[serde(rename = "post-run")]
+    /// Command to execute after all target output files are written and formatted
+    post_run: Option<String>,
+    #[serde(rename = "on-error")]
+    /// Command to execute if the run fails, with the error message as `error`
+    on_error: Option<String>,
+}
+
+impl CtGenHooksConfig {
+    /// Check if nothing is configured
+    pub fn is_empty(&self) -> bool {
+        self.pre_run.is_none() && self.post_run.is_none() && self.on_error.is_none()
+    }
+    /// Command to execute before any target output file is written. Failing aborts the run
+    pub fn pre_run(&self) -> Option<&str> {
+        self.pre_run.as_deref()
+    }
+    /// Command to execute after all target output files are written and formatted
+    pub fn post_run(&self) -> Option<&str> {
+        self.post_run.as_deref()
+    }
+    /// Command to execute if the run fails, with the error message as `error`
+    pub fn on_error(&self) -> Option<&str> {
+        self.on_error.as_deref()
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Output language preset, exposed to templates as `language` when the language is chosen
+pub struct CtGenLanguage {
+    #[serde(default)]
+    /// Type map from database column types to language types, like `varchar = "String"`
+    types: BTreeMap<String, String>,
+}
+
+impl CtGenLanguage {
+    /// Type map from database column types to language types, like `varchar = "String"`
+    pub fn types(&self) -> &BTreeMap<String, String> {
+        &self.types
+    }
+    /// Language type of a column type as the reflection adapter reports it, like `int(10) unsigned`. Falls back to the
+    /// base type, `int unsigned` then `int`, for type maps keyed by base types
+    pub fn type_of(&self, datatype: &str) -> Option<&str> {
+        let (base, unsigned) = starter::base_type(datatype);
+
+        self.types
+            .get(datatype)
+            .or_else(|| unsigned.then(|| self.types.get(&format!("{} unsigned", base))).flatten())
+            .or_else(|| self.types.get(&base))
+            .map(String::as_str)
+    }
+    /// Type map extended with every given column type the map resolves through its base type, so templates can
+    /// `lookup` the reflected column types
+    pub fn types_for<'a>(&self, datatypes: impl Iterator<Item = &'a str>) -> BTreeMap<String, String> {
+        let mut types = self.types.clone();
+
+        for datatype in datatypes {
+            if let Some(language_type) = self.type_of(datatype) {
+                types.entry(datatype.to_string()).or_insert_with(|| language_type.to_string());
+            }
+        }
+
+        types
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Output file rules
+pub struct CtGenOutputConfig {
+    #[serde(default)]
+    /// Glob patterns of output file paths, relative to the target dir, that are never written
+    exclude: Vec<String>,
+}
+
+impl CtGenOutputConfig {
+    /// Check if nothing is configured
+    pub fn is_empty(&self) -> bool {
+        self.exclude.is_empty()
+    }
+    /// Glob patterns of output file paths, relative to the target dir, that are never written
+    pub fn exclude(&self) -> &Vec<String> {
+        &self.exclude
+    }
+
+    /// Compile an exclude pattern. Like in `.gitignore`, `*` doesn't match `/` and `**` matches any number of directories
+    pub fn glob(pattern: &str) -> Result<Glob> {
+        Ok(GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| CtGenError::ValidationError(format!("Invalid output exclude pattern `{}`: {}", pattern, e)))?)
+    }
+
+    /// Compile all exclude patterns into one matcher
+    pub fn exclude_set(&self) -> Result<GlobSet> {
+        let mut builder = GlobSetBuilder::new();
+
+        for pattern in self.exclude.iter() {
+            builder.add(CtGenOutputConfig::glob(pattern)?);
+        }
+
+        Ok(builder
+            .build()
+            .map_err(|e| CtGenError::ValidationError(format!("Invalid output exclude patterns: {}", e)))?)
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// `exec` helper configuration
+pub struct CtGenExecHelperConfig {
+    #[serde(default)]
+    /// Commands the `exec` helper is allowed to run
+    allow: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Seconds a command may run before it is killed and the render fails
+    timeout: Option<u64>,
+}
+
+impl CtGenExecHelperConfig {
+    /// Commands the `exec` helper is allowed to run
+    pub fn allow(&self) -> &Vec<String> {
+        &self.allow
+    }
+    /// Seconds a command may run before it is killed and the render fails
+    pub fn timeout(&self) -> u64 {
+        self.timeout.unwrap_or(EXEC_TIMEOUT_DEFAULT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE_CONFIG: &str = r#"
+        name = "test"
+        env-file = ".env"
+        env-var = "DATABASE_URL"
+        dsn = ""
+        target-dir = "out"
+        templates-dir = "templates"
+        scripts-dir = "scripts"
+        prompts = []
+        targets = []
+    "#;
+
+    #[test]
+    fn command_dir_defaults_to_inherit() {
+        let config: CtGenProfileConfig = toml::from_str(PROFILE_CONFIG).unwrap();
+
+        assert_eq!(config.command_dir(), CtGenCommandDir::Inherit);
+        assert_eq!(config.command_dir().resolve("target", "context", "profile"), None);
+    }
+
+    #[test]
+    fn command_dir_resolves_configured_dir() {
+        for (command_dir, expected) in [("target", "target"), ("context", "context"), ("profile", "profile")] {
+            let config: CtGenProfileConfig = toml::from_str(&format!("{}command-dir = \"{}\"\n", PROFILE_CONFIG, command_dir)).unwrap();
+
+            assert_eq!(config.command_dir().to_string(), command_dir);
+            assert_eq!(config.command_dir().resolve("target", "context", "profile"), Some(expected));
+        }
+    }
+
+    #[test]
+    fn command_dir_rejects_unknown_dir() {
+        assert!(toml::from_str::<CtGenProfileConfig>(&format!("{}command-dir = \"home\"\n", PROFILE_CONFIG)).is_err());
+    }
+
+    #[test]
+    fn language_type_of_falls_back_to_base_type() {
+        let language: CtGenLanguage = toml::from_str(
+            r#"
+            types = { varchar = "String", "int unsigned" = "u32", int = "i32", "decimal(10,2)" = "Decimal" }
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(language.type_of("varchar(255)"), Some("String"));
+        assert_eq!(language.type_of("int(10) unsigned"), Some("u32"));
+        assert_eq!(language.type_of("int(11)"), Some("i32"));
+        assert_eq!(language.type_of("decimal(10,2)"), Some("Decimal"));
+        assert_eq!(language.type_of("geometry"), None);
+        assert_eq!(
+            language
+                .types_for(["varchar(64)", "geometry"].into_iter())
+                .get("varchar(64)")
+                .map(String::as_str),
+            Some("String")
+        );
+    }
+
+    #[test]
+    fn prompt_filter_compiles_datatype_on_load() {
+        let filter: CtGenPromptFilter = toml::from_str(r#"datatype = "^(var)?char""#).unwrap();
+
+        assert_eq!(filter.datatype(), Some("^(var)?char"));
+        assert!(filter.matches_column(&serde_json::json!({"datatype": "varchar(64)", "nullable": true})));
+        assert!(!filter.matches_column(&serde_json::json!({"datatype": "int(11)", "nullable": true})));
+        assert_eq!(toml::to_string(&filter).unwrap().trim(), r#"datatype = "^(var)?char""#);
+
+        assert!(toml::from_str::<CtGenPromptFilter>(r#"datatype = "(char""#).is_err());
+    }
+
+    #[test]
+    fn prompt_depends_on_table_by_source_or_reference() {
+        let profile: CtGenProfile = toml::from_str(&format!(
+            r#"
+            target = {{}}
+
+            [profile]
+            {}
+
+            [prompt.fields]
+            prompt = "Fields"
+            source = "columns"
+
+            [prompt.model]
+            prompt = "Model name"
+            default = "{{{{inflect table/name to_pascal_case=true}}}}"
+
+            [prompt.author]
+            prompt = "Author"
+            default = "{{{{prompts/table}}}}"
+        "#,
+            PROFILE_CONFIG
+        ))
+        .unwrap();
+
+        assert!(profile.prompt_depends_on_table("fields"));
+        assert!(profile.prompt_depends_on_table("model"));
+        assert!(!profile.prompt_depends_on_table("author"));
+        assert!(!profile.prompt_depends_on_table("missing"));
+    }
+
+    #[test]
+    fn columns_source_prompt_is_multiple_choice() {
+        let columns: CtGenPrompt = toml::from_str("prompt = \"Fields\"\nsource = \"columns\"").unwrap();
+        let tables: CtGenPrompt = toml::from_str("prompt = \"Table\"\nsource = \"tables\"").unwrap();
+
+        assert!(!columns.multiple());
+        assert!(columns.is_multiple_choice());
+        assert!(!tables.is_multiple_choice());
     }
 }
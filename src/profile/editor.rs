@@ -0,0 +1,256 @@
+use crate::consts::{CONFIG_NAME_PATTERN, PROFILE_CONFIG_KEYS};
+use crate::error::CtGenError;
+use crate::profile::{CtGenProfile, CtGenPrompt, CtGenTarget};
+use crate::CtGen;
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+use toml_edit::ser::ValueSerializer;
+use toml_edit::{Array, DocumentMut, Item, Table, Value};
+
+#[derive(Clone, Debug)]
+/// Profile config file editor. Edits are applied to the parsed document, so comments and formatting are preserved on save
+pub struct CtGenProfileEditor {
+    /// Profile config file path
+    file: String,
+    /// Parsed profile config document
+    document: DocumentMut,
+}
+
+impl CtGenProfileEditor {
+    /// Load profile config file for editing
+    pub async fn load(file: &str) -> Result<Self> {
+        match tokio::fs::read_to_string(file).await {
+            Ok(c) => CtGenProfileEditor::parse(file, &c),
+            Err(e) => Err(CtGenError::RuntimeError(format!("Failed to load profile config: {}", e)).into()),
+        }
+    }
+
+    /// Parse profile config contents. The file path is only used when saving
+    pub fn parse(file: &str, content: &str) -> Result<Self> {
+        let document = content
+            .parse::<DocumentMut>()
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to parse profile config: {}", e)))?;
+
+        Ok(Self {
+            file: file.to_string(),
+            document,
+        })
+    }
+
+    /// Profile config file path
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Typed profile model of the current document state
+    pub fn profile(&self, name: &str) -> Result<CtGenProfile> {
+        let mut profile: CtGenProfile = toml::from_str(&self.document.to_string())
+            .map_err(|e| CtGenError::ValidationError(format!("Failed to parse profile config: {}", e)))?;
+
+        if !name.is_empty() {
+            profile.set_name(name);
+        } else {
+            let name = profile.profile.name().to_string();
+            profile.set_name(&name);
+        }
+
+        if let Some(context_dir) = Path::new(&self.file).parent().and_then(|p| p.to_str()) {
+            profile.set_context_dir(context_dir);
+        }
+
+        Ok(profile)
+    }
+
+    /// Add a new prompt or replace existing. New prompt ids are appended to the profile prompts list
+    pub fn add_prompt(&mut self, id: &str, prompt: &CtGenPrompt) -> Result<&mut Self> {
+        self.set_section_entry("prompt", id, prompt)?;
+        self.list_push("prompts", id)?;
+
+        Ok(self)
+    }
+
+    /// Remove a prompt and its id from the profile prompts list
+    pub fn remove_prompt(&mut self, id: &str) -> Result<&mut Self> {
+        self.remove_section_entry("prompt", id);
+        self.list_remove("prompts", id)?;
+
+        Ok(self)
+    }
+
+    /// Add a new target or replace existing. New target ids are appended to the profile targets list
+    pub fn add_target(&mut self, id: &str, target: &CtGenTarget) -> Result<&mut Self> {
+        self.set_section_entry("target", id, target)?;
+        self.list_push("targets", id)?;
+
+        Ok(self)
+    }
+
+    /// Remove a target and its id from the profile targets list
+    pub fn remove_target(&mut self, id: &str) -> Result<&mut Self> {
+        self.remove_section_entry("target", id);
+        self.list_remove("targets", id)?;
+
+        Ok(self)
+    }
+
+    /// Set a `[profile]` directive, for example `target-dir`
+    pub fn set_config(&mut self, key: &str, value: impl Into<Value>) -> Result<&mut Self> {
+        if !PROFILE_CONFIG_KEYS.contains(&key) {
+            return Err(CtGenError::ValidationError(format!(
+                "Unknown profile directive `{}`. Expected one of: {}",
+                key,
+                PROFILE_CONFIG_KEYS.join(", ")
+            ))
+            .into());
+        }
+
+        let profile = self.profile_table_mut()?;
+
+        if let Some(Item::Value(current)) = profile.get_mut(key) {
+            // keep the existing decoration, like trailing comments
+            let decor = current.decor().clone();
+            *current = value.into();
+            *current.decor_mut() = decor;
+        } else {
+            profile.insert(key, Item::Value(value.into()));
+        }
+
+        Ok(self)
+    }
+
+    /// Remove a `[profile]` directive, so the default value applies
+    pub fn unset_config(&mut self, key: &str) -> Result<&mut Self> {
+        self.profile_table_mut()?.remove(key);
+
+        Ok(self)
+    }
+
+    /// Validate and persist the profile config file
+    pub async fn save(&self) -> Result<()> {
+        self.profile("")?;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&self.file)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to open toml file: {}", e)))?;
+
+        file.write_all(self.document.to_string().as_bytes())
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to write toml file: {}", e)))?;
+
+        file.flush()
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to flush toml file: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Validate prompt and target ids
+    pub(crate) fn validate_id(id: &str) -> Result<()> {
+        if !CtGen::get_name_regex().is_match(id) {
+            return Err(CtGenError::ValidationError(format!("Invalid name: {}. Make sure it matches {}", id, CONFIG_NAME_PATTERN)).into());
+        }
+
+        Ok(())
+    }
+
+    /// Get the `[profile]` table
+    fn profile_table_mut(&mut self) -> Result<&mut Table> {
+        Ok(self
+            .document
+            .get_mut("profile")
+            .and_then(Item::as_table_mut)
+            .ok_or_else(|| CtGenError::ValidationError("Missing [profile] table.".to_string()))?)
+    }
+
+    /// Get a top-level section table like `[prompt]`, created as an implicit table if missing
+    fn section_mut(&mut self, section: &str) -> Result<&mut Table> {
+        let item = self.document.entry(section).or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+
+            Item::Table(table)
+        });
+
+        Ok(item
+            .as_table_mut()
+            .ok_or_else(|| CtGenError::ValidationError(format!("Invalid [{}] table.", section)))?)
+    }
+
+    /// Serialize a prompt or target into its own `[section.id]` table. Nested tables, like prompt options, stay inline
+    fn set_section_entry<T: Serialize>(&mut self, section: &str, id: &str, entry: &T) -> Result<()> {
+        CtGenProfileEditor::validate_id(id)?;
+
+        let value = entry
+            .serialize(ValueSerializer::new())
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to generate toml for {} `{}`: {}", section, id, e)))?;
+
+        let table = Item::Value(value)
+            .into_table()
+            .map_err(|_| CtGenError::RuntimeError(format!("Failed to generate toml table for {} `{}`", section, id)))?;
+
+        let section_table = self.section_mut(section)?;
+
+        if let Some(Item::Table(current)) = section_table.get_mut(id) {
+            // replace values in place, so the table keeps its position and comments
+            let decor = current.decor().clone();
+            let position = current.position();
+
+            *current = table;
+            *current.decor_mut() = decor;
+            if let Some(position) = position {
+                current.set_position(position);
+            }
+        } else {
+            section_table.insert(id, Item::Table(table));
+        }
+
+        Ok(())
+    }
+
+    /// Remove a `[section.id]` table
+    fn remove_section_entry(&mut self, section: &str, id: &str) {
+        if let Some(section_table) = self.document.get_mut(section).and_then(Item::as_table_mut) {
+            section_table.remove(id);
+        }
+    }
+
+    /// Get a `[profile]` list, like `prompts` or `targets`, created if missing
+    fn list_mut(&mut self, list: &str) -> Result<&mut Array> {
+        Ok(self
+            .profile_table_mut()?
+            .entry(list)
+            .or_insert_with(|| Item::Value(Value::Array(Array::new())))
+            .as_array_mut()
+            .ok_or_else(|| CtGenError::ValidationError(format!("Invalid profile directive `{}`. Expected an array.", list)))?)
+    }
+
+    /// Append an id to a `[profile]` list, unless already present
+    fn list_push(&mut self, list: &str, id: &str) -> Result<()> {
+        let array = self.list_mut(list)?;
+
+        if !array.iter().any(|v| v.as_str() == Some(id)) {
+            array.push(id);
+        }
+
+        Ok(())
+    }
+
+    /// Remove an id from a `[profile]` list
+    fn list_remove(&mut self, list: &str, id: &str) -> Result<()> {
+        self.list_mut(list)?.retain(|v| v.as_str() != Some(id));
+
+        Ok(())
+    }
+}
+
+impl Display for CtGenProfileEditor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.document)
+    }
+}
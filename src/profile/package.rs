@@ -0,0 +1,119 @@
+use crate::consts::{PROFILE_DEFAULT_FILENAME, SNAPSHOTS_DIR};
+use crate::error::CtGenError;
+use crate::profile::CtGenProfile;
+use crate::task::archive::CtGenArchiveEntry;
+use crate::CtGen;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+use walkdir::WalkDir;
+
+/// Collect profile files into archive entries, relative to the profile dir: the config file as `Ctgen.toml`, everything in
+/// the templates, scripts and snapshot tests dirs, copy target sources and WASM plugin modules. Hidden files are skipped
+pub async fn collect(profile: &CtGenProfile, config_file: &str) -> Result<Vec<CtGenArchiveEntry>> {
+    let context_dir = profile.context_dir();
+
+    let mut files = BTreeMap::new();
+
+    // the config file may have any name, installed profiles always use the default one
+    files.insert(PROFILE_DEFAULT_FILENAME.to_string(), config_file.to_string());
+
+    for dir in [
+        profile.templates_dir(),
+        profile.scripts_dir(),
+        CtGen::get_filepath(context_dir, SNAPSHOTS_DIR),
+    ] {
+        for entry in WalkDir::new(&dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let file = entry.path().to_string_lossy().to_string();
+
+            files.entry(archive_path(context_dir, &file)?).or_insert(file);
+        }
+    }
+
+    for target_name in profile.targets() {
+        if let Some(source) = profile.target(target_name).and_then(|target| profile.target_source_file(target)) {
+            files.insert(archive_path(context_dir, &source)?, source);
+        }
+    }
+
+    for (_, module) in profile.helpers().plugins() {
+        let module = CtGen::get_filepath(context_dir, module);
+
+        files.insert(archive_path(context_dir, &module)?, module);
+    }
+
+    let mut entries = Vec::with_capacity(files.len());
+
+    for (path, file) in files {
+        let content = tokio::fs::read(&file)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to read profile file {}: {}", file, e)))?;
+
+        entries.push((path, content, 0o644));
+    }
+
+    Ok(entries)
+}
+
+/// Archive entry path of a profile file, with `/` separators. Files outside of the profile dir can't be bundled
+fn archive_path(context_dir: &str, file: &str) -> Result<String> {
+    let path = CtGen::normalize_path(file);
+
+    let relative = path.strip_prefix(CtGen::normalize_path(context_dir)).map_err(|_e| {
+        CtGenError::ValidationError(format!(
+            "Profile file {} is outside of the profile dir {} and can't be exported",
+            file, context_dir
+        ))
+    })?;
+
+    Ok(relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// Profile name declared by the config file of an archive
+pub fn profile_name(entries: &[CtGenArchiveEntry]) -> Result<String> {
+    let (_, content, _) = entries
+        .iter()
+        .find(|(path, _, _)| path == PROFILE_DEFAULT_FILENAME)
+        .ok_or_else(|| CtGenError::ValidationError(format!("Profile archive is missing {}", PROFILE_DEFAULT_FILENAME)))?;
+
+    let profile: CtGenProfile = toml::from_str(&String::from_utf8_lossy(content))
+        .map_err(|e| CtGenError::ValidationError(format!("Failed to parse archived profile config: {}", e)))?;
+
+    Ok(profile.configuration().name().to_string())
+}
+
+/// Write archive entries into a profile dir. Nothing is written if any entry would end up outside of it
+pub async fn extract(entries: &[CtGenArchiveEntry], profile_dir: &str) -> Result<()> {
+    if let Some((path, _, _)) = entries
+        .iter()
+        .find(|(path, _, _)| !Path::new(path).components().all(|c| matches!(c, Component::Normal(_))))
+    {
+        return Err(CtGenError::ValidationError(format!("Invalid profile archive entry {}", path)).into());
+    }
+
+    for (path, content, _) in entries {
+        let file = CtGen::get_filepath(profile_dir, path);
+
+        if let Some(parent) = Path::new(&file).parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to create directory {}: {}", parent.display(), e)))?;
+        }
+
+        tokio::fs::write(&file, content)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to write profile file {}: {}", file, e)))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,357 @@
+use crate::error::CtGenError;
+use anyhow::Result;
+use std::ops::Range;
+use toml_edit::{ImDocument, Item, TableLike};
+
+/// Expected value of a profile config key
+#[derive(Clone, Copy, Debug)]
+enum CtGenSchemaKind {
+    String,
+    Bool,
+    Integer,
+    /// Integer or float
+    Number,
+    /// Array of strings
+    Strings,
+    /// Any value
+    Any,
+    /// One of the listed strings
+    OneOf(&'static [&'static str]),
+    /// Table with the listed keys
+    Table(&'static [CtGenSchemaField]),
+    /// Table of entries with any name, each matching the kind
+    Named(&'static CtGenSchemaKind),
+}
+
+/// Profile config key, its expected value and whether it is required
+#[derive(Clone, Copy, Debug)]
+struct CtGenSchemaField(&'static str, CtGenSchemaKind, bool);
+
+// Keep these in sync with the serde names of the profile config structs
+const PROFILE_FIELDS: &[CtGenSchemaField] = &[
+    CtGenSchemaField("name", CtGenSchemaKind::String, true),
+    CtGenSchemaField("env-file", CtGenSchemaKind::String, true),
+    CtGenSchemaField("env-var", CtGenSchemaKind::String, true),
+    CtGenSchemaField("dsn", CtGenSchemaKind::String, true),
+    CtGenSchemaField("target-dir", CtGenSchemaKind::String, true),
+    CtGenSchemaField("templates-dir", CtGenSchemaKind::String, true),
+    CtGenSchemaField("scripts-dir", CtGenSchemaKind::String, true),
+    CtGenSchemaField("prompts", CtGenSchemaKind::Strings, true),
+    CtGenSchemaField("targets", CtGenSchemaKind::Strings, true),
+    CtGenSchemaField("reflect-include", CtGenSchemaKind::Strings, false),
+    CtGenSchemaField("reflect-exclude", CtGenSchemaKind::Strings, false),
+    CtGenSchemaField("parallelism", CtGenSchemaKind::Integer, false),
+    CtGenSchemaField("strict", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("allow-external-target-dir", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("generation-log", CtGenSchemaKind::String, false),
+    CtGenSchemaField("auto-targets", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField(
+        "command-dir",
+        CtGenSchemaKind::OneOf(&["inherit", "target", "context", "profile"]),
+        false,
+    ),
+    CtGenSchemaField("languages", CtGenSchemaKind::Strings, false),
+    CtGenSchemaField("reflect-mode", CtGenSchemaKind::OneOf(&["full", "table"]), false),
+    CtGenSchemaField("version", CtGenSchemaKind::String, false),
+    CtGenSchemaField("requires-ctgen", CtGenSchemaKind::String, false),
+    CtGenSchemaField("features", CtGenSchemaKind::Strings, false),
+    CtGenSchemaField("uses", CtGenSchemaKind::Strings, false),
+];
+
+const CONFIRM_STYLES: &[&str] = &["newline", "keypress"];
+
+const PROMPT_TYPES: &[&str] = &["string", "number", "integer", "boolean", "path", "confirm", "editor"];
+
+const PROMPT_FIELDS: &[CtGenSchemaField] = &[
+    CtGenSchemaField("condition", CtGenSchemaKind::String, false),
+    CtGenSchemaField("enumerate", CtGenSchemaKind::String, false),
+    CtGenSchemaField("prompt", CtGenSchemaKind::String, true),
+    CtGenSchemaField("default", CtGenSchemaKind::Any, false),
+    CtGenSchemaField("transform", CtGenSchemaKind::String, false),
+    CtGenSchemaField("options", CtGenSchemaKind::Any, false),
+    CtGenSchemaField("multiple", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("ordered", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("required", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("pattern", CtGenSchemaKind::String, false),
+    CtGenSchemaField("min", CtGenSchemaKind::Number, false),
+    CtGenSchemaField("max", CtGenSchemaKind::Number, false),
+    CtGenSchemaField("report", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("max-options", CtGenSchemaKind::Integer, false),
+    CtGenSchemaField("confirm", CtGenSchemaKind::OneOf(CONFIRM_STYLES), false),
+    CtGenSchemaField("platforms", CtGenSchemaKind::Strings, false),
+    CtGenSchemaField("when", CtGenSchemaKind::String, false),
+    CtGenSchemaField("secret", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("type", CtGenSchemaKind::OneOf(PROMPT_TYPES), false),
+    CtGenSchemaField("kind", CtGenSchemaKind::OneOf(PROMPT_TYPES), false),
+    CtGenSchemaField("must-exist", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("create-if-missing", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("source", CtGenSchemaKind::OneOf(&["columns", "tables"]), false),
+    CtGenSchemaField(
+        "filter",
+        CtGenSchemaKind::Table(&[
+            CtGenSchemaField("datatype", CtGenSchemaKind::String, false),
+            CtGenSchemaField("nullable", CtGenSchemaKind::Bool, false),
+        ]),
+        false,
+    ),
+    CtGenSchemaField("order", CtGenSchemaKind::Integer, false),
+    CtGenSchemaField("group", CtGenSchemaKind::String, false),
+    CtGenSchemaField(
+        "labels",
+        CtGenSchemaKind::Table(&[
+            CtGenSchemaField("yes", CtGenSchemaKind::String, false),
+            CtGenSchemaField("no", CtGenSchemaKind::String, false),
+            CtGenSchemaField("skip", CtGenSchemaKind::String, false),
+        ]),
+        false,
+    ),
+];
+
+const TARGET_FIELDS: &[CtGenSchemaField] = &[
+    CtGenSchemaField("type", CtGenSchemaKind::OneOf(&["template", "copy"]), false),
+    CtGenSchemaField("condition", CtGenSchemaKind::String, false),
+    CtGenSchemaField("template", CtGenSchemaKind::String, false),
+    CtGenSchemaField("source", CtGenSchemaKind::String, false),
+    CtGenSchemaField("target", CtGenSchemaKind::String, true),
+    CtGenSchemaField("enumerate", CtGenSchemaKind::String, false),
+    CtGenSchemaField("formatter", CtGenSchemaKind::String, false),
+    CtGenSchemaField("formatter-on-error", CtGenSchemaKind::OneOf(&["fail", "warn", "ignore"]), false),
+    CtGenSchemaField("delimiters", CtGenSchemaKind::Strings, false),
+    CtGenSchemaField("params", CtGenSchemaKind::Named(&CtGenSchemaKind::Any), false),
+    CtGenSchemaField(
+        "mode",
+        CtGenSchemaKind::OneOf(&["overwrite", "skip-if-exists", "append", "error-if-exists"]),
+        false,
+    ),
+    CtGenSchemaField("executable", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("permissions", CtGenSchemaKind::String, false),
+    CtGenSchemaField("on-success", CtGenSchemaKind::String, false),
+    CtGenSchemaField("on-failure", CtGenSchemaKind::String, false),
+    CtGenSchemaField("languages", CtGenSchemaKind::Strings, false),
+    CtGenSchemaField("platforms", CtGenSchemaKind::Strings, false),
+    CtGenSchemaField("when", CtGenSchemaKind::String, false),
+];
+
+const CONNECTION_FIELDS: &[CtGenSchemaField] = &[
+    CtGenSchemaField("env-file", CtGenSchemaKind::String, false),
+    CtGenSchemaField("env-var", CtGenSchemaKind::String, false),
+    CtGenSchemaField("dsn", CtGenSchemaKind::String, false),
+];
+
+const HELPERS_FIELDS: &[CtGenSchemaField] = &[
+    CtGenSchemaField(
+        "exec",
+        CtGenSchemaKind::Table(&[CtGenSchemaField("allow", CtGenSchemaKind::Strings, false)]),
+        false,
+    ),
+    CtGenSchemaField("memoize", CtGenSchemaKind::Strings, false),
+    CtGenSchemaField("plugins", CtGenSchemaKind::Named(&CtGenSchemaKind::String), false),
+    CtGenSchemaField(
+        "dialect",
+        CtGenSchemaKind::OneOf(&["mysql", "mariadb", "postgres", "postgresql", "sqlite", "ansi", "mssql", "sqlserver"]),
+        false,
+    ),
+];
+
+const HOOKS_FIELDS: &[CtGenSchemaField] = &[
+    CtGenSchemaField("pre-run", CtGenSchemaKind::String, false),
+    CtGenSchemaField("post-run", CtGenSchemaKind::String, false),
+    CtGenSchemaField("on-error", CtGenSchemaKind::String, false),
+];
+
+const PROMPT_DEFAULTS_FIELDS: &[CtGenSchemaField] = &[
+    CtGenSchemaField("report", CtGenSchemaKind::Bool, false),
+    CtGenSchemaField("max-options", CtGenSchemaKind::Integer, false),
+    CtGenSchemaField("confirm", CtGenSchemaKind::OneOf(CONFIRM_STYLES), false),
+    CtGenSchemaField("ordered", CtGenSchemaKind::Bool, false),
+];
+
+const CONFIG_FIELDS: &[CtGenSchemaField] = &[
+    CtGenSchemaField("profile", CtGenSchemaKind::Table(PROFILE_FIELDS), true),
+    CtGenSchemaField("prompt", CtGenSchemaKind::Named(&CtGenSchemaKind::Table(PROMPT_FIELDS)), true),
+    CtGenSchemaField("target", CtGenSchemaKind::Named(&CtGenSchemaKind::Table(TARGET_FIELDS)), true),
+    CtGenSchemaField(
+        "connection",
+        CtGenSchemaKind::Named(&CtGenSchemaKind::Table(CONNECTION_FIELDS)),
+        false,
+    ),
+    CtGenSchemaField("helpers", CtGenSchemaKind::Table(HELPERS_FIELDS), false),
+    CtGenSchemaField("hooks", CtGenSchemaKind::Table(HOOKS_FIELDS), false),
+    CtGenSchemaField("prompt-defaults", CtGenSchemaKind::Table(PROMPT_DEFAULTS_FIELDS), false),
+    CtGenSchemaField(
+        "output",
+        CtGenSchemaKind::Table(&[CtGenSchemaField("exclude", CtGenSchemaKind::Strings, false)]),
+        false,
+    ),
+    CtGenSchemaField(
+        "language",
+        CtGenSchemaKind::Named(&CtGenSchemaKind::Table(&[CtGenSchemaField(
+            "types",
+            CtGenSchemaKind::Named(&CtGenSchemaKind::String),
+            false,
+        )])),
+        false,
+    ),
+    CtGenSchemaField("vars", CtGenSchemaKind::Named(&CtGenSchemaKind::Any), false),
+];
+
+/// Validate profile config content against the config schema. Fails with every unknown key, value of the wrong type,
+/// missing required key and undeclared prompt or target at once, each with its line and column
+pub fn validate(file: &str, content: &str) -> Result<()> {
+    let document =
+        ImDocument::parse(content).map_err(|e| CtGenError::RuntimeError(format!("Failed to parse profile config {}: {}", file, e)))?;
+
+    let mut issues = Vec::new();
+
+    check_table(
+        content,
+        document.as_table(),
+        document.as_item().span(),
+        "",
+        CONFIG_FIELDS,
+        &mut issues,
+    );
+    check_references(content, document.as_table(), &mut issues);
+
+    if !issues.is_empty() {
+        return Err(CtGenError::ValidationError(format!("Invalid profile config {}:\n{}", file, issues.join("\n"))).into());
+    }
+
+    Ok(())
+}
+
+/// Check the keys of a table against the schema fields
+fn check_table(
+    content: &str,
+    table: &dyn TableLike,
+    span: Option<Range<usize>>,
+    path: &str,
+    fields: &[CtGenSchemaField],
+    issues: &mut Vec<String>,
+) {
+    for (key, item) in table.iter() {
+        let key_span = table.get_key_value(key).and_then(|(key, _)| key.span());
+        let key_path = join_path(path, key);
+
+        match fields.iter().find(|field| field.0 == key) {
+            Some(field) => check_item(content, item, key_span, &key_path, &field.1, issues),
+            None => issues.push(issue(
+                content,
+                key_span,
+                &format!(
+                    "unknown key `{}`. Expected one of: {}",
+                    key_path,
+                    fields.iter().map(|field| field.0).collect::<Vec<&str>>().join(", ")
+                ),
+            )),
+        }
+    }
+
+    for field in fields.iter().filter(|field| field.2) {
+        if table.get(field.0).is_none() {
+            issues.push(issue(
+                content,
+                span.clone(),
+                &format!("missing required key `{}`", join_path(path, field.0)),
+            ));
+        }
+    }
+}
+
+/// Check a value against the expected kind
+fn check_item(content: &str, item: &Item, key_span: Option<Range<usize>>, path: &str, kind: &CtGenSchemaKind, issues: &mut Vec<String>) {
+    let span = item.span().or(key_span);
+
+    let expected = match kind {
+        CtGenSchemaKind::String if item.as_str().is_none() => "a string",
+        CtGenSchemaKind::Bool if item.as_bool().is_none() => "a boolean",
+        CtGenSchemaKind::Integer if item.as_integer().is_none() => "an integer",
+        CtGenSchemaKind::Number if item.as_integer().is_none() && item.as_float().is_none() => "a number",
+        CtGenSchemaKind::Strings
+            if !item
+                .as_array()
+                .is_some_and(|array| array.iter().all(|value| value.as_str().is_some())) =>
+        {
+            "an array of strings"
+        }
+        CtGenSchemaKind::OneOf(options) if !item.as_str().is_some_and(|value| options.iter().any(|option| *option == value)) => {
+            issues.push(issue(content, span, &format!("`{}` must be one of: {}", path, options.join(", "))));
+            return;
+        }
+        CtGenSchemaKind::Table(fields) => match item.as_table_like() {
+            Some(table) => {
+                check_table(content, table, span, path, fields, issues);
+                return;
+            }
+            None => "a table",
+        },
+        CtGenSchemaKind::Named(entry_kind) => match item.as_table_like() {
+            Some(table) => {
+                for (name, entry) in table.iter() {
+                    let entry_span = table.get_key_value(name).and_then(|(key, _)| key.span());
+                    check_item(content, entry, entry_span, &join_path(path, name), entry_kind, issues);
+                }
+                return;
+            }
+            None => "a table",
+        },
+        _ => return,
+    };
+
+    issues.push(issue(content, span, &format!("`{}` must be {}", path, expected)));
+}
+
+/// Check that the prompts and targets listed in `[profile]` are declared. Auto targets may declare targets later
+fn check_references(content: &str, config: &dyn TableLike, issues: &mut Vec<String>) {
+    let Some(profile) = config.get("profile").and_then(Item::as_table_like) else {
+        return;
+    };
+
+    let auto_targets = profile.get("auto-targets").and_then(Item::as_bool).unwrap_or(false);
+
+    for (list, section) in [("prompts", "prompt"), ("targets", "target")] {
+        if section == "target" && auto_targets {
+            continue;
+        }
+
+        let declared = config.get(section).and_then(Item::as_table_like);
+
+        for value in profile.get(list).and_then(Item::as_array).into_iter().flatten() {
+            if let Some(name) = value.as_str() {
+                if !declared.is_some_and(|declared| declared.contains_key(name)) {
+                    issues.push(issue(
+                        content,
+                        value.span(),
+                        &format!(
+                            "`profile.{}` lists `{}`, but there is no [{}.{}] section",
+                            list, name, section, name
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Dotted key path
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Issue message prefixed with the line and column of a span
+fn issue(content: &str, span: Option<Range<usize>>, message: &str) -> String {
+    match span {
+        Some(span) => {
+            let before = &content[..span.start.min(content.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+
+            format!("  line {}, column {}: {}", line, column, message)
+        }
+        None => format!("  {}", message),
+    }
+}
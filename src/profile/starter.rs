@@ -0,0 +1,295 @@
+use crate::consts::*;
+use crate::error::CtGenError;
+use crate::profile::{CtGenLanguage, CtGenProfile, CtGenTarget};
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug)]
+/// Bundled starter template set of new profiles
+pub struct CtGenStarterKit {
+    /// Kit name, like `rust-sqlx`
+    name: &'static str,
+    /// Short description of what the kit generates
+    description: &'static str,
+    /// Output language of the kit templates, holding the column type map
+    language: Option<&'static str>,
+    /// Targets by name, with their template contents and output path, in rendering order
+    targets: &'static [(&'static str, &'static str, &'static str)],
+    /// Language type of a database column type
+    types: fn(&str) -> &'static str,
+}
+
+/// Bundled starter kits, the blank kit first
+pub const STARTER_KITS: [CtGenStarterKit; 4] = [
+    CtGenStarterKit {
+        name: KIT_BLANK,
+        description: "Dummy prompt and target showing off the task context",
+        language: None,
+        targets: &[],
+        types: untyped,
+    },
+    CtGenStarterKit {
+        name: "rust-sqlx",
+        description: "Rust sqlx model, repository and test",
+        language: Some("rust"),
+        targets: &[
+            (
+                "model",
+                KIT_RUST_SQLX_MODEL_TEMPLATE,
+                "src/models/{{inflect (inflect table/name to_singular=true) to_snake_case=true}}.rs",
+            ),
+            (
+                "repository",
+                KIT_RUST_SQLX_REPOSITORY_TEMPLATE,
+                "src/repositories/{{inflect (inflect table/name to_singular=true) to_snake_case=true}}_repository.rs",
+            ),
+            (
+                "test",
+                KIT_RUST_SQLX_TEST_TEMPLATE,
+                "tests/{{inflect (inflect table/name to_singular=true) to_snake_case=true}}_test.rs",
+            ),
+        ],
+        types: rust_sqlx_type,
+    },
+    CtGenStarterKit {
+        name: "php-laravel",
+        description: "PHP Laravel Eloquent model, repository and test",
+        language: Some("php"),
+        targets: &[
+            (
+                "model",
+                KIT_PHP_LARAVEL_MODEL_TEMPLATE,
+                "app/Models/{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}.php",
+            ),
+            (
+                "repository",
+                KIT_PHP_LARAVEL_REPOSITORY_TEMPLATE,
+                "app/Repositories/{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}Repository.php",
+            ),
+            (
+                "test",
+                KIT_PHP_LARAVEL_TEST_TEMPLATE,
+                "tests/Unit/{{inflect (inflect table/name to_singular=true) to_pascal_case=true}}Test.php",
+            ),
+        ],
+        types: php_laravel_cast,
+    },
+    CtGenStarterKit {
+        name: "ts-prisma",
+        description: "TypeScript Prisma model, repository and test",
+        language: Some("typescript"),
+        targets: &[
+            (
+                "model",
+                KIT_TS_PRISMA_MODEL_TEMPLATE,
+                "prisma/{{inflect (inflect table/name to_singular=true) to_snake_case=true}}.prisma",
+            ),
+            (
+                "repository",
+                KIT_TS_PRISMA_REPOSITORY_TEMPLATE,
+                "src/repositories/{{inflect (inflect table/name to_singular=true) to_camel_case=true}}Repository.ts",
+            ),
+            (
+                "test",
+                KIT_TS_PRISMA_TEST_TEMPLATE,
+                "tests/{{inflect (inflect table/name to_singular=true) to_camel_case=true}}Repository.test.ts",
+            ),
+        ],
+        types: ts_prisma_type,
+    },
+];
+
+impl CtGenStarterKit {
+    /// Find a bundled kit by name
+    pub fn get(name: &str) -> Result<CtGenStarterKit> {
+        STARTER_KITS.iter().find(|kit| kit.name == name).copied().ok_or_else(|| {
+            CtGenError::ValidationError(format!(
+                "Unknown starter kit `{}`. Available kits: {}",
+                name,
+                STARTER_KITS.iter().map(|kit| kit.name).collect::<Vec<&str>>().join(", ")
+            ))
+            .into()
+        })
+    }
+
+    /// Kit name, like `rust-sqlx`
+    pub fn name(&self) -> &str {
+        self.name
+    }
+    /// Short description of what the kit generates
+    pub fn description(&self) -> &str {
+        self.description
+    }
+    /// Output language of the kit templates, holding the column type map
+    pub fn language(&self) -> Option<&str> {
+        self.language
+    }
+
+    /// Check if this is the blank kit, the dummy profile
+    pub fn is_blank(&self) -> bool {
+        self.name == KIT_BLANK
+    }
+
+    /// Kit templates by name. Templates generated from a table open with a comment listing its reflected columns
+    pub fn templates(&self, table: Option<&Value>) -> Vec<(String, String)> {
+        if self.is_blank() {
+            return vec![("dummy".to_string(), DUMMY_TEMPLATE.to_string())];
+        }
+
+        let header = table
+            .map(|table| {
+                format!(
+                    "{{{{!-- Starter template generated from table `{}`: {} --}}}}\n",
+                    table.get("name").and_then(Value::as_str).unwrap_or_default(),
+                    columns(table)
+                        .map(|(name, datatype, nullable)| format!("{} {}{}", name, datatype, if nullable { " null" } else { "" }))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            })
+            .unwrap_or_default();
+
+        self.targets
+            .iter()
+            .map(|(name, template, _)| (name.to_string(), format!("{}{}", header, template)))
+            .collect()
+    }
+
+    /// Language type map of the column types of a table, like `varchar = "String"`. Without a table, common column types
+    /// are mapped
+    fn types(&self, table: Option<&Value>) -> BTreeMap<String, String> {
+        let datatypes = match table {
+            Some(table) => columns(table)
+                .map(|(_, datatype, _)| datatype)
+                .filter(|datatype| !datatype.is_empty())
+                .collect::<Vec<&str>>(),
+            None => KIT_DATATYPES.to_vec(),
+        };
+
+        datatypes
+            .into_iter()
+            .map(|datatype| (datatype.to_string(), (self.types)(datatype).to_string()))
+            .collect()
+    }
+}
+
+impl Display for CtGenStarterKit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.description)
+    }
+}
+
+impl CtGenProfile {
+    /// Create a new profile with the targets of a starter kit. The kit language type map holds the column types of the
+    /// given table, the JSON of its reflection like the `table` of the task context, or common column types without one
+    pub fn new_from_kit(path: &str, name: &str, kit: &CtGenStarterKit, table: Option<&Value>) -> CtGenProfile {
+        let mut profile = CtGenProfile::new(path, name);
+
+        if kit.is_blank() {
+            return profile;
+        }
+
+        profile.profile.target_dir = ".".to_string();
+        profile.profile.prompts = Vec::new();
+        profile.prompt = HashMap::new();
+
+        profile.profile.targets = kit.targets.iter().map(|(name, _, _)| name.to_string()).collect();
+        profile.target = kit
+            .targets
+            .iter()
+            .map(|(name, _, target)| (name.to_string(), CtGenTarget::new(name, target)))
+            .collect();
+
+        if let Some(language) = kit.language {
+            profile.profile.languages = vec![language.to_string()];
+            profile.language = HashMap::from([(language.to_string(), CtGenLanguage { types: kit.types(table) })]);
+        }
+
+        profile
+    }
+}
+
+/// Reflected columns of a table as name, datatype and nullability
+fn columns(table: &Value) -> impl Iterator<Item = (&str, &str, bool)> {
+    table.get("columns").and_then(Value::as_array).into_iter().flatten().map(|column| {
+        (
+            column.get("name").and_then(Value::as_str).unwrap_or_default(),
+            column.get("datatype").and_then(Value::as_str).unwrap_or_default(),
+            column.get("nullable").and_then(Value::as_bool).unwrap_or_default(),
+        )
+    })
+}
+
+/// Lowercase base type of a column type, like `int` for `int(11) unsigned`, and whether it is unsigned
+pub(crate) fn base_type(datatype: &str) -> (String, bool) {
+    let datatype = datatype.to_lowercase();
+    let base = if datatype.starts_with("tinyint(1)") {
+        "bool"
+    } else {
+        datatype.split(['(', ' ']).next().unwrap_or_default()
+    };
+
+    (base.to_string(), datatype.contains("unsigned"))
+}
+
+/// Language type of kits without a language
+fn untyped(_datatype: &str) -> &'static str {
+    ""
+}
+
+/// Rust type of a column type, as decoded by sqlx with its `chrono`, `rust_decimal` and `json` features
+fn rust_sqlx_type(datatype: &str) -> &'static str {
+    let (base, unsigned) = base_type(datatype);
+
+    match (base.as_str(), unsigned) {
+        ("bool" | "boolean", _) => "bool",
+        ("tinyint", true) => "u8",
+        ("tinyint", false) => "i8",
+        ("smallint", true) => "u16",
+        ("smallint", false) => "i16",
+        ("mediumint" | "int" | "integer", true) => "u32",
+        ("mediumint" | "int" | "integer", false) => "i32",
+        ("bigint", true) => "u64",
+        ("bigint", false) => "i64",
+        ("float", _) => "f32",
+        ("double" | "real", _) => "f64",
+        ("decimal" | "numeric", _) => "rust_decimal::Decimal",
+        ("date", _) => "chrono::NaiveDate",
+        ("time", _) => "chrono::NaiveTime",
+        ("datetime" | "timestamp", _) => "chrono::NaiveDateTime",
+        ("json", _) => "serde_json::Value",
+        ("bit" | "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob", _) => "Vec<u8>",
+        _ => "String",
+    }
+}
+
+/// Eloquent attribute cast of a column type
+fn php_laravel_cast(datatype: &str) -> &'static str {
+    match base_type(datatype).0.as_str() {
+        "bool" | "boolean" => "boolean",
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "year" => "integer",
+        "float" | "double" | "real" => "float",
+        "decimal" | "numeric" => "decimal:2",
+        "date" => "date",
+        "datetime" | "timestamp" => "datetime",
+        "json" => "array",
+        _ => "string",
+    }
+}
+
+/// Prisma scalar type of a column type
+fn ts_prisma_type(datatype: &str) -> &'static str {
+    match base_type(datatype).0.as_str() {
+        "bool" | "boolean" => "Boolean",
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "year" => "Int",
+        "bigint" => "BigInt",
+        "float" | "double" | "real" => "Float",
+        "decimal" | "numeric" => "Decimal",
+        "date" | "time" | "datetime" | "timestamp" => "DateTime",
+        "json" => "Json",
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" | "bit" => "Bytes",
+        _ => "String",
+    }
+}
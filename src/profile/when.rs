@@ -0,0 +1,247 @@
+use crate::consts::PLATFORMS;
+use crate::error::CtGenError;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::env;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, Debug, PartialEq)]
+/// `when` expression token
+enum Token {
+    Name(String),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+/// Split a `when` expression into tokens
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '!' => tokens.push(Token::Not),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '&' | '|' => {
+                if chars.next_if_eq(&c).is_none() {
+                    return Err(invalid(expression, &format!("expected `{}{}`", c, c)));
+                }
+
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            c if c.is_alphanumeric() || c == '_' => tokens.push(Token::Name(name(c, &mut chars))),
+            c => return Err(invalid(expression, &format!("unexpected `{}`", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read the rest of a platform or feature name
+fn name(first: char, chars: &mut Peekable<Chars>) -> String {
+    let mut name = first.to_string();
+
+    while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || matches!(c, '_' | '-')) {
+        name.push(c);
+    }
+
+    name
+}
+
+/// Message for an invalid `when` expression
+fn invalid(expression: &str, reason: &str) -> String {
+    format!("Invalid `when` expression `{}`: {}", expression, reason)
+}
+
+/// Recursive descent evaluator over `when` tokens. `||` binds looser than `&&`, which binds looser than `!`
+struct Evaluator<'a> {
+    expression: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+    platform: (&'a str, &'a str),
+    features: &'a BTreeMap<String, bool>,
+}
+
+impl Evaluator<'_> {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn or(&mut self) -> Result<bool, String> {
+        let mut value = self.and()?;
+
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            // evaluate both sides so errors surface whatever the platform
+            value |= self.and()?;
+        }
+
+        Ok(value)
+    }
+
+    fn and(&mut self) -> Result<bool, String> {
+        let mut value = self.not()?;
+
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            value &= self.not()?;
+        }
+
+        Ok(value)
+    }
+
+    fn not(&mut self) -> Result<bool, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.position += 1;
+            return Ok(!self.not()?);
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<bool, String> {
+        match self.next() {
+            Some(Token::Open) => {
+                let value = self.or()?;
+                self.close()?;
+                Ok(value)
+            }
+            Some(Token::Name(name)) if name == "feature" => {
+                if self.next() != Some(Token::Open) {
+                    return Err(invalid(self.expression, "expected `(` after `feature`"));
+                }
+
+                let Some(Token::Name(feature)) = self.next() else {
+                    return Err(invalid(self.expression, "expected a feature name"));
+                };
+
+                self.close()?;
+
+                Ok(self.features.get(&feature).copied().unwrap_or_default())
+            }
+            Some(Token::Name(name)) if PLATFORMS.contains(&name.as_str()) => Ok(name == self.platform.0 || name == self.platform.1),
+            Some(Token::Name(name)) => Err(invalid(
+                self.expression,
+                &format!(
+                    "unknown platform `{}`. Expected one of {}, or `feature(<name>)`",
+                    name,
+                    PLATFORMS.join(", ")
+                ),
+            )),
+            Some(token) => Err(invalid(self.expression, &format!("unexpected {:?}", token))),
+            None => Err(invalid(self.expression, "unexpected end")),
+        }
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        if self.next() != Some(Token::Close) {
+            return Err(invalid(self.expression, "expected `)`"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Evaluate a `when` expression like `unix && !feature(legacy)` for a platform, given as its OS and family like
+/// `("linux", "unix")`, and the enabled feature flags
+fn evaluate_on(expression: &str, platform: (&str, &str), features: &BTreeMap<String, bool>) -> Result<bool, String> {
+    let mut evaluator = Evaluator {
+        expression,
+        tokens: tokenize(expression)?,
+        position: 0,
+        platform,
+        features,
+    };
+
+    let value = evaluator.or()?;
+
+    if let Some(token) = evaluator.peek() {
+        return Err(invalid(expression, &format!("unexpected {:?}", token)));
+    }
+
+    Ok(value)
+}
+
+/// Evaluate a `when` expression on the platform ctgen runs on with the enabled feature flags. Platform names are true on
+/// that platform, `feature(<name>)` is true if the feature flag is enabled, and both combine with `!`, `&&`, `||` and
+/// parentheses
+pub fn evaluate(expression: &str, features: &BTreeMap<String, bool>) -> Result<bool> {
+    evaluate_on(expression, (env::consts::OS, env::consts::FAMILY), features).map_err(|e| CtGenError::ValidationError(e).into())
+}
+
+/// Check a `when` expression for syntax errors and unknown platforms
+pub fn validate(expression: &str) -> Result<(), String> {
+    evaluate_on(expression, (env::consts::OS, env::consts::FAMILY), &BTreeMap::new()).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(names: &[&str]) -> BTreeMap<String, bool> {
+        names.iter().map(|name| (name.to_string(), true)).collect()
+    }
+
+    #[test]
+    fn evaluates_platforms() {
+        let linux = ("linux", "unix");
+        let windows = ("windows", "windows");
+
+        assert!(evaluate_on("linux", linux, &features(&[])).unwrap());
+        assert!(evaluate_on("unix", linux, &features(&[])).unwrap());
+        assert!(!evaluate_on("unix", windows, &features(&[])).unwrap());
+        assert!(evaluate_on("macos || windows", windows, &features(&[])).unwrap());
+        assert!(evaluate_on("!windows", linux, &features(&[])).unwrap());
+    }
+
+    #[test]
+    fn evaluates_features() {
+        let linux = ("linux", "unix");
+
+        assert!(evaluate_on("feature(legacy)", linux, &features(&["legacy"])).unwrap());
+        assert!(!evaluate_on("unix && !feature(legacy)", linux, &features(&["legacy"])).unwrap());
+        assert!(evaluate_on(
+            "windows || (unix && feature(docker-compose))",
+            linux,
+            &features(&["docker-compose"])
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn binds_and_tighter_than_or() {
+        let windows = ("windows", "windows");
+
+        assert!(evaluate_on("windows || linux && feature(legacy)", windows, &features(&[])).unwrap());
+        assert!(!evaluate_on("(windows || linux) && feature(legacy)", windows, &features(&[])).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in [
+            "",
+            "linux &",
+            "linux ||",
+            "(linux",
+            "linux)",
+            "beos",
+            "feature legacy",
+            "feature()",
+            "linux windows",
+        ] {
+            assert!(validate(expression).is_err(), "{}", expression);
+        }
+    }
+}
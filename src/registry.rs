@@ -0,0 +1,148 @@
+use crate::manifest::CtGenManifest;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+/// Registration metadata of a profile, kept in `Profiles.toml` next to its config file path
+pub struct CtGenProfileRegistration {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Registration time, RFC3339
+    added: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Where the profile was registered from, like `init`, `add`, `copy of backend` or an imported archive path
+    source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// SHA-256 hash of the profile config file when registered
+    checksum: Option<String>,
+    #[serde(default, rename = "alias-of", skip_serializing_if = "Option::is_none")]
+    /// Name of the profile this entry stands for. Alias entries have no config file
+    alias_of: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Deprecation message, printed whenever the profile is used by this name
+    deprecated: Option<String>,
+}
+
+impl CtGenProfileRegistration {
+    /// Create registration metadata of a config file registered now
+    pub fn new(source: &str, content: &[u8]) -> Self {
+        Self {
+            added: Some(chrono::Utc::now().to_rfc3339()),
+            source: Some(source.to_string()),
+            checksum: Some(CtGenManifest::hash(content)),
+            ..Default::default()
+        }
+    }
+
+    /// Registration time, RFC3339
+    pub fn added(&self) -> Option<&str> {
+        self.added.as_deref()
+    }
+    /// Where the profile was registered from, like `init`, `add`, `copy of backend` or an imported archive path
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+    /// SHA-256 hash of the profile config file when registered
+    pub fn checksum(&self) -> Option<&str> {
+        self.checksum.as_deref()
+    }
+
+    /// Name of the profile this entry stands for. Alias entries have no config file
+    pub fn alias_of(&self) -> Option<&str> {
+        self.alias_of.as_deref()
+    }
+    /// Deprecation message, printed whenever the profile is used by this name
+    pub fn deprecated(&self) -> Option<&str> {
+        self.deprecated.as_deref()
+    }
+
+    /// Set or clear the deprecation message
+    pub fn set_deprecated(&mut self, deprecated: Option<&str>) -> &mut Self {
+        self.deprecated = deprecated.map(str::to_string);
+        self
+    }
+
+    /// Record the current config file content, after ctgen edited it
+    pub fn refresh(&mut self, content: &[u8]) {
+        self.checksum = Some(CtGenManifest::hash(content));
+    }
+
+    /// Check if the config file changed since it was registered. Profiles registered before checksums were kept are
+    /// never stale
+    pub fn is_stale(&self, content: &[u8]) -> bool {
+        self.checksum
+            .as_deref()
+            .is_some_and(|checksum| checksum != CtGenManifest::hash(content))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+/// Problem with a registered profile
+pub enum CtGenRegistryIssue {
+    /// The config file doesn't exist anymore
+    Missing,
+    /// Other profiles are registered with the same config file
+    Duplicate { profiles: Vec<String> },
+    /// The config file changed since the profile was registered
+    Stale,
+    /// The profile is deprecated
+    Deprecated { message: String },
+}
+
+impl Display for CtGenRegistryIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenRegistryIssue::Missing => write!(f, "config file not found"),
+            CtGenRegistryIssue::Duplicate { profiles } => write!(f, "same config file as {}", profiles.join(", ")),
+            CtGenRegistryIssue::Stale => write!(f, "config file changed since it was added"),
+            CtGenRegistryIssue::Deprecated { message } => write!(f, "deprecated: {}", message),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Registered profile with its registration metadata and problems
+pub struct CtGenRegistryStatus {
+    /// Registered profile name
+    name: String,
+    /// Profile config file path
+    file: String,
+    /// Registration metadata
+    registration: CtGenProfileRegistration,
+    /// Problems found with the registration
+    issues: Vec<CtGenRegistryIssue>,
+}
+
+impl CtGenRegistryStatus {
+    /// Create a registered profile status
+    pub fn new(name: &str, file: &str, registration: CtGenProfileRegistration, issues: Vec<CtGenRegistryIssue>) -> Self {
+        Self {
+            name: name.to_string(),
+            file: file.to_string(),
+            registration,
+            issues,
+        }
+    }
+
+    /// Registered profile name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Profile config file path
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+    /// Registration metadata
+    pub fn registration(&self) -> &CtGenProfileRegistration {
+        &self.registration
+    }
+    /// Problems found with the registration
+    pub fn issues(&self) -> &Vec<CtGenRegistryIssue> {
+        &self.issues
+    }
+
+    /// Check if the config file exists
+    pub fn is_missing(&self) -> bool {
+        self.issues.iter().any(|issue| matches!(issue, CtGenRegistryIssue::Missing))
+    }
+}
@@ -0,0 +1,721 @@
+use crate::consts::{CONFIG_NAME_DEFAULT, SERVER_IDLE_TIMEOUT_DEFAULT, SERVER_TOKEN_LENGTH};
+use crate::error::CtGenError;
+use crate::profile::{CtGenProfile, CtGenProfileConfigOverrides};
+use crate::task::{CtGenConflictPolicy, CtGenRunMode, CtGenTargetOutput, CtGenTargetResult, CtGenTask};
+use crate::CtGen;
+use anyhow::Result;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::stream::{self, Stream};
+use rand::distributions::{Alphanumeric, DistString};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, Mutex};
+
+/// JSON-RPC error code for unknown methods
+const RPC_METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for malformed method params
+const RPC_INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code for failed calls
+const RPC_CALL_FAILED: i64 = -32000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// JSON-RPC 2.0 request
+pub struct CtGenRpcRequest {
+    #[serde(default)]
+    /// Request id, echoed back in the response
+    id: Value,
+    /// Method name
+    method: String,
+    #[serde(default)]
+    /// Method params object
+    params: Value,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// JSON-RPC 2.0 response
+pub struct CtGenRpcResponse {
+    /// Protocol version, always `2.0`
+    jsonrpc: String,
+    /// Request id
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Method result
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Method error
+    error: Option<CtGenRpcError>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// JSON-RPC 2.0 error object
+pub struct CtGenRpcError {
+    /// Error code
+    code: i64,
+    /// Error message
+    message: String,
+}
+
+impl CtGenRpcError {
+    fn new(code: i64, message: String) -> Self {
+        Self { code, message }
+    }
+}
+
+impl From<anyhow::Error> for CtGenRpcError {
+    fn from(e: anyhow::Error) -> Self {
+        CtGenRpcError::new(RPC_CALL_FAILED, e.to_string())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Server event, streamed to every `/events` subscriber
+pub struct CtGenServerEvent {
+    /// Event name, for example `task-started`
+    event: String,
+    /// Task id, if the event concerns a task
+    task_id: Option<String>,
+    /// Event details
+    data: Value,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct TaskStartParams {
+    #[serde(default)]
+    profile: Option<String>,
+    #[serde(default)]
+    context_dir: Option<String>,
+    #[serde(default)]
+    table: Option<String>,
+    #[serde(default)]
+    env_file: Option<String>,
+    #[serde(default)]
+    env_var: Option<String>,
+    #[serde(default)]
+    dsn: Option<String>,
+    #[serde(default)]
+    target_dir: Option<String>,
+    #[serde(default)]
+    deterministic: bool,
+    #[serde(default)]
+    strict: bool,
+    #[serde(default)]
+    no_rollback: bool,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default)]
+    prompts: HashMap<String, Value>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct TaskParams {
+    task_id: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct TaskAnswerParams {
+    task_id: String,
+    prompt_id: String,
+    answer: Value,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct TaskAnswersParams {
+    task_id: String,
+    answers: Value,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct EditorGenerateParams {
+    file: String,
+    #[serde(default)]
+    target: Option<String>,
+    #[serde(flatten)]
+    task: TaskStartParams,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct TaskRunParams {
+    task_id: String,
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    on_conflict: CtGenConflictPolicy,
+}
+
+/// Open server task
+struct CtGenServerTask {
+    /// Task
+    task: Arc<Mutex<CtGenTask<'static>>>,
+    /// Time of the last call on the task
+    last_used: Instant,
+}
+
+/// Local HTTP server driving ctgen tasks over JSON-RPC
+pub struct CtGenServer {
+    /// Profile registry
+    ctgen: Mutex<CtGen>,
+    /// Context directory of tasks. Tasks may use a directory inside it
+    context_dir: String,
+    /// Token clients send as `Authorization: Bearer <token>`
+    token: String,
+    /// Address the server listens on
+    address: String,
+    /// Flag that shows whether tasks may connect to a DSN given in params
+    allow_dsn: bool,
+    /// Time after which a task without calls is closed
+    idle_timeout: Duration,
+    /// Open tasks by task id
+    tasks: Mutex<HashMap<String, CtGenServerTask>>,
+    /// Last issued task id
+    task_counter: AtomicUsize,
+    /// Event channel
+    events: broadcast::Sender<CtGenServerEvent>,
+}
+
+impl CtGenServer {
+    /// Create a new server. Tasks run in the given context directory, and every request must carry the given token
+    pub fn new(ctgen: CtGen, context_dir: &str, token: &str) -> Self {
+        let (events, _) = broadcast::channel(256);
+
+        Self {
+            ctgen: Mutex::new(ctgen),
+            context_dir: context_dir.to_string(),
+            token: token.to_string(),
+            address: String::new(),
+            allow_dsn: false,
+            idle_timeout: Duration::from_secs(SERVER_IDLE_TIMEOUT_DEFAULT),
+            tasks: Mutex::new(HashMap::new()),
+            task_counter: AtomicUsize::new(0),
+            events,
+        }
+    }
+
+    /// Generate a random token
+    pub fn generate_token() -> String {
+        Alphanumeric.sample_string(&mut rand::thread_rng(), SERVER_TOKEN_LENGTH)
+    }
+
+    /// Allow tasks to connect to a DSN given in params, instead of only the profile database
+    pub fn set_allow_dsn(&mut self, allow_dsn: bool) -> &mut Self {
+        self.allow_dsn = allow_dsn;
+        self
+    }
+
+    /// Set the time after which a task without calls is closed
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) -> &mut Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Listen on the given address until the process is stopped
+    pub async fn serve(mut self, address: &str) -> Result<()> {
+        let listener = TcpListener::bind(address)
+            .await
+            .map_err(|e| CtGenError::InitError(format!("Failed to listen on {}: {}", address, e)))?;
+
+        self.address = address.to_string();
+
+        let server = Arc::new(self);
+
+        // close idle tasks, so abandoned tasks don't hold their reflection forever
+        let evicting = Arc::clone(&server);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(evicting.idle_timeout.min(Duration::from_secs(60)));
+
+            loop {
+                interval.tick().await;
+                evicting.close_idle_tasks().await;
+            }
+        });
+
+        let app = Router::new()
+            .route("/rpc", post(CtGenServer::handle_rpc))
+            .route("/events", get(CtGenServer::handle_events))
+            .layer(middleware::from_fn_with_state(Arc::clone(&server), CtGenServer::guard))
+            .with_state(server);
+
+        axum::serve(listener, app)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Server failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Reject requests without the token, and requests for another host or from another origin, which are web pages
+    /// reaching the server through the browser
+    async fn guard(State(server): State<Arc<CtGenServer>>, request: Request, next: Next) -> Response {
+        let headers = request.headers();
+
+        if !headers
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .is_some_and(|host| server.is_local_host(host))
+        {
+            return (StatusCode::FORBIDDEN, "Host not allowed").into_response();
+        }
+
+        if let Some(origin) = headers.get(header::ORIGIN) {
+            let host = origin
+                .to_str()
+                .ok()
+                .and_then(|origin| origin.split_once("://"))
+                .map(|(_scheme, host)| host);
+
+            if !host.is_some_and(|host| server.is_local_host(host)) {
+                return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+            }
+        }
+
+        if !server.is_authorized(headers) {
+            return (StatusCode::UNAUTHORIZED, "Missing or invalid token").into_response();
+        }
+
+        next.run(request).await
+    }
+
+    /// Check if a host, with an optional port, is the server address or a loopback name
+    fn is_local_host(&self, host: &str) -> bool {
+        if host == self.address {
+            return true;
+        }
+
+        let name = match host.rsplit_once(':') {
+            Some((name, port)) if !name.ends_with(':') && port.chars().all(|c| c.is_ascii_digit()) => name,
+            _ => host,
+        };
+
+        matches!(name, "localhost" | "127.0.0.1" | "[::1]")
+    }
+
+    /// Check the bearer token of a request. Compares every byte, so the time taken doesn't tell how much matched
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|authorization| authorization.to_str().ok())
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+        else {
+            return false;
+        };
+
+        token.len() == self.token.len() && token.bytes().zip(self.token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+
+    /// JSON-RPC endpoint
+    async fn handle_rpc(State(server): State<Arc<CtGenServer>>, Json(request): Json<CtGenRpcRequest>) -> Json<CtGenRpcResponse> {
+        let (result, error) = match server.call(&request.method, request.params).await {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e)),
+        };
+
+        Json(CtGenRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result,
+            error,
+        })
+    }
+
+    /// Server-sent events endpoint
+    async fn handle_events(State(server): State<Arc<CtGenServer>>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+        let events = stream::unfold(server.events.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        let data = serde_json::to_string(&event).unwrap_or_default();
+
+                        return Some((Ok(Event::default().event(&event.event).data(data)), receiver));
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Sse::new(events).keep_alive(KeepAlive::default())
+    }
+
+    /// Dispatch a method call
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value, CtGenRpcError> {
+        match method {
+            "profiles.list" => Ok(self.list_profiles().await),
+            "task.start" => Ok(self.start_task(CtGenServer::params(params)?).await?),
+            "task.prompt" => Ok(self.task_prompt(CtGenServer::params(params)?).await?),
+            "task.answer" => Ok(self.answer_prompt(CtGenServer::params(params)?).await?),
+            "task.prompts" => Ok(self.task_prompts(CtGenServer::params(params)?).await?),
+            "task.answers" => Ok(self.answer_prompts(CtGenServer::params(params)?).await?),
+            "task.outputs" => Ok(self.task_outputs(CtGenServer::params(params)?).await?),
+            "task.run" => Ok(self.run_task(CtGenServer::params(params)?).await?),
+            "task.close" => Ok(self.close_task(CtGenServer::params(params)?).await?),
+            "editor.generate" => Ok(self.editor_generate(CtGenServer::params(params)?).await?),
+            _ => Err(CtGenRpcError::new(RPC_METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+
+    /// Parse method params
+    fn params<T: DeserializeOwned>(params: Value) -> Result<T, CtGenRpcError> {
+        serde_json::from_value(params).map_err(|e| CtGenRpcError::new(RPC_INVALID_PARAMS, format!("Invalid params: {}", e)))
+    }
+
+    /// Send event to subscribers, if any
+    fn emit(&self, event: &str, task_id: Option<&str>, data: Value) {
+        let _ = self.events.send(CtGenServerEvent {
+            event: event.to_string(),
+            task_id: task_id.map(str::to_string),
+            data,
+        });
+    }
+
+    /// Get open task by id
+    async fn task(&self, task_id: &str) -> Result<Arc<Mutex<CtGenTask<'static>>>> {
+        let mut tasks = self.tasks.lock().await;
+
+        let task = tasks
+            .get_mut(task_id)
+            .ok_or_else(|| CtGenError::ValidationError(format!("Task `{}` not found", task_id)))?;
+        task.last_used = Instant::now();
+
+        Ok(Arc::clone(&task.task))
+    }
+
+    /// Close tasks without calls for longer than the idle timeout
+    async fn close_idle_tasks(&self) {
+        let mut tasks = self.tasks.lock().await;
+
+        let idle = tasks
+            .iter()
+            .filter(|(_task_id, task)| task.last_used.elapsed() >= self.idle_timeout)
+            .map(|(task_id, _task)| task_id.to_string())
+            .collect::<Vec<String>>();
+
+        for task_id in idle {
+            tasks.remove(&task_id);
+
+            self.emit("task-closed", Some(&task_id), json!({"reason": "idle"}));
+        }
+    }
+
+    /// List registered profiles
+    async fn list_profiles(&self) -> Value {
+        let ctgen = self.ctgen.lock().await;
+
+        Value::from(
+            ctgen
+                .get_profiles()
+                .iter()
+                .map(|(name, file)| json!({"name": name, "file": file}))
+                .collect::<Vec<Value>>(),
+        )
+    }
+
+    /// Load profile, create task and pre-answer given prompts
+    async fn start_task(&self, params: TaskStartParams) -> Result<Value> {
+        let profile_name = params.profile.clone().unwrap_or_else(|| CONFIG_NAME_DEFAULT.to_string());
+
+        let mut task = self.create_task(params).await?;
+
+        let task_id = (self.task_counter.fetch_add(1, Ordering::Relaxed) + 1).to_string();
+
+        let prompt = CtGenServer::next_prompt(&mut task).await?;
+
+        self.tasks.lock().await.insert(
+            task_id.clone(),
+            CtGenServerTask {
+                task: Arc::new(Mutex::new(task)),
+                last_used: Instant::now(),
+            },
+        );
+
+        self.emit("task-started", Some(&task_id), json!({"profile": profile_name}));
+
+        Ok(json!({"task_id": task_id, "prompt": prompt}))
+    }
+
+    /// Resolve a path, absolute or relative to a dir
+    fn resolve_path(dir: &str, path: &str) -> String {
+        if Path::new(path).is_absolute() {
+            path.to_string()
+        } else {
+            CtGen::get_filepath(dir, path)
+        }
+    }
+
+    /// Resolve the context dir of a task, absolute or relative to the server context dir. It must be inside the server
+    /// context dir
+    async fn task_context_dir(&self, context_dir: Option<&str>) -> Result<String> {
+        let context_dir = match context_dir {
+            Some(context_dir) => CtGen::get_realpath(&CtGenServer::resolve_path(&self.context_dir, context_dir)).await?,
+            None => self.context_dir.clone(),
+        };
+
+        if !CtGen::is_path_within(&self.context_dir, &context_dir) {
+            return Err(CtGenError::ValidationError(format!(
+                "Context dir {} is outside of the server context dir {}",
+                context_dir, self.context_dir
+            ))
+            .into());
+        }
+
+        Ok(context_dir)
+    }
+
+    /// Load profile, create task and pre-answer given prompts, without registering the task. Tasks are kept inside the
+    /// server context dir, and only connect to the profile database unless the server allows a DSN in params
+    async fn create_task(&self, params: TaskStartParams) -> Result<CtGenTask<'static>> {
+        let profile_name = params.profile.as_deref().unwrap_or(CONFIG_NAME_DEFAULT);
+
+        let context_dir = self.task_context_dir(params.context_dir.as_deref()).await?;
+
+        if params.dsn.is_some() && !self.allow_dsn {
+            return Err(CtGenError::ValidationError("DSN params are not allowed. Start the server with --allow-dsn".to_string()).into());
+        }
+
+        let env_file = params
+            .env_file
+            .map(|env_file| {
+                let env_file = CtGen::normalize_path(&CtGenServer::resolve_path(&context_dir, &env_file));
+
+                if CtGen::is_path_within(&context_dir, &env_file.to_string_lossy()) {
+                    Ok(env_file.to_string_lossy().to_string())
+                } else {
+                    Err(CtGenError::ValidationError(format!(
+                        "Env file {} is outside of the context dir",
+                        env_file.display()
+                    )))
+                }
+            })
+            .transpose()?;
+
+        let profile: CtGenProfile = self.ctgen.lock().await.set_current_profile(profile_name).await?.clone();
+
+        let profile_overrides = CtGenProfileConfigOverrides::new(
+            env_file,
+            params.env_var,
+            params.dsn,
+            params.target_dir,
+            None,
+            None,
+            None,
+            params.deterministic,
+            params.strict,
+            params.no_rollback,
+            false,
+            params.features,
+        );
+
+        let mut task = CtGenTask::new(&profile, &context_dir, params.table.as_deref(), Some(profile_overrides)).await?;
+
+        // follow prompt order, so the database is set before the table is validated
+        for prompt in task.prompts_unanswered() {
+            if let Some(answer) = params.prompts.get(prompt.prompt_id()) {
+                task.set_prompt_answer(&prompt, answer.clone()).await?;
+            }
+        }
+
+        Ok(task)
+    }
+
+    /// Render the targets that write to a given file, or a given target, in one call. Every asked prompt must be answered in params
+    async fn editor_generate(&self, params: EditorGenerateParams) -> Result<Value> {
+        if params.task.table.is_none() {
+            return Err(CtGenError::ValidationError("Table name is required".to_string()).into());
+        }
+
+        let context_dir = self.task_context_dir(params.task.context_dir.as_deref()).await?;
+
+        let mut task = self.create_task(params.task).await?;
+
+        if let Some(prompt) = CtGenServer::next_prompt(&mut task).await? {
+            return Err(CtGenError::ValidationError(format!(
+                "Prompt `{}` has no answer. Pass it in prompts",
+                prompt["id"].as_str().unwrap_or_default()
+            ))
+            .into());
+        }
+
+        let outputs = if let Some(target_name) = params.target.as_deref() {
+            task.plan_target(target_name).await?
+        } else {
+            let file = if Path::new(&params.file).is_absolute() {
+                CtGen::normalize_path(&params.file)
+            } else {
+                CtGen::normalize_path(&CtGen::get_filepath(&context_dir, &params.file))
+            };
+
+            task.plan()
+                .await?
+                .into_iter()
+                .filter(|output| CtGen::normalize_path(output.path()) == file)
+                .collect()
+        };
+
+        if outputs.is_empty() {
+            return Err(CtGenError::ValidationError(format!("No target renders to file {}", params.file)).into());
+        }
+
+        Ok(Value::from(
+            outputs.iter().map(CtGenServer::output_content_value).collect::<Vec<Value>>(),
+        ))
+    }
+
+    /// Next prompt of a task
+    async fn task_prompt(&self, params: TaskParams) -> Result<Value> {
+        let task = self.task(&params.task_id).await?;
+        let mut task = task.lock().await;
+
+        Ok(json!({"prompt": CtGenServer::next_prompt(&mut task).await?}))
+    }
+
+    /// Answer a prompt and get the next one
+    async fn answer_prompt(&self, params: TaskAnswerParams) -> Result<Value> {
+        let task = self.task(&params.task_id).await?;
+        let mut task = task.lock().await;
+
+        let prompt = task
+            .prompts_unanswered()
+            .into_iter()
+            .find(|prompt| prompt.prompt_id() == params.prompt_id)
+            .ok_or_else(|| CtGenError::ValidationError(format!("Prompt `{}` is not pending", params.prompt_id)))?;
+
+        task.set_prompt_answer(&prompt, params.answer).await?;
+
+        self.emit("prompt-answered", Some(&params.task_id), json!({"prompt_id": params.prompt_id}));
+
+        Ok(json!({"prompt": CtGenServer::next_prompt(&mut task).await?}))
+    }
+
+    /// Every prompt of a task that can be answered now
+    async fn task_prompts(&self, params: TaskParams) -> Result<Value> {
+        let task = self.task(&params.task_id).await?;
+        let mut task = task.lock().await;
+
+        task.export_prompts().await
+    }
+
+    /// Answer several prompts at once and get the prompts that can be answered next
+    async fn answer_prompts(&self, params: TaskAnswersParams) -> Result<Value> {
+        let task = self.task(&params.task_id).await?;
+        let mut task = task.lock().await;
+
+        let prompt_ids = params
+            .answers
+            .as_object()
+            .map(|answers| answers.keys().cloned().collect::<Vec<String>>());
+
+        task.import_answers(params.answers).await?;
+
+        for prompt_id in prompt_ids.unwrap_or_default() {
+            self.emit("prompt-answered", Some(&params.task_id), json!({"prompt_id": prompt_id}));
+        }
+
+        task.export_prompts().await
+    }
+
+    /// Render all targets without writing and return their content
+    async fn task_outputs(&self, params: TaskParams) -> Result<Value> {
+        let task = self.task(&params.task_id).await?;
+        let task = task.lock().await;
+
+        let outputs = task.plan().await?;
+
+        Ok(Value::from(
+            outputs.iter().map(CtGenServer::output_content_value).collect::<Vec<Value>>(),
+        ))
+    }
+
+    /// Run task, or plan it in dry-run mode
+    async fn run_task(&self, params: TaskRunParams) -> Result<Value> {
+        let task = self.task(&params.task_id).await?;
+        let task = task.lock().await;
+
+        let results = if params.dry_run {
+            task.run(CtGenRunMode::DryRun).await?
+        } else {
+            // files modified by hand since generation are resolved by the request policy before anything is written
+            let outputs = task.resolve_conflicts(task.plan().await?, params.on_conflict).await?;
+
+            task.write(&outputs).await?
+        };
+        let outputs = results.iter().map(CtGenServer::result_value).collect::<Vec<Value>>();
+
+        self.emit(
+            "task-finished",
+            Some(&params.task_id),
+            json!({"dry_run": params.dry_run, "outputs": &outputs}),
+        );
+
+        Ok(Value::from(outputs))
+    }
+
+    /// Drop task
+    async fn close_task(&self, params: TaskParams) -> Result<Value> {
+        if self.tasks.lock().await.remove(&params.task_id).is_none() {
+            return Err(CtGenError::ValidationError(format!("Task `{}` not found", params.task_id)).into());
+        }
+
+        self.emit("task-closed", Some(&params.task_id), Value::Null);
+
+        Ok(Value::Bool(true))
+    }
+
+    /// Target output summary
+    fn output_value(output: &CtGenTargetOutput) -> Value {
+        json!({
+            "name": output.name(),
+            "file": output.file(),
+            "size": output.size(),
+            "overwrite": output.overwrite(),
+            "action": output.action().to_string(),
+            "content": output.is_stdout().then(|| output.content()),
+        })
+    }
+
+    /// Target output summary with the run status and formatter outcome
+    fn result_value(result: &CtGenTargetResult) -> Value {
+        let mut value = CtGenServer::output_value(result.output());
+        value["status"] = Value::from(result.status().to_string());
+        value["formatter"] = result.formatter().map_or(Value::Null, |formatter| {
+            json!({
+                "command": formatter.command(),
+                "success": formatter.success(),
+                "stdout": formatter.stdout(),
+                "stderr": formatter.stderr(),
+            })
+        });
+
+        value
+    }
+
+    /// Target output summary with rendered content
+    fn output_content_value(output: &CtGenTargetOutput) -> Value {
+        let mut value = CtGenServer::output_value(output);
+        value["content"] = Value::from(output.content());
+
+        value
+    }
+
+    /// Render the next prompt to ask. Prompts whose condition is not met are answered with an empty value on the way
+    async fn next_prompt(task: &mut CtGenTask<'static>) -> Result<Option<Value>> {
+        while let Some(prompt) = task.prompts_unanswered().into_iter().next() {
+            if let Some(document) = task.prompt_document(&prompt).await? {
+                return Ok(Some(document));
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,392 @@
+use crate::consts::{SNAPSHOTS_DIR, SNAPSHOT_CONTEXT_FILE, SNAPSHOT_EXPECTED_DIR};
+use crate::error::CtGenError;
+use crate::profile::CtGenProfile;
+use crate::task::renderer;
+use crate::CtGen;
+use anyhow::Result;
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use walkdir::WalkDir;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// State of a rendered output compared to its expected output
+pub enum CtGenSnapshotStatus {
+    /// Rendered output matches the expected output
+    Passed,
+    /// Rendered output differs from the expected output
+    Changed,
+    /// Output is rendered, but has no expected output yet
+    Added,
+    /// Expected output is no longer rendered
+    Removed,
+}
+
+impl Display for CtGenSnapshotStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenSnapshotStatus::Passed => write!(f, "passed"),
+            CtGenSnapshotStatus::Changed => write!(f, "changed"),
+            CtGenSnapshotStatus::Added => write!(f, "added"),
+            CtGenSnapshotStatus::Removed => write!(f, "removed"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Snapshot comparison of a single output file
+pub struct CtGenSnapshotFile {
+    /// Test case name
+    case: String,
+    /// Output file path, relative to the expected outputs dir of the case
+    file: String,
+    /// Comparison state
+    status: CtGenSnapshotStatus,
+    /// Number of lines the rendered output adds
+    insertions: usize,
+    /// Number of lines the rendered output removes
+    deletions: usize,
+    /// Unified diff from the expected to the rendered output. Empty if passed
+    diff: String,
+}
+
+impl CtGenSnapshotFile {
+    /// Test case name
+    pub fn case(&self) -> &str {
+        &self.case
+    }
+    /// Output file path, relative to the expected outputs dir of the case
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+    /// Comparison state
+    pub fn status(&self) -> CtGenSnapshotStatus {
+        self.status
+    }
+    /// Number of lines the rendered output adds
+    pub fn insertions(&self) -> usize {
+        self.insertions
+    }
+    /// Number of lines the rendered output removes
+    pub fn deletions(&self) -> usize {
+        self.deletions
+    }
+    /// Unified diff from the expected to the rendered output. Empty if passed
+    pub fn diff(&self) -> &str {
+        &self.diff
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// Snapshot test results of a profile
+pub struct CtGenSnapshotReport {
+    /// Number of test cases
+    cases: usize,
+    /// Output files of every test case, sorted by case and file
+    files: Vec<CtGenSnapshotFile>,
+    /// Flag that shows whether expected outputs were rewritten
+    updated: bool,
+}
+
+impl CtGenSnapshotReport {
+    /// Number of test cases
+    pub fn cases(&self) -> usize {
+        self.cases
+    }
+    /// Output files of every test case, sorted by case and file
+    pub fn files(&self) -> &Vec<CtGenSnapshotFile> {
+        &self.files
+    }
+    /// Flag that shows whether expected outputs were rewritten
+    pub fn updated(&self) -> bool {
+        self.updated
+    }
+
+    /// Check if every rendered output matches its expected output
+    pub fn is_passed(&self) -> bool {
+        self.files.iter().all(|file| file.status == CtGenSnapshotStatus::Passed)
+    }
+}
+
+impl Display for CtGenSnapshotReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for file in self.files.iter().filter(|file| file.status != CtGenSnapshotStatus::Passed) {
+            writeln!(
+                f,
+                "{:>8} {}/{} (+{} -{})",
+                file.status, file.case, file.file, file.insertions, file.deletions
+            )?;
+        }
+
+        let failed = self.files.iter().filter(|file| file.status != CtGenSnapshotStatus::Passed).count();
+
+        write!(
+            f,
+            "{} cases, {} outputs, {} {}",
+            self.cases,
+            self.files.len(),
+            failed,
+            if self.updated { "updated" } else { "failed" }
+        )
+    }
+}
+
+/// Render every target of a profile against a test case context. Returns outputs by target file
+fn render_case(handlebars: &Handlebars, profile: &CtGenProfile, context: &Value) -> Result<BTreeMap<String, String>> {
+    let mut outputs = BTreeMap::new();
+
+    for target_name in profile.targets() {
+        if let Some(target) = profile.target(target_name) {
+            let mut target_context = context.clone();
+
+            if let Some(target_context) = target_context.as_object_mut() {
+                target_context.insert("params".to_string(), serde_json::to_value(target.params())?);
+            }
+
+            // enumerating targets render once per item
+            let item_contexts: Vec<Value> = if let Some(enumerate) = target.enumerate() {
+                let rendered = handlebars
+                    .render_template(enumerate, &target_context)
+                    .map_err(renderer::render_error)?;
+
+                renderer::enumerate_items(&rendered)
+                    .into_iter()
+                    .map(|item| {
+                        let mut item_context = target_context.clone();
+
+                        if let Some(item_context) = item_context.as_object_mut() {
+                            item_context.insert("item".to_string(), item);
+                        }
+
+                        item_context
+                    })
+                    .collect()
+            } else {
+                vec![target_context]
+            };
+
+            for target_context in item_contexts {
+                if let Some(condition) = target.condition() {
+                    let evaluated_condition = handlebars
+                        .render_template(condition, &target_context)
+                        .map_err(renderer::render_error)?;
+
+                    if evaluated_condition.trim() != "1" {
+                        continue;
+                    }
+                }
+
+                let content = if target.is_copy() {
+                    let source_file = profile.target_source_file(target).unwrap_or_default();
+                    let asset = std::fs::read(&source_file)
+                        .map_err(|e| CtGenError::RuntimeError(format!("Failed to read source file {}: {}", source_file, e)))?;
+
+                    String::from_utf8_lossy(&asset).into_owned()
+                } else if let Some((open, close)) = target.delimiters() {
+                    handlebars
+                        .render(&renderer::delimited_template_name(target.template(), open, close), &target_context)
+                        .map_err(renderer::render_error)?
+                } else {
+                    handlebars
+                        .render(target.template(), &target_context)
+                        .map_err(renderer::render_error)?
+                };
+
+                let target_file = handlebars
+                    .render_template(target.target(), &target_context)
+                    .map_err(renderer::render_error)?;
+
+                outputs.insert(target_file, content);
+            }
+        }
+    }
+
+    Ok(outputs)
+}
+
+/// Compare rendered output with expected output
+fn compare(case: &str, file: &str, expected: Option<&str>, rendered: Option<&str>) -> CtGenSnapshotFile {
+    let status = match (expected, rendered) {
+        (Some(expected), Some(rendered)) if expected == rendered => CtGenSnapshotStatus::Passed,
+        (Some(_), Some(_)) => CtGenSnapshotStatus::Changed,
+        (None, _) => CtGenSnapshotStatus::Added,
+        (_, None) => CtGenSnapshotStatus::Removed,
+    };
+
+    let expected = expected.unwrap_or_default();
+    let rendered = rendered.unwrap_or_default();
+
+    let text_diff = TextDiff::from_lines(expected, rendered);
+
+    let mut insertions = 0;
+    let mut deletions = 0;
+
+    for change in text_diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => insertions += 1,
+            ChangeTag::Delete => deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+
+    let diff = if status == CtGenSnapshotStatus::Passed {
+        String::new()
+    } else {
+        text_diff
+            .unified_diff()
+            .header(&format!("expected/{}", file), &format!("rendered/{}", file))
+            .to_string()
+    };
+
+    CtGenSnapshotFile {
+        case: case.to_string(),
+        file: file.to_string(),
+        status,
+        insertions,
+        deletions,
+        diff,
+    }
+}
+
+/// Load expected outputs of a test case by file path relative to the expected outputs dir
+async fn load_expected(expected_dir: &str) -> Result<BTreeMap<String, String>> {
+    let mut expected = BTreeMap::new();
+
+    for expected_path in WalkDir::new(expected_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+    {
+        if let Ok(expected_file) = expected_path.strip_prefix(expected_dir) {
+            let expected_file = expected_file
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            let content = tokio::fs::read(&expected_path)
+                .await
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to read expected output {}: {}", expected_path.display(), e)))?;
+
+            expected.insert(expected_file, String::from_utf8_lossy(&content).into_owned());
+        }
+    }
+
+    Ok(expected)
+}
+
+/// Rewrite expected output of a test case to match the rendered output. Files resolving outside of the expected dir are
+/// rejected, so a target path rendering `..` can't touch other files
+async fn update_expected(expected_dir: &str, file: &CtGenSnapshotFile, rendered: Option<&str>) -> Result<()> {
+    let expected_file = CtGen::get_filepath(expected_dir, file.file());
+
+    if !CtGen::is_path_within(expected_dir, &expected_file) {
+        return Err(CtGenError::ValidationError(format!(
+            "Expected output {} resolves outside of the expected dir {}",
+            file.file(),
+            expected_dir
+        ))
+        .into());
+    }
+
+    if let Some(rendered) = rendered {
+        if let Some(parent) = Path::new(&expected_file).parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&expected_file, rendered)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to write expected output {}: {}", expected_file, e)))?;
+    } else {
+        tokio::fs::remove_file(&expected_file)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to remove expected output {}: {}", expected_file, e)))?;
+    }
+
+    Ok(())
+}
+
+/// Render profile targets against every test case context under the profile `tests` dir and compare the outputs with
+/// the expected ones. With `update`, expected outputs are rewritten to match
+pub async fn test(profile: &CtGenProfile, update: bool) -> Result<CtGenSnapshotReport> {
+    let snapshots_dir = CtGen::get_filepath(profile.context_dir(), SNAPSHOTS_DIR);
+
+    if !CtGen::file_exists(&snapshots_dir).await {
+        return Err(CtGenError::ValidationError(format!(
+            "Profile {} has no test cases. Add a {} file per case under {}",
+            profile.name(),
+            SNAPSHOT_CONTEXT_FILE,
+            snapshots_dir
+        ))
+        .into());
+    }
+
+    let mut cases = WalkDir::new(&snapshots_dir)
+        .min_depth(1)
+        .max_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect::<Vec<String>>();
+    cases.sort();
+
+    let handlebars = renderer::init_renderer(profile)?;
+
+    let mut report = CtGenSnapshotReport {
+        updated: update,
+        ..Default::default()
+    };
+
+    for case in cases {
+        let case_dir = CtGen::get_filepath(&snapshots_dir, &case);
+        let context_file = CtGen::get_filepath(&case_dir, SNAPSHOT_CONTEXT_FILE);
+
+        if !CtGen::file_exists(&context_file).await {
+            continue;
+        }
+
+        let content = tokio::fs::read_to_string(&context_file)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to read context file {}: {}", context_file, e)))?;
+
+        let context: Value = serde_json::from_str(&content)
+            .map_err(|e| CtGenError::ValidationError(format!("Invalid context file {}: {}", context_file, e)))?;
+
+        let rendered = render_case(&handlebars, profile, &context)
+            .map_err(|e| CtGenError::ValidationError(format!("Failed to render test case {}: {}", case, e)))?;
+
+        let expected_dir = CtGen::get_filepath(&case_dir, SNAPSHOT_EXPECTED_DIR);
+        let expected = load_expected(&expected_dir).await?;
+
+        let mut files = rendered.keys().chain(expected.keys()).collect::<Vec<&String>>();
+        files.sort();
+        files.dedup();
+
+        for file in files {
+            let snapshot_file = compare(
+                &case,
+                file,
+                expected.get(file).map(String::as_str),
+                rendered.get(file).map(String::as_str),
+            );
+
+            if update && snapshot_file.status != CtGenSnapshotStatus::Passed {
+                update_expected(&expected_dir, &snapshot_file, rendered.get(file).map(String::as_str)).await?;
+            }
+
+            report.files.push(snapshot_file);
+        }
+
+        report.cases += 1;
+    }
+
+    Ok(report)
+}
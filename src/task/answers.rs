@@ -0,0 +1,124 @@
+use crate::error::CtGenError;
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+/// Prompt answers by prompt id, including the built-in database, table and language prompts. Saved to and loaded from
+/// `.json` or `.toml` answer files for reproducible headless runs
+pub struct CtGenAnswers {
+    /// Answers by prompt id, in prompt order
+    answers: IndexMap<String, Value>,
+}
+
+/// Answer file format, picked by file extension
+enum CtGenAnswersFormat {
+    Json,
+    Toml,
+}
+
+impl CtGenAnswersFormat {
+    /// Answer file format of a file path
+    fn of(file: &str) -> Result<Self> {
+        match Path::new(file).extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Ok(CtGenAnswersFormat::Json),
+            Some("toml") => Ok(CtGenAnswersFormat::Toml),
+            _ => Err(CtGenError::ValidationError(format!("Unsupported answers file {}. Expected a .json or .toml file", file)).into()),
+        }
+    }
+}
+
+impl CtGenAnswers {
+    /// Load answers from a `.json` or `.toml` file. Numbers and booleans are taken as their text, like `--prompt` values
+    pub async fn load(file: &str) -> Result<Self> {
+        let format = CtGenAnswersFormat::of(file)?;
+
+        let content = tokio::fs::read_to_string(file)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to load answers file {}: {}", file, e)))?;
+
+        let answers: IndexMap<String, Value> = match format {
+            CtGenAnswersFormat::Json => serde_json::from_str(&content)
+                .map_err(|e| CtGenError::ValidationError(format!("Failed to parse answers file {}: {}", file, e)))?,
+            CtGenAnswersFormat::Toml => toml::from_str(&content)
+                .map_err(|e| CtGenError::ValidationError(format!("Failed to parse answers file {}: {}", file, e)))?,
+        };
+
+        Ok(Self {
+            answers: answers
+                .into_iter()
+                .map(|(prompt_id, answer)| (prompt_id, CtGenAnswers::normalize(answer)))
+                .collect(),
+        })
+    }
+
+    /// Read answers from a JSON object of answers by prompt id, like an answer document of an external front-end. Numbers
+    /// and booleans are taken as their text
+    pub fn from_value(answers: Value) -> Result<Self> {
+        let answers: IndexMap<String, Value> =
+            serde_json::from_value(answers).map_err(|e| CtGenError::ValidationError(format!("Failed to parse answers: {}", e)))?;
+
+        Ok(Self {
+            answers: answers
+                .into_iter()
+                .map(|(prompt_id, answer)| (prompt_id, CtGenAnswers::normalize(answer)))
+                .collect(),
+        })
+    }
+
+    /// Save answers to a `.json` or `.toml` file
+    pub async fn save(&self, file: &str) -> Result<()> {
+        let content = match CtGenAnswersFormat::of(file)? {
+            CtGenAnswersFormat::Json => serde_json::to_string_pretty(self)?,
+            CtGenAnswersFormat::Toml => {
+                toml::to_string(self).map_err(|e| CtGenError::RuntimeError(format!("Failed to serialize answers: {}", e)))?
+            }
+        };
+
+        tokio::fs::write(file, content)
+            .await
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to save answers file {}: {}", file, e)))?;
+
+        Ok(())
+    }
+
+    /// Get answer by prompt id
+    pub fn get(&self, prompt_id: &str) -> Option<&Value> {
+        self.answers.get(prompt_id)
+    }
+
+    /// Set answer of a prompt, replacing an existing one
+    pub fn insert(&mut self, prompt_id: &str, answer: Value) -> &mut Self {
+        self.answers.insert(prompt_id.to_string(), answer);
+        self
+    }
+
+    /// Answers by prompt id, in prompt order
+    pub fn answers(&self) -> &IndexMap<String, Value> {
+        &self.answers
+    }
+
+    /// Check if there are no answers
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    /// Turn numbers and booleans into text, as prompt answers are strings
+    pub(crate) fn normalize(answer: Value) -> Value {
+        match answer {
+            Value::Number(n) => Value::from(n.to_string()),
+            Value::Bool(b) => Value::from(b.to_string()),
+            Value::Array(values) => Value::Array(values.into_iter().map(CtGenAnswers::normalize).collect()),
+            Value::Object(answers) => Value::Object(
+                answers
+                    .into_iter()
+                    .map(|(item, answer)| (item, CtGenAnswers::normalize(answer)))
+                    .collect(),
+            ),
+            answer => answer,
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::error::CtGenError;
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Archive format, by file extension
+pub enum CtGenArchiveFormat {
+    /// `.zip`
+    Zip,
+    /// `.tar`
+    Tar,
+    /// `.tar.gz` or `.tgz`
+    TarGz,
+}
+
+impl CtGenArchiveFormat {
+    /// Detect archive format from a file name
+    pub fn from_file(archive_file: &str) -> Result<Self> {
+        if archive_file.ends_with(".zip") {
+            Ok(CtGenArchiveFormat::Zip)
+        } else if archive_file.ends_with(".tar.gz") || archive_file.ends_with(".tgz") {
+            Ok(CtGenArchiveFormat::TarGz)
+        } else if archive_file.ends_with(".tar") {
+            Ok(CtGenArchiveFormat::Tar)
+        } else {
+            Err(CtGenError::ValidationError(format!(
+                "Unsupported archive {}. Expected a .zip, .tar, .tar.gz or .tgz file",
+                archive_file
+            ))
+            .into())
+        }
+    }
+}
+
+/// Archive entry: path inside the archive, content and Unix file mode
+pub type CtGenArchiveEntry = (String, Vec<u8>, u32);
+
+/// Normalize an archive entry path to `/` separated components. Paths that are absolute or lead out of the archive root
+/// with `..` are rejected, so entries never land outside of the dir an archive is extracted to
+pub fn entry_path(path: &str) -> Result<String> {
+    let mut components: Vec<String> = Vec::new();
+
+    for component in Path::new(&path.replace('\\', "/")).components() {
+        match component {
+            Component::Normal(component) => components.push(component.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            Component::ParentDir if components.pop().is_some() => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(CtGenError::ValidationError(format!("Archive entry {} leads outside of the archive", path)).into());
+            }
+        }
+    }
+
+    if components.is_empty() {
+        return Err(CtGenError::ValidationError(format!("Archive entry {} has no file name", path)).into());
+    }
+
+    Ok(components.join("/"))
+}
+
+/// Write entries into a new archive file, replacing an existing one. Entry times are set to `mtime`, a unix timestamp
+pub fn write_archive(archive_file: &str, entries: &[CtGenArchiveEntry], mtime: u64) -> Result<()> {
+    write_archive_as(archive_file, CtGenArchiveFormat::from_file(archive_file)?, entries, mtime)
+}
+
+/// Write entries into a new archive file of the given format, whatever its extension
+pub fn write_archive_as(archive_file: &str, format: CtGenArchiveFormat, entries: &[CtGenArchiveEntry], mtime: u64) -> Result<()> {
+    let file =
+        File::create(archive_file).map_err(|e| CtGenError::RuntimeError(format!("Failed to create archive {}: {}", archive_file, e)))?;
+
+    match format {
+        CtGenArchiveFormat::Zip => {
+            let mut zip = ZipWriter::new(file);
+
+            for (path, content, mode) in entries {
+                let options = SimpleFileOptions::default()
+                    .compression_method(CompressionMethod::Deflated)
+                    .unix_permissions(*mode);
+
+                zip.start_file(path.as_str(), options)?;
+                zip.write_all(content)?;
+            }
+
+            zip.finish()?;
+        }
+        CtGenArchiveFormat::Tar => {
+            write_tar(file, entries, mtime)?.flush()?;
+        }
+        CtGenArchiveFormat::TarGz => {
+            write_tar(GzEncoder::new(file, Compression::default()), entries, mtime)?.finish()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write entries as a tar stream and return the underlying writer
+fn write_tar<W: Write>(writer: W, entries: &[CtGenArchiveEntry], mtime: u64) -> Result<W> {
+    let mut builder = tar::Builder::new(writer);
+
+    for (path, content, mode) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(*mode);
+        header.set_mtime(mtime);
+        header.set_cksum();
+
+        builder.append_data(&mut header, path, content.as_slice())?;
+    }
+
+    Ok(builder.into_inner()?)
+}
+
+/// Read all file entries of an archive of the given format. Directories and links are skipped, and entries leading outside
+/// of the archive fail the read
+pub fn read_archive(archive_file: &str, format: CtGenArchiveFormat) -> Result<Vec<CtGenArchiveEntry>> {
+    let file = File::open(archive_file).map_err(|e| CtGenError::RuntimeError(format!("Failed to open archive {}: {}", archive_file, e)))?;
+
+    match format {
+        CtGenArchiveFormat::Zip => {
+            let mut zip = ZipArchive::new(file)?;
+            let mut entries = Vec::with_capacity(zip.len());
+
+            for index in 0..zip.len() {
+                let mut entry = zip.by_index(index)?;
+
+                if !entry.is_file() {
+                    continue;
+                }
+
+                let path = entry_path(entry.name())?;
+                let mode = entry.unix_mode().unwrap_or(0o644);
+
+                let mut content = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut content)?;
+
+                entries.push((path, content, mode));
+            }
+
+            Ok(entries)
+        }
+        CtGenArchiveFormat::Tar => read_tar(file),
+        CtGenArchiveFormat::TarGz => read_tar(GzDecoder::new(file)),
+    }
+}
+
+/// Read all file entries of a tar stream
+fn read_tar<R: Read>(reader: R) -> Result<Vec<CtGenArchiveEntry>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = entry_path(&entry.path()?.to_string_lossy())?;
+        let mode = entry.header().mode().unwrap_or(0o644);
+
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+
+        entries.push((path, content, mode));
+    }
+
+    Ok(entries)
+}
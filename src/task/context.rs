@@ -1,10 +1,12 @@
+use crate::consts::ENV_SOURCE_DATE_EPOCH;
 use crate::error::CtGenError;
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use database_reflection::reflection::{Constraint, ConstraintSide, Database, Table};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
 use std::sync::Arc;
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
@@ -14,14 +16,19 @@ pub struct CtGenTaskContext {
     table: Arc<Table>,
     constraints_local: Vec<Arc<Constraint>>,
     constraints_foreign: Vec<Arc<Constraint>>,
-    prompts: HashMap<String, Value>,
+    connections: BTreeMap<String, Database>,
+    prompts: BTreeMap<String, Value>,
+    #[serde(default)]
+    vars: BTreeMap<String, Value>,
+    #[serde(default)]
+    features: BTreeMap<String, bool>,
     timestamp: String,
     ctgen_ver: String,
 }
 
 impl CtGenTaskContext {
-    /// Init new task context
-    pub fn new(database: Database, table_name: &str) -> Result<Self> {
+    /// Init new task context. Deterministic contexts get a frozen timestamp
+    pub fn new(database: Database, table_name: &str, connections: HashMap<String, Database>, deterministic: bool) -> Result<Self> {
         let table = database
             .table(table_name)
             .ok_or_else(|| CtGenError::ValidationError(format!("Table not found: {}", table_name)))?;
@@ -35,14 +42,60 @@ impl CtGenTaskContext {
             table,
             constraints_local,
             constraints_foreign,
-            timestamp: Utc::now().to_rfc3339(),
+            connections: connections.into_iter().collect(),
+            timestamp: CtGenTaskContext::timestamp(deterministic),
             ctgen_ver: env!("CARGO_PKG_VERSION").into(),
             ..Default::default()
         })
     }
 
+    /// Init sample task context without a database connection. Every prompt id gets an empty answer.
+    pub fn sample<'a>(prompt_ids: impl Iterator<Item = &'a String>, connection_names: impl Iterator<Item = &'a String>) -> Self {
+        Self {
+            connections: connection_names.map(|name| (name.to_string(), Database::default())).collect(),
+            prompts: prompt_ids.map(|prompt_id| (prompt_id.to_string(), Value::from(""))).collect(),
+            timestamp: Utc::now().to_rfc3339(),
+            ctgen_ver: env!("CARGO_PKG_VERSION").into(),
+            ..Default::default()
+        }
+    }
+
+    /// Current time, or `SOURCE_DATE_EPOCH` (defaults to the unix epoch) when deterministic
+    pub(crate) fn timestamp(deterministic: bool) -> String {
+        if deterministic {
+            let epoch = env::var(ENV_SOURCE_DATE_EPOCH)
+                .ok()
+                .and_then(|epoch| epoch.trim().parse::<i64>().ok())
+                .unwrap_or_default();
+
+            DateTime::<Utc>::from_timestamp(epoch, 0).unwrap_or_default().to_rfc3339()
+        } else {
+            Utc::now().to_rfc3339()
+        }
+    }
+
     /// Set prompt answer in task context
     pub fn set_prompt_answer(&mut self, prompt_id: &str, prompt_answer: &Value) {
         self.prompts.insert(prompt_id.to_string(), prompt_answer.clone());
     }
+
+    /// Remove prompt answer from task context
+    pub fn remove_prompt_answer(&mut self, prompt_id: &str) {
+        self.prompts.remove(prompt_id);
+    }
+
+    /// Task table reflection
+    pub fn table(&self) -> &Arc<Table> {
+        &self.table
+    }
+
+    /// Set rendered profile vars in task context
+    pub fn set_vars(&mut self, vars: BTreeMap<String, Value>) {
+        self.vars = vars;
+    }
+
+    /// Set enabled feature flags in task context
+    pub fn set_features(&mut self, features: BTreeMap<String, bool>) {
+        self.features = features;
+    }
 }
@@ -0,0 +1,94 @@
+use crate::consts::{FRONT_MATTER_FENCE_TOML, FRONT_MATTER_FENCE_YAML};
+use crate::error::CtGenError;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+/// Template metadata declared in front matter at the top of a template file
+pub struct CtGenTemplateMeta {
+    /// Short description of what the template generates
+    description: Option<String>,
+    /// Prompt ids the template needs answered
+    prompts: Vec<String>,
+    /// Context paths the template reads, like `table/name`
+    context: Vec<String>,
+    /// Default output file path template of targets using the template
+    target: Option<String>,
+    /// Condition template of targets derived from the template by `auto-targets`
+    condition: Option<String>,
+}
+
+impl CtGenTemplateMeta {
+    /// Short description of what the template generates
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    /// Prompt ids the template needs answered
+    pub fn prompts(&self) -> &Vec<String> {
+        &self.prompts
+    }
+    /// Context paths the template reads, like `table/name`
+    pub fn context(&self) -> &Vec<String> {
+        &self.context
+    }
+    /// Default output file path template of targets using the template
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+    /// Condition template of targets derived from the template by `auto-targets`
+    pub fn condition(&self) -> Option<&str> {
+        self.condition.as_deref()
+    }
+}
+
+/// Split template content into raw front matter and its fence, and the template body.
+/// Front matter is only recognized when the very first line is a `---` (YAML) or `+++` (TOML) fence
+pub fn split(content: &str) -> (Option<(&str, &str)>, &str) {
+    let first_line_end = content.find('\n').map(|i| i + 1).unwrap_or(content.len());
+    let fence = content[..first_line_end].trim_end();
+
+    if fence != FRONT_MATTER_FENCE_YAML && fence != FRONT_MATTER_FENCE_TOML {
+        return (None, content);
+    }
+
+    let mut offset = first_line_end;
+
+    while offset < content.len() {
+        let line_end = content[offset..].find('\n').map(|i| offset + i + 1).unwrap_or(content.len());
+
+        if content[offset..line_end].trim_end() == fence {
+            return (Some((fence, &content[first_line_end..offset])), &content[line_end..]);
+        }
+
+        offset = line_end;
+    }
+
+    // no closing fence, it's not front matter
+    (None, content)
+}
+
+/// Get template body with front matter stripped. Content is kept as is when the front matter doesn't parse, so templates
+/// that happen to start with a `---` line, like markdown, render unchanged
+pub fn strip(content: &str) -> &str {
+    match split(content) {
+        (Some(front_matter), body) if parse_front_matter(front_matter).is_ok() => body,
+        _ => content,
+    }
+}
+
+/// Parse template front matter, if any
+pub fn parse(content: &str) -> Result<Option<CtGenTemplateMeta>> {
+    split(content).0.map(parse_front_matter).transpose()
+}
+
+/// Parse raw front matter by its fence
+fn parse_front_matter((fence, front_matter): (&str, &str)) -> Result<CtGenTemplateMeta> {
+    if fence == FRONT_MATTER_FENCE_TOML {
+        toml::from_str(front_matter).map_err(|e| CtGenError::ValidationError(format!("Invalid TOML front matter: {}", e)).into())
+    } else {
+        serde_yaml::from_str::<Option<CtGenTemplateMeta>>(front_matter)
+            .map(Option::unwrap_or_default)
+            .map_err(|e| CtGenError::ValidationError(format!("Invalid YAML front matter: {}", e)).into())
+    }
+}
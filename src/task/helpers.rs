@@ -0,0 +1,893 @@
+use crate::consts::{PLUGIN_FUEL, RUST_KEYWORDS, RUST_KEYWORDS_NOT_RAW};
+use crate::error::CtGenError;
+use crate::profile::{CtGenExecHelperConfig, CtGenSqlDialect};
+use crate::task::blocking_shell_command;
+use crate::CtGen;
+use handlebars::{
+    BlockContext, Context, Handlebars, Helper, HelperDef, HelperResult, JsonTruthy, Output, RenderContext, RenderError, RenderErrorReason,
+    Renderable, ScopedJson, StringOutput,
+};
+use rhai::serde::{from_dynamic, to_dynamic};
+use rhai::{Dynamic, Scope, AST};
+use serde_json::{json, Value};
+use serde_json_path::JsonPath;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use wasmtime::{Caller, Extern, InstancePre, Linker, Module, Store};
+
+#[derive(Clone, Debug)]
+/// Inline file contents, `{{include_file "path/to/file" render=true}}`.
+/// The path is relative to the profile context dir and must stay inside it. With `render=true` the contents are rendered
+/// with the current context.
+pub struct IncludeFileHelper {
+    context_dir: String,
+}
+
+impl IncludeFileHelper {
+    pub fn new(context_dir: &str) -> Self {
+        Self {
+            context_dir: context_dir.to_string(),
+        }
+    }
+}
+
+impl HelperDef for IncludeFileHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let path = h
+            .param(0)
+            .and_then(|p| p.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("include_file", 0))?;
+
+        let render = h.hash_get("render").and_then(|v| v.value().as_bool()).unwrap_or(false);
+
+        let file = CtGen::get_filepath(&self.context_dir, path);
+
+        if Path::new(path).is_absolute() || !CtGen::is_path_within(&self.context_dir, &file) {
+            return Err(RenderErrorReason::Other(format!(
+                "Include file {} resolves outside of the profile dir {}",
+                path, self.context_dir
+            ))
+            .into());
+        }
+
+        let file = CtGen::normalize_path(&file).to_string_lossy().to_string();
+
+        let content =
+            std::fs::read_to_string(&file).map_err(|e| RenderErrorReason::Other(format!("Failed to read include file {}: {}", file, e)))?;
+
+        if render {
+            out.write(&r.render_template_with_context(&content, ctx)?)?;
+        } else {
+            out.write(&content)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Run an allowed command and output its trimmed stdout, `{{exec "git" "rev-parse" "HEAD"}}`.
+/// Only commands listed in the profile `[helpers.exec]` section are allowed. Commands run through the shell in the
+/// working directory of formatters and hooks, and are killed once they run longer than the configured timeout.
+pub struct ExecHelper {
+    allow: Vec<String>,
+    command_dir: Option<String>,
+    timeout: Duration,
+}
+
+impl ExecHelper {
+    pub fn new(config: &CtGenExecHelperConfig, command_dir: Option<&str>) -> Self {
+        Self {
+            allow: config.allow().to_vec(),
+            command_dir: command_dir.map(str::to_string),
+            timeout: Duration::from_secs(config.timeout()),
+        }
+    }
+}
+
+/// Read a child process pipe to the end on its own thread, so a full pipe can't block the child while it is waited for
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+
+        buffer
+    })
+}
+
+impl HelperDef for ExecHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _r: &'reg Handlebars<'reg>,
+        _ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let command = h
+            .param(0)
+            .and_then(|p| p.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("exec", 0))?;
+
+        if !self.allow.iter().any(|allowed| allowed == command) {
+            return Err(RenderErrorReason::Other(format!("Command `{}` is not allowed by the profile exec helper config", command)).into());
+        }
+
+        let args = h
+            .params()
+            .iter()
+            .skip(1)
+            .map(|p| p.value().as_str().map(str::to_string).unwrap_or_else(|| p.value().to_string()))
+            .collect::<Vec<String>>();
+
+        let mut child = blocking_shell_command(command, &args, self.command_dir.as_deref(), &[])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| RenderErrorReason::Other(format!("Failed to run command `{}`: {}", command, e)))?;
+
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+
+        let deadline = Instant::now() + self.timeout;
+
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+                Ok(None) => {
+                    // the pipe readers are left behind, a process spawned by the command may still hold the pipes
+                    let _ = child.kill();
+                    let _ = child.wait();
+
+                    return Err(RenderErrorReason::Other(format!(
+                        "Command `{}` timed out after {} seconds",
+                        command,
+                        self.timeout.as_secs()
+                    ))
+                    .into());
+                }
+                Err(e) => return Err(RenderErrorReason::Other(format!("Failed to run command `{}`: {}", command, e)).into()),
+            }
+        };
+
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        if !status.success() {
+            return Err(RenderErrorReason::Other(format!(
+                "Command `{}` returned error: {}",
+                command,
+                String::from_utf8_lossy(&stderr).trim()
+            ))
+            .into());
+        }
+
+        out.write(String::from_utf8_lossy(&stdout).trim())?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Quote an SQL identifier for the profile dialect, `{{quote_ident name dialect="postgres" qualified=true}}`.
+/// With `qualified=true` each dot separated part is quoted on its own.
+pub struct QuoteIdentHelper {
+    dialect: CtGenSqlDialect,
+}
+
+impl QuoteIdentHelper {
+    pub fn new(dialect: CtGenSqlDialect) -> Self {
+        Self { dialect }
+    }
+}
+
+impl HelperDef for QuoteIdentHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _r: &'reg Handlebars<'reg>,
+        _ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let ident = h
+            .param(0)
+            .and_then(|p| p.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("quote_ident", 0))?;
+
+        let dialect = if let Some(dialect) = h.hash_get("dialect") {
+            serde_json::from_value::<CtGenSqlDialect>(dialect.value().clone())
+                .map_err(|_e| RenderErrorReason::Other(format!("Unknown SQL dialect {}", dialect.value())))?
+        } else {
+            self.dialect
+        };
+
+        if h.hash_get("qualified").map(|q| q.value().is_truthy(false)).unwrap_or(false) {
+            out.write(&ident.split('.').map(|part| dialect.quote(part)).collect::<Vec<String>>().join("."))?;
+        } else {
+            out.write(&dialect.quote(ident))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+/// Output of the memoized helpers of a renderer by helper call. Cleared at the start of every run, so helpers don't
+/// serve output of templates, scripts or a schema that changed since
+pub struct MemoizeCache(Arc<Mutex<HashMap<String, Value>>>);
+
+impl MemoizeCache {
+    /// Forget all cached helper output
+    pub fn clear(&self) {
+        if let Ok(mut cache) = self.0.lock() {
+            cache.clear();
+        }
+    }
+}
+
+/// Cache helper output by params and hash for the duration of a run. Block helpers are never cached.
+/// Values returned to subexpressions are cached apart from written output, so both keep their JSON type.
+pub struct MemoizedHelper {
+    inner: Box<dyn HelperDef + Send + Sync>,
+    cache: MemoizeCache,
+}
+
+impl MemoizedHelper {
+    pub fn new(inner: Box<dyn HelperDef + Send + Sync>, cache: MemoizeCache) -> Self {
+        Self { inner, cache }
+    }
+
+    /// Cache key of a helper call. The cache is shared by all memoized helpers, so the key names the helper
+    fn key(h: &Helper<'_>, rc: &RenderContext<'_, '_>, inner: bool) -> String {
+        json!({
+            "name": h.name(),
+            "params": h.params().iter().map(|p| p.value()).collect::<Vec<&Value>>(),
+            "hash": h.hash().iter().map(|(k, v)| (k.to_string(), v.value())).collect::<BTreeMap<String, &Value>>(),
+            "raw": rc.is_disable_escape(),
+            "inner": inner,
+        })
+        .to_string()
+    }
+
+    /// Get cached value
+    fn cached(&self, key: &str) -> Result<Option<Value>, RenderError> {
+        Ok(self
+            .cache
+            .0
+            .lock()
+            .map_err(|_e| RenderErrorReason::Other("Memoization cache is poisoned".to_string()))?
+            .get(key)
+            .cloned())
+    }
+
+    /// Cache value
+    fn cache(&self, key: String, value: Value) -> Result<(), RenderError> {
+        self.cache
+            .0
+            .lock()
+            .map_err(|_e| RenderErrorReason::Other("Memoization cache is poisoned".to_string()))?
+            .insert(key, value);
+
+        Ok(())
+    }
+}
+
+impl HelperDef for MemoizedHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let key = MemoizedHelper::key(h, rc, true);
+
+        if let Some(value) = self.cached(&key)? {
+            return Ok(ScopedJson::Derived(value));
+        }
+
+        // helpers that only write output fail here as unimplemented, so handlebars falls back to `call`
+        let value = self.inner.call_inner(h, r, ctx, rc)?.as_json().clone();
+
+        self.cache(key, value.clone())?;
+
+        Ok(ScopedJson::Derived(value))
+    }
+
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        if h.is_block() {
+            return self.inner.call(h, r, ctx, rc, out);
+        }
+
+        let key = MemoizedHelper::key(h, rc, false);
+
+        if let Some(Value::String(output)) = self.cached(&key)? {
+            out.write(&output)?;
+
+            return Ok(());
+        }
+
+        let mut output = StringOutput::new();
+        self.inner.call(h, r, ctx, rc, &mut output)?;
+        let output = output.into_string()?;
+
+        out.write(&output)?;
+
+        self.cache(key, Value::String(output))?;
+
+        Ok(())
+    }
+}
+
+/// Rhai script helper compiled by ctgen instead of the registry, so it can be wrapped by other helpers
+pub struct CompiledScriptHelper {
+    script: AST,
+}
+
+impl CompiledScriptHelper {
+    pub fn new(script: AST) -> Self {
+        Self { script }
+    }
+}
+
+impl HelperDef for CompiledScriptHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        _ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let params: Dynamic = to_dynamic(h.params().iter().map(|p| p.value()).collect::<Vec<&Value>>()).map_err(RenderErrorReason::from)?;
+
+        let hash: Dynamic = to_dynamic(
+            h.hash()
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.value()))
+                .collect::<HashMap<String, &Value>>(),
+        )
+        .map_err(RenderErrorReason::from)?;
+
+        let mut scope = Scope::new();
+        scope.push_dynamic("params", params);
+        scope.push_dynamic("hash", hash);
+
+        let result = r
+            .engine()
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.script)
+            .map_err(RenderErrorReason::from)?;
+
+        let result: Value = from_dynamic(&result).map_err(RenderErrorReason::from)?;
+
+        Ok(ScopedJson::Derived(result))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Query JSON path (RFC 9535) against the root context or a passed value and return all matches as array,
+/// `{{#each (jsonpath "$.table.columns[?search(@.datatype, '^varchar')]")}}`. With `first=true` only the first match is returned.
+pub struct JsonPathHelper;
+
+impl HelperDef for JsonPathHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let path = h
+            .param(0)
+            .and_then(|p| p.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("jsonpath", 0))?;
+
+        let path = JsonPath::parse(path).map_err(|e| RenderErrorReason::Other(format!("Invalid JSON path `{}`: {}", path, e)))?;
+
+        let value = h.param(1).map(|p| p.value()).unwrap_or_else(|| ctx.data());
+
+        let nodes = path.query(value).all();
+
+        if h.hash_get("first").and_then(|v| v.value().as_bool()).unwrap_or(false) {
+            Ok(ScopedJson::Derived(nodes.first().map(|v| (*v).clone()).unwrap_or(Value::Null)))
+        } else {
+            Ok(ScopedJson::Derived(Value::Array(nodes.into_iter().cloned().collect())))
+        }
+    }
+}
+
+/// Transliterate a name to ASCII and replace everything but letters, digits and underscores with the separator,
+/// so accented or non-Latin table and column names can be used as code identifiers and file names
+pub fn to_identifier(input: &str, separator: &str) -> String {
+    let mut identifier = String::new();
+    let mut pending_separator = false;
+
+    for c in deunicode::deunicode(input).chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            if pending_separator && !identifier.is_empty() {
+                identifier.push_str(separator);
+            }
+            pending_separator = false;
+            identifier.push(c);
+        } else {
+            pending_separator = true;
+        }
+    }
+
+    // identifiers can't start with a digit
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+
+    identifier
+}
+
+/// Escape a Rust keyword used as a name, like a `type` column, as a raw identifier `r#type`. Keywords that can't be raw
+/// identifiers, like `self`, get a trailing underscore. Other names are kept
+pub fn rust_ident(input: &str) -> String {
+    if RUST_KEYWORDS_NOT_RAW.contains(&input) {
+        format!("{}_", input)
+    } else if RUST_KEYWORDS.contains(&input) {
+        format!("r#{}", input)
+    } else {
+        input.to_string()
+    }
+}
+
+/// Get array items from the first helper param
+fn array_param(h: &Helper, helper: &'static str) -> Result<Vec<Value>, RenderError> {
+    match h.param(0).map(|p| p.value()) {
+        Some(Value::Array(items)) => Ok(items.clone()),
+        Some(Value::Null) | None => Ok(Vec::new()),
+        Some(_) => Err(RenderErrorReason::ParamTypeMismatchForName(helper, "0".to_string(), "array".to_string()).into()),
+    }
+}
+
+/// Get field value from the second helper param. Nested fields are separated with `/` or `.`
+fn field_param<'a>(h: &Helper, item: &'a Value, helper: &'static str) -> Result<Option<&'a Value>, RenderError> {
+    let field = h
+        .param(1)
+        .and_then(|p| p.value().as_str())
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex(helper, 1))?;
+
+    Ok(field.split(['/', '.']).try_fold(item, |value, segment| value.get(segment)))
+}
+
+/// Output array items. Block helpers render their template for every item, like `each`
+fn render_items<'reg: 'rc, 'rc>(
+    items: Vec<Value>,
+    h: &Helper<'rc>,
+    r: &'reg Handlebars<'reg>,
+    ctx: &'rc Context,
+    rc: &mut RenderContext<'reg, 'rc>,
+    out: &mut dyn Output,
+) -> HelperResult {
+    if !h.is_block() {
+        out.write(&serde_json::to_string(&items).map_err(RenderErrorReason::from)?)?;
+
+        return Ok(());
+    }
+
+    if items.is_empty() {
+        if let Some(inverse) = h.inverse() {
+            inverse.render(r, ctx, rc, out)?;
+        }
+
+        return Ok(());
+    }
+
+    if let Some(template) = h.template() {
+        let len = items.len();
+
+        for (index, item) in items.into_iter().enumerate() {
+            let mut block = BlockContext::new();
+            block.set_base_value(item);
+            block.set_local_var("index", json!(index));
+            block.set_local_var("first", json!(index == 0));
+            block.set_local_var("last", json!(index + 1 == len));
+
+            rc.push_block(block);
+            template.render(r, ctx, rc, out)?;
+            rc.pop_block();
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Keep array items by field, `{{#filter table/columns "nullable"}}`, `(filter table/columns "datatype" starts_with="varchar")`.
+/// With a third param, the field must be equal to it. Otherwise the field must be truthy. `not=true` inverts the match.
+pub struct FilterHelper;
+
+impl FilterHelper {
+    fn filter(h: &Helper) -> Result<Vec<Value>, RenderError> {
+        let expected = h.param(2).map(|p| p.value());
+        let starts_with = h.hash_get("starts_with").and_then(|v| v.value().as_str());
+        let not = h.hash_get("not").and_then(|v| v.value().as_bool()).unwrap_or(false);
+
+        let mut items = Vec::new();
+        for item in array_param(h, "filter")? {
+            let field = field_param(h, &item, "filter")?;
+
+            let matched = if let Some(prefix) = starts_with {
+                field.and_then(|v| v.as_str()).is_some_and(|v| v.starts_with(prefix))
+            } else if let Some(expected) = expected {
+                field == Some(expected)
+            } else {
+                field.is_some_and(|v| v.is_truthy(false))
+            };
+
+            if matched != not {
+                items.push(item);
+            }
+        }
+
+        Ok(items)
+    }
+}
+
+impl HelperDef for FilterHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _r: &'reg Handlebars<'reg>,
+        _ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        Ok(ScopedJson::Derived(Value::Array(FilterHelper::filter(h)?)))
+    }
+
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        render_items(FilterHelper::filter(h)?, h, r, ctx, rc, out)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Map array items to one of their fields, `(map table/columns "name")`
+pub struct MapHelper;
+
+impl MapHelper {
+    fn map(h: &Helper) -> Result<Vec<Value>, RenderError> {
+        array_param(h, "map")?
+            .iter()
+            .map(|item| field_param(h, item, "map").map(|field| field.cloned().unwrap_or(Value::Null)))
+            .collect()
+    }
+}
+
+impl HelperDef for MapHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _r: &'reg Handlebars<'reg>,
+        _ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        Ok(ScopedJson::Derived(Value::Array(MapHelper::map(h)?)))
+    }
+
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        render_items(MapHelper::map(h)?, h, r, ctx, rc, out)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Sort array items by field, `{{#sort_by table/columns "ordinal"}}`. `desc=true` reverses the order
+pub struct SortByHelper;
+
+impl SortByHelper {
+    fn compare(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+        match (a, b) {
+            (Some(Value::Number(a)), Some(Value::Number(b))) => a
+                .as_f64()
+                .unwrap_or_default()
+                .partial_cmp(&b.as_f64().unwrap_or_default())
+                .unwrap_or(Ordering::Equal),
+            (Some(Value::String(a)), Some(Value::String(b))) => a.cmp(b),
+            (Some(Value::Bool(a)), Some(Value::Bool(b))) => a.cmp(b),
+            (Some(a), Some(b)) => a.to_string().cmp(&b.to_string()),
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    fn sort(h: &Helper) -> Result<Vec<Value>, RenderError> {
+        let desc = h.hash_get("desc").and_then(|v| v.value().as_bool()).unwrap_or(false);
+
+        let mut keyed = array_param(h, "sort_by")?
+            .into_iter()
+            .map(|item| field_param(h, &item, "sort_by").map(|field| (field.cloned(), item.clone())))
+            .collect::<Result<Vec<(Option<Value>, Value)>, RenderError>>()?;
+
+        keyed.sort_by(|(a, _), (b, _)| SortByHelper::compare(a.as_ref(), b.as_ref()));
+
+        if desc {
+            keyed.reverse();
+        }
+
+        Ok(keyed.into_iter().map(|(_, item)| item).collect())
+    }
+}
+
+impl HelperDef for SortByHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _r: &'reg Handlebars<'reg>,
+        _ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        Ok(ScopedJson::Derived(Value::Array(SortByHelper::sort(h)?)))
+    }
+
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        rc: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        render_items(SortByHelper::sort(h)?, h, r, ctx, rc, out)
+    }
+}
+
+/// Helper implemented by a WASM plugin module.
+///
+/// The module must export `memory`, `alloc(len: i32) -> i32` and `call(ptr: i32, len: i32) -> i64`.
+/// The host writes the helper input as JSON `{"params": [...], "hash": {...}}` into a buffer returned by `alloc`,
+/// then `call` returns the pointer (upper 32 bits) and length (lower 32 bits) of the JSON encoded helper result.
+/// Modules may import `ctgen.log(ptr: i32, len: i32)` to write UTF-8 debug messages to the log.
+pub struct WasmPluginHelper {
+    engine: wasmtime::Engine,
+    instance: InstancePre<()>,
+}
+
+impl WasmPluginHelper {
+    /// Compile a plugin module and link it against the host API
+    pub fn load(file: &str) -> anyhow::Result<Self> {
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+
+        let engine = wasmtime::Engine::new(&config)?;
+        let module = Module::from_file(&engine, file)?;
+
+        let mut linker = Linker::new(&engine);
+        linker.func_wrap(
+            "ctgen",
+            "log",
+            |mut caller: Caller<'_, ()>, ptr: i32, len: i32| -> anyhow::Result<()> {
+                let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+                    return Ok(());
+                };
+
+                let message = WasmPluginHelper::memory_slice(memory.data(&caller), ptr as u32 as usize, len as u32 as usize)?;
+                log::info!("{}", String::from_utf8_lossy(message));
+
+                Ok(())
+            },
+        )?;
+
+        let instance = linker.instantiate_pre(&module)?;
+
+        Ok(Self { engine, instance })
+    }
+
+    /// Get a buffer of plugin memory, or an error if it reaches past the end of the memory
+    fn memory_slice(data: &[u8], ptr: usize, len: usize) -> anyhow::Result<&[u8]> {
+        ptr.checked_add(len).and_then(|end| data.get(ptr..end)).ok_or_else(|| {
+            CtGenError::RuntimeError(format!(
+                "Plugin buffer at {} of {} bytes is out of memory bounds ({} bytes)",
+                ptr,
+                len,
+                data.len()
+            ))
+            .into()
+        })
+    }
+
+    /// Run the plugin in a fresh instance, so calls never share state. Every call gets the same fuel, so plugins that
+    /// never return fail instead of hanging the render
+    fn invoke(&self, input: &Value) -> anyhow::Result<Value> {
+        let mut store = Store::new(&self.engine, ());
+        store.set_fuel(PLUGIN_FUEL)?;
+        let instance = self.instance.instantiate(&mut store)?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| CtGenError::RuntimeError("Plugin does not export memory".to_string()))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let call = instance.get_typed_func::<(i32, i32), i64>(&mut store, "call")?;
+
+        let input = serde_json::to_vec(input)?;
+        let input_len = i32::try_from(input.len())?;
+        let input_ptr = alloc.call(&mut store, input_len)?;
+        memory.write(&mut store, input_ptr as usize, &input)?;
+
+        let packed = call.call(&mut store, (input_ptr, input_len))? as u64;
+        let output_ptr = (packed >> 32) as usize;
+        let output_len = (packed & 0xffff_ffff) as usize;
+
+        let output = WasmPluginHelper::memory_slice(memory.data(&store), output_ptr, output_len)?;
+
+        Ok(serde_json::from_slice(output)?)
+    }
+}
+
+impl HelperDef for WasmPluginHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _r: &'reg Handlebars<'reg>,
+        _ctx: &'rc Context,
+        _rc: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let input = json!({
+            "params": h.params().iter().map(|p| p.value()).collect::<Vec<&Value>>(),
+            "hash": h.hash().iter().map(|(k, v)| (k.to_string(), v.value().clone())).collect::<BTreeMap<String, Value>>(),
+        });
+
+        let output = self
+            .invoke(&input)
+            .map_err(|e| RenderErrorReason::Other(format!("Plugin helper {} failed: {}", h.name(), e)))?;
+
+        Ok(ScopedJson::Derived(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_identifier_transliterates_names() {
+        assert_eq!(to_identifier("Größe", "_"), "Grosse");
+        assert_eq!(to_identifier("Заказы клиентов", "_"), "Zakazy_klientov");
+        assert_eq!(to_identifier("café crème", "-"), "cafe-creme");
+    }
+
+    #[test]
+    fn to_identifier_collapses_separators() {
+        assert_eq!(to_identifier("  Größe (mm) / Stück ", "_"), "Grosse_mm_Stuck");
+        assert_eq!(to_identifier("snake_case_name", "-"), "snake_case_name");
+        assert_eq!(to_identifier("---", "_"), "");
+    }
+
+    #[test]
+    fn to_identifier_never_starts_with_digit() {
+        assert_eq!(to_identifier("2024 Umsätze", "_"), "_2024_Umsatze");
+    }
+
+    #[test]
+    fn rust_ident_escapes_keywords() {
+        assert_eq!(rust_ident("type"), "r#type");
+        assert_eq!(rust_ident("match"), "r#match");
+        assert_eq!(rust_ident("self"), "self_");
+        assert_eq!(rust_ident("user_type"), "user_type");
+    }
+
+    #[test]
+    fn include_file_stays_inside_context_dir() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("include_file", Box::new(IncludeFileHelper::new(env!("CARGO_MANIFEST_DIR"))));
+
+        assert!(handlebars
+            .render_template(r#"{{include_file "./src/../Cargo.toml"}}"#, &json!({}))
+            .unwrap()
+            .contains("[package]"));
+
+        for path in ["../../../etc/passwd", "src/../../outside.txt", "/etc/passwd"] {
+            assert!(
+                handlebars
+                    .render_template(&format!(r#"{{{{include_file "{}"}}}}"#, path), &json!({}))
+                    .is_err(),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exec_runs_in_command_dir_with_timeout() {
+        let config: CtGenExecHelperConfig = toml::from_str("allow = [\"pwd\", \"sleep\", \"printf\"]\ntimeout = 1").unwrap();
+
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("exec", Box::new(ExecHelper::new(&config, Some("/"))));
+
+        assert_eq!(handlebars.render_template(r#"{{exec "pwd"}}"#, &json!({})).unwrap(), "/");
+        assert_eq!(
+            handlebars
+                .render_template(r#"{{exec "printf" "%s" "a; echo b"}}"#, &json!({}))
+                .unwrap(),
+            "a; echo b"
+        );
+
+        let started = Instant::now();
+
+        assert!(handlebars.render_template(r#"{{exec "sleep" "10"}}"#, &json!({})).is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    /// Helper writing how often it was called
+    struct CountingHelper(Arc<Mutex<usize>>);
+
+    impl HelperDef for CountingHelper {
+        fn call<'reg: 'rc, 'rc>(
+            &self,
+            _h: &Helper<'rc>,
+            _r: &'reg Handlebars<'reg>,
+            _ctx: &'rc Context,
+            _rc: &mut RenderContext<'reg, 'rc>,
+            out: &mut dyn Output,
+        ) -> HelperResult {
+            let mut calls = self.0.lock().unwrap();
+            *calls += 1;
+
+            out.write(&calls.to_string())?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn memoized_helper_cache_clears_per_run() {
+        let cache = MemoizeCache::default();
+
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper(
+            "count",
+            Box::new(MemoizedHelper::new(
+                Box::new(CountingHelper(Arc::new(Mutex::new(0)))),
+                cache.clone(),
+            )),
+        );
+
+        assert_eq!(handlebars.render_template("{{count 1}} {{count 1}}", &json!({})).unwrap(), "1 1");
+
+        cache.clear();
+
+        assert_eq!(handlebars.render_template("{{count 1}}", &json!({})).unwrap(), "2");
+    }
+}
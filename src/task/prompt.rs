@@ -1,4 +1,5 @@
-use crate::profile::CtGenPrompt;
+use crate::consts::{ENV_PROMPT_PREFIX, PROMPT_ID_DATABASE, PROMPT_ID_TABLE};
+use crate::profile::{CtGenConfirmStyle, CtGenPrompt, CtGenPromptDefaults};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -8,6 +9,29 @@ pub enum CtGenTaskPrompt {
     PromptGeneric { prompt_id: String, prompt_data: CtGenPrompt },
 }
 
+impl CtGenTaskPrompt {
+    /// Prompt id used to match answer overrides. Built-in prompts use reserved ids.
+    pub fn prompt_id(&self) -> &str {
+        match self {
+            CtGenTaskPrompt::PromptDatabase => PROMPT_ID_DATABASE,
+            CtGenTaskPrompt::PromptTable => PROMPT_ID_TABLE,
+            CtGenTaskPrompt::PromptGeneric { prompt_id, prompt_data: _ } => prompt_id,
+        }
+    }
+
+    /// Environment variable answering the prompt, like `CTGEN_PROMPT_TABLE` or `CTGEN_PROMPT_AUTH_MODE` for `auth-mode`
+    pub fn env_var(&self) -> String {
+        format!(
+            "{}{}",
+            ENV_PROMPT_PREFIX,
+            self.prompt_id()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+                .collect::<String>()
+        )
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CtGenRenderedPrompt {
     should_ask: bool,
@@ -15,7 +39,10 @@ pub struct CtGenRenderedPrompt {
     prompt: String,
     options: serde_json::Value,
     multiple: bool,
-    ordered: bool,
+    secret: bool,
+    appearance: CtGenPromptDefaults,
+    #[serde(default)]
+    selected: Vec<String>,
 }
 
 impl CtGenRenderedPrompt {
@@ -25,7 +52,8 @@ impl CtGenRenderedPrompt {
         prompt: String,
         options: serde_json::Value,
         multiple: bool,
-        ordered: bool,
+        secret: bool,
+        appearance: CtGenPromptDefaults,
     ) -> CtGenRenderedPrompt {
         CtGenRenderedPrompt {
             should_ask,
@@ -33,10 +61,18 @@ impl CtGenRenderedPrompt {
             prompt,
             options,
             multiple,
-            ordered,
+            secret,
+            appearance,
+            selected: Vec::new(),
         }
     }
 
+    /// Pre-select options of a multiple choice prompt, by answer value
+    pub fn with_selected(mut self, selected: Vec<String>) -> CtGenRenderedPrompt {
+        self.selected = selected;
+        self
+    }
+
     pub fn should_ask(&self) -> bool {
         self.should_ask
     }
@@ -52,7 +88,25 @@ impl CtGenRenderedPrompt {
     pub fn multiple(&self) -> bool {
         self.multiple
     }
+    pub fn secret(&self) -> bool {
+        self.secret
+    }
     pub fn ordered(&self) -> bool {
-        self.ordered
+        self.appearance.ordered()
+    }
+    pub fn report(&self) -> bool {
+        self.appearance.report()
+    }
+    pub fn max_options(&self) -> usize {
+        self.appearance.max_options()
+    }
+    pub fn confirm(&self) -> CtGenConfirmStyle {
+        self.appearance.confirm()
+    }
+    pub fn appearance(&self) -> &CtGenPromptDefaults {
+        &self.appearance
+    }
+    pub fn selected(&self) -> &Vec<String> {
+        &self.selected
     }
 }
@@ -0,0 +1,110 @@
+use crate::consts::{KEEP_MARKER_END, KEEP_MARKER_START};
+use crate::error::CtGenError;
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Get protected region name from a start marker line, if the line is one
+fn region_start(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once(KEEP_MARKER_START)?;
+
+    let name = rest
+        .trim_start()
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/')))
+        .next()
+        .unwrap_or_default();
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Check if a line is an end marker
+fn region_end(line: &str) -> bool {
+    line.contains(KEEP_MARKER_END)
+}
+
+/// Extract protected region bodies by name. Marker lines are not part of the body
+pub fn extract_regions(content: &str) -> Result<HashMap<String, String>> {
+    let mut regions = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in content.split_inclusive('\n') {
+        if let Some((name, mut body)) = current.take() {
+            if region_end(line) {
+                if regions.insert(name.clone(), body).is_some() {
+                    return Err(CtGenError::ValidationError(format!("Duplicate protected region `{}`", name)).into());
+                }
+            } else if let Some(nested) = region_start(line) {
+                return Err(CtGenError::ValidationError(format!(
+                    "Protected region `{}` starts inside protected region `{}`",
+                    nested, name
+                ))
+                .into());
+            } else {
+                body.push_str(line);
+                current = Some((name, body));
+            }
+        } else if let Some(name) = region_start(line) {
+            current = Some((name.to_string(), String::new()));
+        }
+    }
+
+    if let Some((name, _)) = current {
+        return Err(CtGenError::ValidationError(format!("Protected region `{}` has no end marker", name)).into());
+    }
+
+    Ok(regions)
+}
+
+/// Re-inject protected regions of the existing file content into freshly rendered content at matching markers
+pub fn merge_regions(rendered: &str, existing: &str) -> Result<String> {
+    let regions = extract_regions(existing)?;
+
+    if regions.is_empty() {
+        return Ok(rendered.to_string());
+    }
+
+    let mut merged = String::with_capacity(rendered.len());
+    let mut merged_regions = HashSet::new();
+    let mut current: Option<String> = None;
+
+    for line in rendered.split_inclusive('\n') {
+        if let Some(name) = current.as_deref() {
+            if region_end(line) {
+                merged.push_str(line);
+                current = None;
+            } else if !regions.contains_key(name) {
+                // regions new to the file keep the rendered body
+                merged.push_str(line);
+            }
+        } else {
+            merged.push_str(line);
+
+            if let Some(name) = region_start(line) {
+                if let Some(body) = regions.get(name) {
+                    merged.push_str(body);
+                    merged_regions.insert(name.to_string());
+                }
+
+                current = Some(name.to_string());
+            }
+        }
+    }
+
+    if let Some(name) = current {
+        return Err(CtGenError::ValidationError(format!("Protected region `{}` has no end marker in rendered output", name)).into());
+    }
+
+    // refuse to drop hand-written code
+    if let Some(name) = regions.keys().find(|name| !merged_regions.contains(*name)) {
+        return Err(CtGenError::ValidationError(format!(
+            "Protected region `{}` is missing from rendered output. Remove it from the existing file to drop it",
+            name
+        ))
+        .into());
+    }
+
+    Ok(merged)
+}
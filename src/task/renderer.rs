@@ -0,0 +1,541 @@
+use crate::consts::{FILE_EXT_HBS, FILE_EXT_RHAI, HELPERS_BUILTIN, HELPERS_BUNDLED, SCRIPT_TEST_SUFFIX};
+use crate::error::CtGenError;
+use crate::pack::CtGenPack;
+use crate::profile::{CtGenProfile, CtGenSqlDialect};
+use crate::task::frontmatter;
+use crate::task::helpers::{
+    self, CompiledScriptHelper, ExecHelper, FilterHelper, IncludeFileHelper, JsonPathHelper, MapHelper, MemoizeCache, MemoizedHelper,
+    QuoteIdentHelper, SortByHelper, WasmPluginHelper,
+};
+use crate::CtGen;
+use anyhow::Result;
+use handlebars::{handlebars_helper, Handlebars, HelperDef, RenderError, RenderErrorReason};
+use handlebars_chrono::HandlebarsChronoDateTime;
+use handlebars_concat::HandlebarsConcat;
+use handlebars_inflector::HandlebarsInflector;
+use rhai::packages::Package;
+use rhai::Engine;
+use rhai_chrono::ChronoPackage;
+use rhai_fs::FilesystemPackage;
+use rhai_sci::SciPackage;
+use rhai_url::UrlPackage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use walkdir::WalkDir;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CtGenHelperKind {
+    /// Handlebars built-in helper
+    Builtin,
+    /// Helper bundled with ctgen
+    Bundled,
+    /// Profile rhai script
+    Script,
+    /// Rhai script of a pack used by the profile
+    Pack,
+    /// Profile WASM plugin
+    Plugin,
+}
+
+impl Display for CtGenHelperKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenHelperKind::Builtin => write!(f, "builtin"),
+            CtGenHelperKind::Bundled => write!(f, "bundled"),
+            CtGenHelperKind::Script => write!(f, "script"),
+            CtGenHelperKind::Pack => write!(f, "pack"),
+            CtGenHelperKind::Plugin => write!(f, "plugin"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Helper available to profile templates
+pub struct CtGenHelperInfo {
+    /// Helper name, as called from templates
+    name: String,
+    /// Helper origin
+    kind: CtGenHelperKind,
+    /// Script or plugin file, if any
+    file: Option<String>,
+    /// Flag that shows whether the helper output is memoized
+    memoized: bool,
+}
+
+impl CtGenHelperInfo {
+    /// Helper name, as called from templates
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Helper origin
+    pub fn kind(&self) -> CtGenHelperKind {
+        self.kind
+    }
+    /// Script or plugin file, if any
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+    /// Flag that shows whether the helper output is memoized
+    pub fn memoized(&self) -> bool {
+        self.memoized
+    }
+}
+
+/// List all helpers a profile registers, in registration order
+pub fn list_helpers(profile: &CtGenProfile) -> Vec<CtGenHelperInfo> {
+    let memoize = profile.helpers().memoize();
+    let info = |name: &str, kind: CtGenHelperKind, file: Option<String>| CtGenHelperInfo {
+        name: name.to_string(),
+        kind,
+        file,
+        memoized: memoize.iter().any(|memoized| memoized == name),
+    };
+
+    let mut helpers = Vec::new();
+
+    helpers.extend(HELPERS_BUILTIN.iter().map(|name| info(name, CtGenHelperKind::Builtin, None)));
+
+    helpers.extend(
+        script_helpers(&profile.scripts_dir())
+            .into_iter()
+            .map(|(name, path)| info(&name, CtGenHelperKind::Script, Some(path.to_string_lossy().to_string()))),
+    );
+
+    // pack scripts overridden by profile scripts aren't available
+    for pack in profile.packs().unwrap_or_default() {
+        for (name, path) in script_helpers(&pack.scripts_dir()) {
+            if !helpers.iter().any(|helper| helper.name == name) {
+                helpers.push(info(&name, CtGenHelperKind::Pack, Some(path.to_string_lossy().to_string())));
+            }
+        }
+    }
+
+    helpers.extend(HELPERS_BUNDLED.iter().map(|name| info(name, CtGenHelperKind::Bundled, None)));
+
+    let mut plugins = profile.helpers().plugins().collect::<Vec<(&String, &String)>>();
+    plugins.sort();
+
+    helpers.extend(plugins.into_iter().map(|(name, path)| {
+        info(
+            name,
+            CtGenHelperKind::Plugin,
+            Some(CtGen::get_filepath(profile.context_dir(), path)),
+        )
+    }));
+
+    helpers
+}
+
+/// Init rhai engine with all additional packages registered
+pub fn init_engine() -> Engine {
+    let mut rhai_engine = Engine::new();
+
+    // register rhai-chrono
+    let rhai_chrono = ChronoPackage::new();
+    rhai_chrono.register_into_engine(&mut rhai_engine);
+
+    // register rhai-sci
+    let rhai_sci = SciPackage::new();
+    rhai_sci.register_into_engine(&mut rhai_engine);
+
+    // register rhai-fs
+    let rhai_fs = FilesystemPackage::new();
+    rhai_fs.register_into_engine(&mut rhai_engine);
+
+    // register rhai-url
+    let rhai_url = UrlPackage::new();
+    rhai_url.register_into_engine(&mut rhai_engine);
+
+    rhai_engine
+}
+
+/// Get all rhai scripts inside the scripts dir as pairs of helper name and script path. Helper test scripts are left out
+pub fn script_helpers(scripts_dir: &str) -> Vec<(String, PathBuf)> {
+    script_files(scripts_dir)
+        .into_iter()
+        .filter(|(script_name, _)| !script_name.ends_with(SCRIPT_TEST_SUFFIX))
+        .collect()
+}
+
+/// Get all helper test scripts, named like `case_test.rhai`, inside the scripts dir as pairs of test name and script path
+pub fn script_tests(scripts_dir: &str) -> Vec<(String, PathBuf)> {
+    script_files(scripts_dir)
+        .into_iter()
+        .filter(|(script_name, _)| script_name.ends_with(SCRIPT_TEST_SUFFIX))
+        .collect()
+}
+
+/// Get all rhai scripts inside the scripts dir as pairs of canonical name and script path
+fn script_files(scripts_dir: &str) -> Vec<(String, PathBuf)> {
+    let walker = WalkDir::new(scripts_dir);
+    walker
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok().map(|e| e.into_path()))
+        .filter(|tpl_path| tpl_path.to_string_lossy().ends_with(FILE_EXT_RHAI))
+        .filter(|tpl_path| {
+            tpl_path
+                .file_stem()
+                .map(|stem| !stem.to_string_lossy().starts_with('.'))
+                .unwrap_or(false)
+        })
+        .filter_map(|script_path| {
+            script_path
+                .strip_prefix(scripts_dir)
+                .ok()
+                .map(|script_canonical_name| {
+                    let script_name = script_canonical_name
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+
+                    script_name.strip_suffix(FILE_EXT_RHAI).map(|s| s.to_owned()).unwrap_or(script_name)
+                })
+                .map(|script_canonical_name| (script_canonical_name, script_path))
+        })
+        .collect()
+}
+
+/// Get all templates inside the templates dir as pairs of template name and template path. Hidden files are skipped
+pub fn template_files(templates_dir: &str) -> Vec<(String, PathBuf)> {
+    let walker = WalkDir::new(templates_dir);
+    walker
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok().map(|e| e.into_path()))
+        .filter(|tpl_path| tpl_path.to_string_lossy().ends_with(FILE_EXT_HBS))
+        .filter(|tpl_path| {
+            tpl_path
+                .file_stem()
+                .map(|stem| !stem.to_string_lossy().starts_with('.'))
+                .unwrap_or(false)
+        })
+        .filter_map(|template_path| {
+            template_path
+                .strip_prefix(templates_dir)
+                .ok()
+                .map(|template_canonical_name| {
+                    let template_name = template_canonical_name
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+
+                    template_name
+                        .strip_suffix(FILE_EXT_HBS)
+                        .map(|s| s.to_owned())
+                        .unwrap_or(template_name)
+                })
+                .map(|template_canonical_name| (template_canonical_name, template_path))
+        })
+        .collect()
+}
+
+/// Helper names the profile memoizes, and the cache they share
+struct Memoize<'a> {
+    names: &'a [String],
+    cache: &'a MemoizeCache,
+}
+
+/// Register a helper, wrapped in a memoization layer if the profile asks for it
+fn register_helper(handlebars: &mut Handlebars, name: &str, helper: Box<dyn HelperDef + Send + Sync>, memoize: &Memoize) {
+    if memoize.names.iter().any(|memoized| memoized == name) {
+        handlebars.register_helper(name, Box::new(MemoizedHelper::new(helper, memoize.cache.clone())));
+    } else {
+        handlebars.register_helper(name, helper);
+    }
+}
+
+/// Register ctgen bundled helpers, memoized ones sharing the given cache
+pub fn register_helpers(handlebars: &mut Handlebars, profile: &CtGenProfile, cache: &MemoizeCache) {
+    let memoize = &Memoize {
+        names: profile.helpers().memoize(),
+        cache,
+    };
+
+    register_helper(handlebars, "concat", Box::new(HandlebarsConcat), memoize);
+    register_helper(handlebars, "inflect", Box::new(HandlebarsInflector), memoize);
+    register_helper(handlebars, "datetime", Box::new(HandlebarsChronoDateTime), memoize);
+
+    handlebars_helper!(json: |input: Value| serde_json::to_string(&input).unwrap_or(String::from("{}")));
+    register_helper(handlebars, "json", Box::new(json), memoize);
+    register_helper(handlebars, "jsonpath", Box::new(JsonPathHelper), memoize);
+    register_helper(handlebars, "filter", Box::new(FilterHelper), memoize);
+    register_helper(handlebars, "map", Box::new(MapHelper), memoize);
+    register_helper(handlebars, "sort_by", Box::new(SortByHelper), memoize);
+
+    handlebars_helper!(to_ascii: |input: str| deunicode::deunicode(input));
+    register_helper(handlebars, "to_ascii", Box::new(to_ascii), memoize);
+    handlebars_helper!(to_identifier: |input: str, { separator: str = "_" }| helpers::to_identifier(input, separator));
+    register_helper(handlebars, "to_identifier", Box::new(to_identifier), memoize);
+    handlebars_helper!(rust_ident: |input: str| helpers::rust_ident(input));
+    register_helper(handlebars, "rust_ident", Box::new(rust_ident), memoize);
+
+    register_helper(
+        handlebars,
+        "include_file",
+        Box::new(IncludeFileHelper::new(profile.context_dir())),
+        memoize,
+    );
+    register_exec(handlebars, profile, None, cache);
+    register_dialect(handlebars, profile, CtGenSqlDialect::default(), cache);
+}
+
+/// Register `exec` to run allowed commands in the working directory of formatters and hooks, `None` inheriting the
+/// process one
+pub fn register_exec(handlebars: &mut Handlebars, profile: &CtGenProfile, command_dir: Option<&str>, cache: &MemoizeCache) {
+    register_helper(
+        handlebars,
+        "exec",
+        Box::new(ExecHelper::new(profile.helpers().exec(), command_dir)),
+        &Memoize {
+            names: profile.helpers().memoize(),
+            cache,
+        },
+    );
+}
+
+/// Register `quote_ident` for the dialect of the reflected server, unless the profile sets its own
+pub fn register_dialect(handlebars: &mut Handlebars, profile: &CtGenProfile, server_dialect: CtGenSqlDialect, cache: &MemoizeCache) {
+    register_helper(
+        handlebars,
+        "quote_ident",
+        Box::new(QuoteIdentHelper::new(profile.helpers().dialect().unwrap_or(server_dialect))),
+        &Memoize {
+            names: profile.helpers().memoize(),
+            cache,
+        },
+    );
+}
+
+/// Convert a template written with alternate delimiters to regular handlebars syntax. Only matched `open … close` pairs
+/// are converted, so a close sequence in the surrounding code is kept as is
+pub fn convert_delimiters(content: &str, open: &str, close: &str) -> String {
+    // literal handlebars expressions are escaped, so they end up verbatim in the output
+    let escape = |text: &str| text.replace("{{", "\\{{");
+
+    let mut converted = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find(open) {
+        let expression_start = start + open.len();
+
+        let Some(length) = rest[expression_start..].find(close) else {
+            break;
+        };
+
+        converted.push_str(&escape(&rest[..start]));
+        converted.push_str("{{");
+        converted.push_str(&rest[expression_start..expression_start + length]);
+        converted.push_str("}}");
+
+        rest = &rest[expression_start + length + close.len()..];
+    }
+
+    converted.push_str(&escape(rest));
+
+    converted
+}
+
+/// Registered name of a template converted from alternate delimiters
+pub fn delimited_template_name(template: &str, open: &str, close: &str) -> String {
+    format!("{}[{} {}]", template, open, close)
+}
+
+/// Parse the rendered enumerate template of a target into items. A JSON array yields its elements, anything else is
+/// split by `,` into trimmed, non-empty strings
+pub fn enumerate_items(rendered: &str) -> Vec<Value> {
+    if let Ok(Value::Array(items)) = serde_json::from_str::<Value>(rendered) {
+        return items;
+    }
+
+    rendered
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(Value::from)
+        .collect()
+}
+
+/// Turn missing variable errors of strict rendering into validation errors naming the template and line
+pub fn render_error(e: RenderError) -> anyhow::Error {
+    if let RenderErrorReason::MissingVariable(path) = e.reason() {
+        return CtGenError::ValidationError(format!(
+            "Missing variable `{}` in template {} at line {}",
+            path.as_deref().unwrap_or_default(),
+            e.template_name.as_deref().unwrap_or("(inline)"),
+            e.line_no.map(|line| line.to_string()).unwrap_or_else(|| "?".to_string())
+        ))
+        .into();
+    }
+
+    e.into()
+}
+
+/// Init renderer with profile templates, profile scripts and bundled helpers
+pub fn init_renderer<'reg>(profile: &CtGenProfile) -> Result<Handlebars<'reg>> {
+    init_memoized_renderer(profile, &MemoizeCache::default())
+}
+
+/// Init renderer with profile templates, profile scripts and bundled helpers, memoized helpers sharing the given cache
+pub fn init_memoized_renderer<'reg>(profile: &CtGenProfile, cache: &MemoizeCache) -> Result<Handlebars<'reg>> {
+    let mut handlebars = Handlebars::new();
+
+    handlebars.set_engine(init_engine());
+    handlebars.set_strict_mode(profile.configuration().strict());
+
+    // pack templates and scripts are registered first, so the profile can override them
+    let packs = profile.packs()?;
+
+    let templates_dirs = packs
+        .iter()
+        .map(CtGenPack::templates_dir)
+        .chain(std::iter::once(profile.templates_dir()));
+    let scripts_dirs = packs
+        .iter()
+        .map(CtGenPack::scripts_dir)
+        .chain(std::iter::once(profile.scripts_dir()))
+        .collect::<Vec<String>>();
+
+    // templates are registered with their front matter stripped. Registered paths are kept for converted copies
+    let mut template_paths = HashMap::new();
+
+    for (template_name, template_path) in templates_dirs.flat_map(|templates_dir| template_files(&templates_dir)) {
+        let content = std::fs::read_to_string(&template_path)
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to read template file {}: {}", template_path.display(), e)))?;
+
+        handlebars.register_template_string(&template_name, frontmatter::strip(&content))?;
+
+        template_paths.insert(template_name, template_path);
+    }
+
+    let memoize = &Memoize {
+        names: profile.helpers().memoize(),
+        cache,
+    };
+
+    for (script_canonical_name, script_path) in scripts_dirs.iter().flat_map(|scripts_dir| script_helpers(scripts_dir)) {
+        if memoize.names.contains(&script_canonical_name) {
+            // memoized scripts are compiled here, so they can be wrapped
+            let script = handlebars
+                .engine()
+                .compile_file(script_path)
+                .map_err(|e| CtGenError::RuntimeError(format!("Failed to compile script {}: {}", script_canonical_name, e)))?;
+
+            handlebars.register_helper(
+                &script_canonical_name,
+                Box::new(MemoizedHelper::new(Box::new(CompiledScriptHelper::new(script)), cache.clone())),
+            );
+        } else {
+            handlebars.register_script_helper_file(&script_canonical_name, script_path)?;
+        }
+    }
+
+    register_helpers(&mut handlebars, profile, cache);
+
+    for (plugin_name, plugin_path) in profile.helpers().plugins() {
+        let plugin_file = CtGen::get_filepath(profile.context_dir(), plugin_path);
+
+        let plugin = WasmPluginHelper::load(&plugin_file)
+            .map_err(|e| CtGenError::RuntimeError(format!("Failed to load plugin {}: {}", plugin_name, e)))?;
+
+        register_helper(&mut handlebars, plugin_name, Box::new(plugin), memoize);
+    }
+
+    // register converted copies of templates used by targets with alternate delimiters
+    for target in profile
+        .targets()
+        .filter_map(|target_name| profile.target(target_name))
+        .filter(|target| !target.is_copy())
+    {
+        if let Some((open, close)) = target.delimiters() {
+            // language variants of the template are converted too
+            let templates = std::iter::once(target.template().to_string()).chain(
+                profile
+                    .configuration()
+                    .languages()
+                    .iter()
+                    .map(|language| format!("{}/{}", language, target.template())),
+            );
+
+            for template in templates {
+                let Some(template_path) = template_paths.get(&template) else {
+                    continue;
+                };
+
+                let content = std::fs::read_to_string(template_path)
+                    .map_err(|e| CtGenError::RuntimeError(format!("Failed to read template file {}: {}", template_path.display(), e)))?;
+
+                handlebars.register_template_string(
+                    &delimited_template_name(&template, open, close),
+                    convert_delimiters(frontmatter::strip(&content), open, close),
+                )?;
+            }
+        }
+    }
+
+    Ok(handlebars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(template: &str, data: &Value) -> String {
+        let mut handlebars = Handlebars::new();
+        register_helpers(&mut handlebars, &CtGenProfile::default(), &MemoizeCache::default());
+
+        handlebars.render_template(template, data).unwrap()
+    }
+
+    #[test]
+    fn non_ascii_table_names_render_as_identifiers() {
+        let context = json!({"table": {"name": "Заказы клиентов"}});
+
+        assert_eq!(render("{{to_ascii table/name}}", &context), "Zakazy klientov");
+        assert_eq!(
+            render("models/{{to_identifier table/name}}.rs", &context),
+            "models/Zakazy_klientov.rs"
+        );
+        assert_eq!(
+            render("{{inflect (to_identifier table/name) to_pascal_case=true}}", &context),
+            "ZakazyKlientov"
+        );
+    }
+
+    #[test]
+    fn non_ascii_column_names_render_with_separator() {
+        let context = json!({"column": {"name": "Größe in mm"}});
+
+        assert_eq!(render("{{to_identifier column/name separator=\"-\"}}", &context), "Grosse-in-mm");
+    }
+
+    #[test]
+    fn non_ascii_names_are_valid() {
+        let regex = CtGen::get_name_regex();
+
+        assert!(regex.is_match("größe"));
+        assert!(regex.is_match("заказы_клиентов"));
+        assert!(regex.is_match("café-crème"));
+        assert!(!regex.is_match("größe in mm"));
+        assert!(!regex.is_match("../model"));
+    }
+
+    #[test]
+    fn converts_only_matched_delimiter_pairs() {
+        assert_eq!(
+            convert_delimiters("let a = b[c[0]]; [[ name ]] {{ literal }}", "[[", "]]"),
+            "let a = b[c[0]]; {{ name }} \\{{ literal }}"
+        );
+        assert_eq!(
+            convert_delimiters("<% name %> 10 %> 5 <% open", "<%", "%>"),
+            "{{ name }} 10 %> 5 <% open"
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::defaults::CtGenDefaults;
+use console::{style, Style, StyledObject};
+use dialoguer::theme::{ColorfulTheme, SimpleTheme, Theme};
+
+/// CLI theme built from user defaults, shared by prompts and info/fail labels
+pub struct CtGenCliTheme {
+    /// Styled glyph in front of info messages
+    info: StyledObject<String>,
+    /// Styled glyph in front of failure messages
+    fail: StyledObject<String>,
+    /// Prompt theme
+    prompts: Box<dyn Theme + Send + Sync>,
+}
+
+impl CtGenCliTheme {
+    /// Build theme from user defaults
+    pub fn new(defaults: &CtGenDefaults) -> Self {
+        let config = defaults.style();
+        let ascii = config.ascii();
+
+        let glyph = |custom: Option<&str>, unicode: &str, fallback: &str| {
+            custom
+                .map(str::to_string)
+                .unwrap_or_else(|| if ascii { fallback } else { unicode }.to_string())
+        };
+
+        let info_glyph = glyph(config.info_glyph(), "❯", ">");
+        let fail_glyph = glyph(config.fail_glyph(), "?", "?");
+
+        if defaults.theme() == Some("simple") {
+            return Self {
+                info: style(info_glyph),
+                fail: style(fail_glyph),
+                prompts: Box::new(SimpleTheme),
+            };
+        }
+
+        let mut prompts = ColorfulTheme {
+            prompt_prefix: style(glyph(config.prompt_glyph(), "?", "?")).for_stderr().yellow(),
+            success_prefix: style(glyph(config.success_glyph(), "✔", "+")).for_stderr().green(),
+            error_prefix: style(glyph(config.error_glyph(), "✘", "x")).for_stderr().red(),
+            ..ColorfulTheme::default()
+        };
+
+        if ascii {
+            prompts.prompt_suffix = style(">".to_string()).for_stderr().black().bright();
+            prompts.success_suffix = style("-".to_string()).for_stderr().black().bright();
+            prompts.active_item_prefix = style(">".to_string()).for_stderr().green();
+            prompts.checked_item_prefix = style("[x]".to_string()).for_stderr().green();
+            prompts.unchecked_item_prefix = style("[ ]".to_string()).for_stderr().magenta();
+            prompts.picked_item_prefix = style(">".to_string()).for_stderr().green();
+        }
+
+        Self {
+            info: Style::from_dotted_str(config.info_color().unwrap_or("green"))
+                .for_stderr()
+                .apply_to(info_glyph),
+            fail: Style::from_dotted_str(config.fail_color().unwrap_or("yellow"))
+                .for_stderr()
+                .apply_to(fail_glyph),
+            prompts: Box::new(prompts),
+        }
+    }
+
+    /// Styled glyph in front of info messages
+    pub fn info(&self) -> &StyledObject<String> {
+        &self.info
+    }
+
+    /// Styled glyph in front of failure messages
+    pub fn fail(&self) -> &StyledObject<String> {
+        &self.fail
+    }
+
+    /// Prompt theme
+    pub fn prompts(&self) -> &dyn Theme {
+        self.prompts.as_ref()
+    }
+}
@@ -0,0 +1,203 @@
+use crate::manifest::{CtGenFileStatus, CtGenManifest};
+use crate::profile::CtGenProfile;
+use crate::task::CtGenTask;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// State of a generated file compared to its manifest record and the current render plan
+pub enum CtGenVerifyStatus {
+    /// File matches its record and, when planned, the current render
+    UpToDate,
+    /// File is untouched since generation, but the current render differs
+    Outdated,
+    /// File was edited by hand since generation
+    Modified,
+    /// File is recorded or planned, but not on disk
+    Missing,
+    /// File is recorded, but no target produces it anymore
+    Orphaned,
+    /// File is planned and on disk, but has no record and differs from the current render
+    Untracked,
+}
+
+impl Display for CtGenVerifyStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CtGenVerifyStatus::UpToDate => write!(f, "up-to-date"),
+            CtGenVerifyStatus::Outdated => write!(f, "outdated"),
+            CtGenVerifyStatus::Modified => write!(f, "modified"),
+            CtGenVerifyStatus::Missing => write!(f, "missing"),
+            CtGenVerifyStatus::Orphaned => write!(f, "orphaned"),
+            CtGenVerifyStatus::Untracked => write!(f, "untracked"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Verification of a single generated file
+pub struct CtGenVerifyFile {
+    /// Output file path relative to the target dir
+    file: String,
+    /// Target name that generated or plans the file
+    target: String,
+    /// Table the file was generated or planned for
+    table: Option<String>,
+    /// Verification state
+    status: CtGenVerifyStatus,
+}
+
+impl CtGenVerifyFile {
+    /// Output file path relative to the target dir
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+    /// Target name that generated or plans the file
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+    /// Table the file was generated or planned for
+    pub fn table(&self) -> Option<&str> {
+        self.table.as_deref()
+    }
+    /// Verification state
+    pub fn status(&self) -> CtGenVerifyStatus {
+        self.status
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// Verification of the files a profile generated into a target dir
+pub struct CtGenVerifyReport {
+    /// Target dir of the verified files
+    target_dir: String,
+    /// Table whose render plan the files were compared with
+    table: Option<String>,
+    /// Verified files, sorted by file
+    files: Vec<CtGenVerifyFile>,
+}
+
+impl CtGenVerifyReport {
+    /// Target dir of the verified files
+    pub fn target_dir(&self) -> &str {
+        &self.target_dir
+    }
+    /// Table whose render plan the files were compared with
+    pub fn table(&self) -> Option<&str> {
+        self.table.as_deref()
+    }
+    /// Verified files, sorted by file
+    pub fn files(&self) -> &Vec<CtGenVerifyFile> {
+        &self.files
+    }
+
+    /// Check if every verified file is up to date
+    pub fn is_verified(&self) -> bool {
+        self.files.iter().all(|file| file.status == CtGenVerifyStatus::UpToDate)
+    }
+}
+
+impl Display for CtGenVerifyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for file in self.files.iter().filter(|file| file.status != CtGenVerifyStatus::UpToDate) {
+            writeln!(f, "{:>10} {} ({})", file.status, file.file, file.target)?;
+        }
+
+        let verified = self.files.iter().filter(|file| file.status == CtGenVerifyStatus::UpToDate).count();
+
+        write!(
+            f,
+            "{} files, {} up to date, {} failed",
+            self.files.len(),
+            verified,
+            self.files.len() - verified
+        )
+    }
+}
+
+/// Verify the files a profile generated, as recorded by the manifest. With a task, the render plan of its table is computed
+/// and compared with the files on disk, like check mode does, to tell outdated files from up to date ones
+pub async fn verify(manifest: &CtGenManifest, profile: &CtGenProfile, task: Option<&CtGenTask>) -> Result<CtGenVerifyReport> {
+    let table = task.and_then(|task| task.table());
+
+    let (outputs, outdated) = if let Some(task) = task {
+        let outputs = task.plan().await?;
+        let outdated = task
+            .check(&outputs)
+            .await?
+            .iter()
+            .map(|output| output.file().to_string())
+            .collect::<BTreeSet<String>>();
+
+        (outputs, outdated)
+    } else {
+        (Vec::new(), BTreeSet::new())
+    };
+
+    let planned = outputs.iter().map(|output| output.file()).collect::<Vec<&str>>();
+
+    let orphaned = manifest.stale(
+        profile.name(),
+        profile.configuration().targets(),
+        table.map(|table| (table, planned.as_slice())),
+    );
+
+    let mut files = BTreeMap::new();
+
+    for (file, entry) in manifest.files().filter(|(_, entry)| entry.profile() == profile.name()) {
+        let status = if orphaned.contains(file) {
+            CtGenVerifyStatus::Orphaned
+        } else {
+            match manifest.status(file).await? {
+                CtGenFileStatus::Missing => CtGenVerifyStatus::Missing,
+                CtGenFileStatus::Modified => CtGenVerifyStatus::Modified,
+                CtGenFileStatus::Unchanged if outdated.contains(file) => CtGenVerifyStatus::Outdated,
+                CtGenFileStatus::Unchanged => CtGenVerifyStatus::UpToDate,
+            }
+        };
+
+        files.insert(
+            file.clone(),
+            CtGenVerifyFile {
+                file: file.clone(),
+                target: entry.target().to_string(),
+                table: entry.table().map(str::to_string),
+                status,
+            },
+        );
+    }
+
+    // planned files generated before manifests existed, or never generated. Targets printing to stdout have no file
+    for output in outputs.iter().filter(|output| !output.is_stdout()) {
+        if files.contains_key(output.file()) {
+            continue;
+        }
+
+        let status = if !output.overwrite() {
+            CtGenVerifyStatus::Missing
+        } else if outdated.contains(output.file()) {
+            CtGenVerifyStatus::Untracked
+        } else {
+            CtGenVerifyStatus::UpToDate
+        };
+
+        files.insert(
+            output.file().to_string(),
+            CtGenVerifyFile {
+                file: output.file().to_string(),
+                target: output.name().to_string(),
+                table: table.map(str::to_string),
+                status,
+            },
+        );
+    }
+
+    Ok(CtGenVerifyReport {
+        target_dir: manifest.target_dir().to_string(),
+        table: table.map(str::to_string),
+        files: files.into_values().collect(),
+    })
+}